@@ -2,7 +2,7 @@
 // Exponential-Golomb conding: https://en.wikipedia.org/wiki/Exponential-Golomb_coding
 //
 
-use std::io;
+use std::io::{self, Read};
 
 use bitstream_io::BitRead;
 
@@ -23,6 +23,96 @@ pub fn ebsp_to_rbsp(ebsp: &[u8]) -> Vec<u8> {
     rbsp
 }
 
+/// Converts an offset in the RBSP of `ebsp` into the offset in `ebsp` itself, i.e. counting the emulation prevention
+/// bytes before it.
+///
+/// E.g. for `SliceSegmentHeader::slice_segment_data_offset`, `ebsp` is the NAL unit following the NAL unit header.
+pub fn rbsp_offset_to_ebsp_offset(ebsp: &[u8], rbsp_offset: usize) -> usize {
+    let mut rbsp_count = 0;
+    let mut zero_count = 0;
+    for (i, &byte) in ebsp.iter().enumerate() {
+        if byte == 3 && zero_count >= 2 {
+            // `emulation_prevention_three_byte`
+            zero_count = 0;
+            continue;
+        }
+        if rbsp_count == rbsp_offset {
+            return i;
+        }
+        zero_count = if byte == 0 { zero_count + 1 } else { 0 };
+        rbsp_count += 1;
+    }
+    ebsp.len()
+}
+
+/// A `Read` over _EBSP(Encapsulated Byte Sequence Payload)_ that yields the _RBSP(Raw Byte Sequence Payload)_, skipping
+/// emulation prevention bytes on the fly like `ebsp_to_rbsp()` without copying the whole payload.
+#[derive(Debug, Clone)]
+pub struct RbspReader<'a> {
+    ebsp: &'a [u8],
+    /// The number of `0x00` bytes immediately preceding `ebsp`.
+    zero_count: usize,
+}
+
+impl<'a> RbspReader<'a> {
+    pub fn new(ebsp: &'a [u8]) -> Self {
+        Self {
+            ebsp,
+            zero_count: 0,
+        }
+    }
+}
+
+impl Read for RbspReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut read = 0;
+        while let (Some(dst), Some((&byte, rest))) = (buf.get_mut(read), self.ebsp.split_first()) {
+            self.ebsp = rest;
+            if byte == 3 && self.zero_count >= 2 {
+                // `emulation_prevention_three_byte`
+                self.zero_count = 0;
+                continue;
+            }
+            self.zero_count = if byte == 0 { self.zero_count + 1 } else { 0 };
+            *dst = byte;
+            read += 1;
+        }
+        Ok(read)
+    }
+}
+
+/// A `Read` that counts the number of bytes read through it.
+///
+/// Useful for finding out how many bytes a `BitReader` has consumed from a reader that does not implement `Seek`.
+#[derive(Debug)]
+pub struct CountingReader<R> {
+    inner: R,
+    count: usize,
+}
+
+impl<R> CountingReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self { inner, count: 0 }
+    }
+
+    /// The number of bytes read so far.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.count += read;
+        Ok(read)
+    }
+}
+
 /// Parses an unsigned 0-th order Exp-Golomb code.
 ///
 /// See _9.2 Parsing process for 0-th order Exp-Golomb codes_ in the H.265/HEVC spec.
@@ -56,6 +146,105 @@ pub fn read_exp_golomb_se<R: BitRead>(reader: &mut R) -> Result<i32, io::Error>
     }
 }
 
+/// Writes `value` as `ue(v)`, for building bitstreams in tests.
+#[cfg(test)]
+pub(crate) fn write_exp_golomb_ue<W: bitstream_io::BitWrite>(bit_writer: &mut W, value: u32) {
+    let code = value as u64 + 1;
+    let length = 64 - code.leading_zeros();
+    bit_writer.write(length - 1, 0u32).unwrap();
+    bit_writer.write(length, code).unwrap();
+}
+
+/// Inserts emulation prevention bytes into `rbsp` as the inverse of `ebsp_to_rbsp()`, for tests.
+#[cfg(test)]
+pub(crate) fn rbsp_to_ebsp(rbsp: &[u8]) -> Vec<u8> {
+    let mut ebsp: Vec<u8> = Vec::with_capacity(rbsp.len());
+    let mut zero_count = 0;
+    for &byte in rbsp {
+        if zero_count >= 2 && byte <= 3 {
+            // `emulation_prevention_three_byte`
+            ebsp.push(3);
+            zero_count = 0;
+        }
+        zero_count = if byte == 0 { zero_count + 1 } else { 0 };
+        ebsp.push(byte);
+    }
+    ebsp
+}
+
+/// A `BitWriter` building a bitstream in a `Vec<u8>`, for tests.
+#[cfg(test)]
+pub(crate) type TestBitWriter<'a> =
+    bitstream_io::BitWriter<&'a mut Vec<u8>, bitstream_io::BigEndian>;
+
+/// Builds the RBSP of a Main profile 1920x1080 SPS of `sps_seq_parameter_set_id`, for tests.
+///
+/// With `pcm_enabled_flag`, `pcm_sample_bit_depth_luma_minus1 = 7`, `pcm_sample_bit_depth_chroma_minus1 = 6`,
+/// `log2_min_pcm_luma_coding_block_size_minus3 = 1`, `log2_diff_max_min_pcm_luma_coding_block_size = 2` and
+/// `pcm_loop_filter_disabled_flag = 1` follow. `write_vui_parameters` writes `vui_parameters()` if any.
+#[cfg(test)]
+pub(crate) fn test_sps_rbsp(
+    sps_seq_parameter_set_id: u32,
+    pcm_enabled_flag: bool,
+    write_vui_parameters: Option<&dyn Fn(&mut TestBitWriter)>,
+) -> Vec<u8> {
+    use bitstream_io::{BigEndian, BitWrite, BitWriter};
+
+    let mut rbsp: Vec<u8> = Vec::new();
+    {
+        let mut bit_writer = BitWriter::endian(&mut rbsp, BigEndian);
+        // sps_video_parameter_set_id = 0, sps_max_sub_layers_minus1 = 0, sps_temporal_id_nesting_flag = 1
+        bit_writer.write(8, 0b0000_0001u8).unwrap();
+        // general_profile_idc = 1, general_profile_compatibility_flag[1..=2], general_progressive_source_flag,
+        // general_frame_only_constraint_flag, general_reserved_zero_43bits, general_inbld_flag, general_level_idc
+        bit_writer.write(8, 1u8).unwrap();
+        bit_writer.write(32, 0x6000_0000u32).unwrap();
+        bit_writer.write(48, 0x9000_0000_0000u64).unwrap();
+        bit_writer.write(8, 93u8).unwrap();
+        // sps_seq_parameter_set_id, chroma_format_idc, pic_width_in_luma_samples, pic_height_in_luma_samples
+        for value in [sps_seq_parameter_set_id, 1, 1920, 1080] {
+            write_exp_golomb_ue(&mut bit_writer, value);
+        }
+        // conformance_window_flag = 0
+        bit_writer.write_bit(false).unwrap();
+        // bit_depth_luma_minus8, bit_depth_chroma_minus8, log2_max_pic_order_cnt_lsb_minus4
+        for value in [0, 0, 4] {
+            write_exp_golomb_ue(&mut bit_writer, value);
+        }
+        // sps_sub_layer_ordering_info_present_flag = 1
+        bit_writer.write_bit(true).unwrap();
+        // The sub-layer ordering info and the coding/transform block sizes and hierarchy depths.
+        for value in [4, 2, 0, 0, 3, 0, 3, 0, 0] {
+            write_exp_golomb_ue(&mut bit_writer, value);
+        }
+        // scaling_list_enabled_flag = 0, amp_enabled_flag = 1, sample_adaptive_offset_enabled_flag = 1
+        bit_writer.write(3, 0b011u8).unwrap();
+        bit_writer.write_bit(pcm_enabled_flag).unwrap();
+        if pcm_enabled_flag {
+            bit_writer.write(4, 7u8).unwrap();
+            bit_writer.write(4, 6u8).unwrap();
+            write_exp_golomb_ue(&mut bit_writer, 1);
+            write_exp_golomb_ue(&mut bit_writer, 2);
+            bit_writer.write_bit(true).unwrap();
+        }
+        // num_short_term_ref_pic_sets = 0
+        write_exp_golomb_ue(&mut bit_writer, 0);
+        // long_term_ref_pics_present_flag = 0, sps_temporal_mvp_enabled_flag = 1,
+        // strong_intra_smoothing_enabled_flag = 1
+        bit_writer.write(3, 0b011u8).unwrap();
+        bit_writer
+            .write_bit(write_vui_parameters.is_some())
+            .unwrap();
+        if let Some(write_vui_parameters) = write_vui_parameters {
+            write_vui_parameters(&mut bit_writer);
+        }
+        // sps_extension_present_flag = 0, rbsp_trailing_bits()
+        bit_writer.write(2, 0b01u8).unwrap();
+        bit_writer.byte_align().unwrap();
+    }
+    rbsp
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -93,6 +282,36 @@ mod tests {
         assert_eq!(de_exp_golomb_se(27, 0b00111), -3);
     }
 
+    #[test]
+    fn rbsp_offset_to_ebsp_offset_works() {
+        let ebsp = [0x12, 0x00, 0x00, 0x03, 0x01, 0x00, 0x00, 0x03, 0x03, 0x80];
+        assert_eq!(rbsp_offset_to_ebsp_offset(&ebsp, 0), 0);
+        assert_eq!(rbsp_offset_to_ebsp_offset(&ebsp, 3), 4);
+        assert_eq!(rbsp_offset_to_ebsp_offset(&ebsp, 6), 8);
+        assert_eq!(rbsp_offset_to_ebsp_offset(&ebsp, 7), 9);
+        assert_eq!(rbsp_offset_to_ebsp_offset(&ebsp, 8), 10);
+    }
+
+    #[test]
+    fn rbsp_reader_works() {
+        // Emulation prevention bytes are skipped even if the preceding zeros were returned by earlier `read()`s.
+        let ebsp = [0x00, 0x00, 0x03, 0x01, 0x00, 0x00, 0x03, 0x00, 0x00, 0x03];
+        let mut rbsp_reader = RbspReader::new(&ebsp);
+        let mut rbsp: Vec<u8> = Vec::new();
+        let mut buf = [0; 1];
+        while rbsp_reader.read(&mut buf).unwrap() == 1 {
+            rbsp.push(buf[0]);
+        }
+        assert_eq!(rbsp, [0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00]);
+        assert_eq!(rbsp, ebsp_to_rbsp(&ebsp));
+
+        let mut rbsp_reader = RbspReader::new(&ebsp);
+        let mut buf = [0; 2];
+        assert_eq!(rbsp_reader.read(&mut buf).unwrap(), 2);
+        assert_eq!(rbsp_reader.read(&mut buf).unwrap(), 2);
+        assert_eq!(buf, [0x01, 0x00]);
+    }
+
     fn de_exp_golomb_ue(skip: u8, seq: u32) -> u32 {
         let mut reader = io::Cursor::new(seq.to_be_bytes());
         let mut bit_reader = BitReader::endian(&mut reader, BigEndian);
@@ -3,8 +3,15 @@ pub mod nalu;
 pub mod poc;
 pub mod ptl;
 pub mod rps;
+pub mod scaling_list;
+pub mod hrd;
 pub mod vps;
+pub mod vps_extension;
 pub mod sps;
 pub mod pps;
+pub mod sei;
+pub mod dolby_vision;
 pub mod slice;
 pub mod nalu_ref;
+pub mod nalu_slice;
+pub mod hvcc;
@@ -1,6 +1,7 @@
-use std::io::{self, Read, Seek};
+use std::io::{self, Read};
+use std::marker::PhantomData;
 
-use crate::h265::nalu::{NaluValueContext, Nalu};
+use crate::h265::nalu::{NaluValueContext, Nalu, NaluType};
 use crate::h265::nalu_ref::NaluRef;
 
 #[derive(Debug, Clone, Copy)]
@@ -15,6 +16,9 @@ pub type LengthPrefixedByteStreamNaluReader<R> =
 pub type LengthPrefixedByteStreamNaluRefReader<R> =
     LengthPrefixedByteStreamContentReader<R, NaluRefReader>;
 
+pub type AnnexBByteStreamNaluReader<R> = AnnexBByteStreamContentReader<R, NaluReader>;
+pub type AnnexBByteStreamNaluRefReader<R> = AnnexBByteStreamContentReader<R, NaluRefReader>;
+
 impl<R> LengthPrefixedByteStreamNaluReader<R> {
     pub fn with_length_size_minus_one(
         length_size_minus_one: usize,
@@ -43,39 +47,86 @@ impl<R> LengthPrefixedByteStreamNaluRefReader<R> {
     }
 }
 
+impl<R> AnnexBByteStreamNaluReader<R> {
+    pub fn new(inner_reader: R, nalu_value_context: NaluValueContext) -> Self {
+        AnnexBByteStreamContentReader::with_content_reader(
+            inner_reader,
+            NaluReader { nalu_value_context },
+        )
+    }
+}
+
+impl<R> AnnexBByteStreamNaluRefReader<R> {
+    pub fn new(inner_reader: R, nalu_value_context: NaluValueContext) -> Self {
+        AnnexBByteStreamContentReader::with_content_reader(
+            inner_reader,
+            NaluRefReader { nalu_value_context },
+        )
+    }
+}
+
 /// A `ReadContent` that reads `Nalu`s.
 #[derive(Debug)]
 pub struct NaluReader {
     nalu_value_context: NaluValueContext,
 }
 
+impl NaluReader {
+    /// The parameter sets received so far.
+    pub fn nalu_value_context(&self) -> &NaluValueContext {
+        &self.nalu_value_context
+    }
+}
+
 impl<R: Read> ReadContent<R, Nalu> for NaluReader {
     fn read_content(
         &mut self,
         reader: &mut R,
         length: usize,
-        current_offset: usize,
+        _current_offset: usize,
     ) -> Result<(usize, Nalu), io::Error> {
-        let nalu = Nalu::from_reader(reader, length, self.nalu_value_context)?;
+        let nalu = Nalu::from_reader(reader, length, &self.nalu_value_context)?;
+        self.nalu_value_context.update_parameter_sets(&nalu);
         Ok((length, nalu))
     }
 }
 
 /// A `ReadContent` that reads `NaluRef`s.
+///
+/// SPSs are read in full to keep track of them, while only the NAL unit header of the others is.
 #[derive(Debug)]
 pub struct NaluRefReader {
     nalu_value_context: NaluValueContext,
 }
 
-impl<R: Read + Seek> ReadContent<R, NaluRef> for NaluRefReader {
+impl NaluRefReader {
+    /// The SPSs received so far.
+    pub fn nalu_value_context(&self) -> &NaluValueContext {
+        &self.nalu_value_context
+    }
+}
+
+impl<R: Read> ReadContent<R, NaluRef> for NaluRefReader {
     fn read_content(
         &mut self,
         reader: &mut R,
         length: usize,
         current_offset: usize,
     ) -> Result<(usize, NaluRef), io::Error> {
-        let (consumed, nalu_ref) = NaluRef::from_reader(reader, current_offset)?;
-        Ok((consumed, nalu_ref))
+        let mut header_bytes = [0; 2];
+        reader.read_exact(&mut header_bytes)?;
+        let (consumed, nalu_ref) = NaluRef::from_reader(&mut &header_bytes[..], current_offset)?;
+        if nalu_ref.nal_unit_type != NaluType::SpsNut {
+            return Ok((consumed, nalu_ref));
+        }
+
+        let nalu = Nalu::from_reader(
+            &mut header_bytes.as_slice().chain(reader),
+            length,
+            &self.nalu_value_context,
+        )?;
+        self.nalu_value_context.update_parameter_sets(&nalu);
+        Ok((length, nalu_ref))
     }
 }
 
@@ -89,6 +140,9 @@ pub trait ReadContent<R: Read, T> {
     ) -> Result<(usize, T), io::Error>;
 }
 
+/// Reads length-prefixed NAL units as in MP4 samples, from any `Read` such as a socket or a pipe.
+///
+/// Bytes of a NAL unit left unread by the `ReadContent` are skipped by reading them into a scratch buffer.
 #[derive(Debug)]
 pub struct LengthPrefixedByteStreamContentReader<R, CR> {
     length_size_minus_one: usize,
@@ -96,32 +150,26 @@ pub struct LengthPrefixedByteStreamContentReader<R, CR> {
     content_reader: CR,
 }
 
-impl<R: Read + Seek, CR> LengthPrefixedByteStreamContentReader<R, CR> {
+impl<R, CR> LengthPrefixedByteStreamContentReader<R, CR> {
+    pub fn content_reader(&self) -> &CR {
+        &self.content_reader
+    }
+}
+
+impl<R: Read, CR> LengthPrefixedByteStreamContentReader<R, CR> {
     pub fn read_contents_until_eof<T>(&mut self) -> Result<Vec<ByteStreamContent<T>>, io::Error>
     where
-        CR: ReadContent<R, T>,
+        CR: for<'a> ReadContent<io::Take<&'a mut R>, T>,
     {
-        let mut contents = Vec::new();
-
-        let mut current_offset: usize = 0;
-
-        loop {
-            match self.read_content(current_offset) {
-                Ok(bytestream_content) => {
-                    current_offset += bytestream_content.consumed;
-                    contents.push(bytestream_content);
-                }
-                Err(err) => {
-                    if err.kind() == io::ErrorKind::UnexpectedEof {
-                        break;
-                    } else {
-                        return Err(err);
-                    }
-                }
-            }
-        }
+        self.contents().collect()
+    }
 
-        Ok(contents)
+    /// Returns an iterator reading the contents one at a time until EOF.
+    pub fn contents<T>(&mut self) -> ByteStreamContents<'_, Self, T>
+    where
+        CR: for<'a> ReadContent<io::Take<&'a mut R>, T>,
+    {
+        ByteStreamContents::new(self)
     }
 
     pub fn read_content<T>(
@@ -129,28 +177,52 @@ impl<R: Read + Seek, CR> LengthPrefixedByteStreamContentReader<R, CR> {
         current_offset: usize,
     ) -> Result<ByteStreamContent<T>, io::Error>
     where
-        CR: ReadContent<R, T>,
+        CR: for<'a> ReadContent<io::Take<&'a mut R>, T>,
     {
         // tracing::trace!("read_content: @0x{:#08x}", current_offset);
 
         // We expect to get `UnexpectedEof` if there is no more content, which we propagate as-is.
-        let length = self.read_length()?;
+        let length = self.read_length(current_offset)?;
         // tracing::trace!("length: {}", length);
 
         // The offset of the content itself, not the length.
         let current_offset = current_offset + self.length_size_minus_one + 1;
 
-        let (content_consumed, content) =
-            self.content_reader
-                .read_content(&mut self.inner_reader, length, current_offset)?;
+        if length < 2 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "NAL unit of {} bytes @{:#x} is shorter than its header",
+                    length, current_offset
+                ),
+            ));
+        }
+
+        // The content reader may not read beyond the NAL unit.
+        let (content_consumed, content) = self
+            .content_reader
+            .read_content(
+                &mut (&mut self.inner_reader).take(length as u64),
+                length,
+                current_offset,
+            )
+            .map_err(|err| truncated_content_error(err, current_offset))?;
         // tracing::trace!("@0x{:#08x} content_consumed: {}", current_offset, content_consumed);
 
         // Consume remaining bytes if any.
-        let remaining = length - content_consumed;
+        let remaining = length.checked_sub(content_consumed).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Consumed {} bytes of the NAL unit of {} bytes @{:#x}",
+                    content_consumed, length, current_offset
+                ),
+            )
+        })?;
         if remaining > 0 {
             tracing::trace!("Consume remaining {} bytes", remaining);
-            self.inner_reader
-                .seek(io::SeekFrom::Current(remaining.try_into().unwrap()))?;
+            skip_bytes(&mut self.inner_reader, remaining)
+                .map_err(|err| truncated_content_error(err, current_offset))?;
         }
 
         let consumed = self.length_size_minus_one + 1 + length;
@@ -162,10 +234,32 @@ impl<R: Read + Seek, CR> LengthPrefixedByteStreamContentReader<R, CR> {
         })
     }
 
-    fn read_length(&mut self) -> Result<usize, io::Error> {
+    /// Returns `UnexpectedEof` only if the byte stream ends right before the length @`current_offset`.
+    fn read_length(&mut self, current_offset: usize) -> Result<usize, io::Error> {
         let mut buf = [0; 4];
-        self.inner_reader
-            .read_exact(&mut buf[..=self.length_size_minus_one])?;
+        let mut read = 0;
+        while read <= self.length_size_minus_one {
+            match self
+                .inner_reader
+                .read(&mut buf[read..=self.length_size_minus_one])
+            {
+                Ok(0) if read == 0 => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "End of length-prefixed byte stream",
+                    ));
+                }
+                Ok(0) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Truncated NAL unit length @{:#x}", current_offset),
+                    ));
+                }
+                Ok(n) => read += n,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            }
+        }
         let length = buf[..=self.length_size_minus_one]
             .iter()
             .fold(0, |acc, &x| acc << 8 | x as usize);
@@ -173,6 +267,255 @@ impl<R: Read + Seek, CR> LengthPrefixedByteStreamContentReader<R, CR> {
     }
 }
 
+/// Reads NAL units from an _Annex B_ byte stream, i.e. each preceded by a start code, as specified in
+/// _B.2 Byte stream NAL unit syntax and semantics_.
+///
+/// Like `LengthPrefixedByteStreamContentReader`, `R` need not be `Seek`: The NAL units are located by scanning for start
+/// codes in the bytes buffered from `inner_reader`, and each is handed to the `ReadContent` in its own `io::Cursor`.
+#[derive(Debug)]
+pub struct AnnexBByteStreamContentReader<R, CR> {
+    inner_reader: R,
+    content_reader: CR,
+    /// Bytes read from `inner_reader` and not consumed yet.
+    buffer: Vec<u8>,
+    inner_reader_eof: bool,
+}
+
+impl<R, CR> AnnexBByteStreamContentReader<R, CR> {
+    /// The number of bytes read from `inner_reader` at a time.
+    const READ_CHUNK_SIZE: usize = 64 * 1024;
+
+    pub fn with_content_reader(inner_reader: R, content_reader: CR) -> Self {
+        Self {
+            inner_reader,
+            content_reader,
+            buffer: Vec::new(),
+            inner_reader_eof: false,
+        }
+    }
+
+    pub fn content_reader(&self) -> &CR {
+        &self.content_reader
+    }
+}
+
+impl<R: Read, CR> AnnexBByteStreamContentReader<R, CR> {
+    pub fn read_contents_until_eof<T>(&mut self) -> Result<Vec<ByteStreamContent<T>>, io::Error>
+    where
+        CR: for<'a> ReadContent<io::Cursor<&'a [u8]>, T>,
+    {
+        self.contents().collect()
+    }
+
+    /// Returns an iterator reading the contents one at a time until EOF.
+    pub fn contents<T>(&mut self) -> ByteStreamContents<'_, Self, T>
+    where
+        CR: for<'a> ReadContent<io::Cursor<&'a [u8]>, T>,
+    {
+        ByteStreamContents::new(self)
+    }
+
+    /// Reads the next NAL unit, where `current_offset` is the offset in the byte stream following the previous one.
+    ///
+    /// `ByteStreamContent::offset` is the offset of the NAL unit header, and `ByteStreamContent::consumed` includes the
+    /// zero bytes and start code preceding the NAL unit.
+    pub fn read_content<T>(
+        &mut self,
+        current_offset: usize,
+    ) -> Result<ByteStreamContent<T>, io::Error>
+    where
+        CR: for<'a> ReadContent<io::Cursor<&'a [u8]>, T>,
+    {
+        // `leading_zero_8bits`, `trailing_zero_8bits` of the previous NAL unit and `zero_byte`,
+        // followed by `start_code_prefix_one_3bytes`.
+        let start_code_end = loop {
+            let zeros = self.buffer.iter().take_while(|&&x| x == 0).count();
+            match self.buffer.get(zeros) {
+                Some(1) if zeros >= 2 => break zeros + 1,
+                Some(_) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "Annex B byte stream without a start code @{:#x}",
+                            current_offset + zeros
+                        ),
+                    ));
+                }
+                // We expect to get `UnexpectedEof` if there is no more content, which we propagate as-is.
+                None if self.inner_reader_eof => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "End of Annex B byte stream",
+                    ));
+                }
+                None => self.fill_buffer()?,
+            }
+        };
+
+        // The NAL unit ends at the next `0x000000` or `0x000001`, or at the end of the byte stream.
+        let mut search_from = start_code_end;
+        let nal_unit_end = loop {
+            let found = self.buffer[search_from..]
+                .windows(3)
+                .position(|x| matches!(x, [0, 0, 0] | [0, 0, 1]));
+            match found {
+                Some(position) => break search_from + position,
+                None if self.inner_reader_eof => {
+                    // No `trailing_zero_8bits` can follow as the last byte of a NAL unit is never `0x00`.
+                    let non_zero_end = self.buffer[start_code_end..]
+                        .iter()
+                        .rposition(|&x| x != 0)
+                        .map_or(0, |position| position + 1);
+                    break start_code_end + non_zero_end;
+                }
+                None => {
+                    search_from = self.buffer.len().saturating_sub(2).max(start_code_end);
+                    self.fill_buffer()?;
+                }
+            }
+        };
+
+        let length = nal_unit_end - start_code_end;
+        let offset = current_offset + start_code_end;
+        if length == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Empty NAL unit @{:#x}", offset),
+            ));
+        }
+
+        let mut nal_unit_reader = io::Cursor::new(&self.buffer[start_code_end..nal_unit_end]);
+        let (_, content) = self
+            .content_reader
+            .read_content(&mut nal_unit_reader, length, offset)
+            .map_err(|err| truncated_content_error(err, offset))?;
+
+        self.buffer.drain(..nal_unit_end);
+
+        Ok(ByteStreamContent {
+            offset,
+            value: content,
+            consumed: nal_unit_end,
+        })
+    }
+
+    fn fill_buffer(&mut self) -> Result<(), io::Error> {
+        let len = self.buffer.len();
+        self.buffer.resize(len + Self::READ_CHUNK_SIZE, 0);
+        let read = loop {
+            match self.inner_reader.read(&mut self.buffer[len..]) {
+                Ok(read) => break read,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => {
+                    self.buffer.truncate(len);
+                    return Err(err);
+                }
+            }
+        };
+        self.buffer.truncate(len + read);
+        self.inner_reader_eof = read == 0;
+        Ok(())
+    }
+}
+
+/// An iterator over the contents of a byte stream, returned by e.g. `LengthPrefixedByteStreamContentReader::contents`.
+///
+/// Ends at EOF, or after yielding an error other than `UnexpectedEof`.
+#[derive(Debug)]
+pub struct ByteStreamContents<'r, BR, T> {
+    byte_stream_reader: &'r mut BR,
+    current_offset: usize,
+    done: bool,
+    _content: PhantomData<fn() -> T>,
+}
+
+impl<'r, BR, T> ByteStreamContents<'r, BR, T> {
+    fn new(byte_stream_reader: &'r mut BR) -> Self {
+        Self {
+            byte_stream_reader,
+            current_offset: 0,
+            done: false,
+            _content: PhantomData,
+        }
+    }
+
+    fn next_with(
+        &mut self,
+        read_content: impl FnOnce(&mut BR, usize) -> Result<ByteStreamContent<T>, io::Error>,
+    ) -> Option<Result<ByteStreamContent<T>, io::Error>> {
+        if self.done {
+            return None;
+        }
+        match read_content(self.byte_stream_reader, self.current_offset) {
+            Ok(bytestream_content) => {
+                self.current_offset += bytestream_content.consumed;
+                Some(Ok(bytestream_content))
+            }
+            Err(err) => {
+                self.done = true;
+                if err.kind() == io::ErrorKind::UnexpectedEof {
+                    None
+                } else {
+                    Some(Err(err))
+                }
+            }
+        }
+    }
+}
+
+impl<R: Read, CR: for<'a> ReadContent<io::Take<&'a mut R>, T>, T> Iterator
+    for ByteStreamContents<'_, LengthPrefixedByteStreamContentReader<R, CR>, T>
+{
+    type Item = Result<ByteStreamContent<T>, io::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with(|reader, current_offset| reader.read_content(current_offset))
+    }
+}
+
+impl<R: Read, CR: for<'a> ReadContent<io::Cursor<&'a [u8]>, T>, T> Iterator
+    for ByteStreamContents<'_, AnnexBByteStreamContentReader<R, CR>, T>
+{
+    type Item = Result<ByteStreamContent<T>, io::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with(|reader, current_offset| reader.read_content(current_offset))
+    }
+}
+
+/// Turns `UnexpectedEof` from reading the content @`offset` into `InvalidData`, so that it is not taken for the end of
+/// the byte stream.
+fn truncated_content_error(err: io::Error, offset: usize) -> io::Error {
+    if err.kind() != io::ErrorKind::UnexpectedEof {
+        return err;
+    }
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Truncated content @{:#x}: {}", offset, err),
+    )
+}
+
+/// Skips `count` bytes of `reader` by reading them into a scratch buffer, as `reader` need not be `Seek`.
+fn skip_bytes<R: Read>(reader: &mut R, count: usize) -> Result<(), io::Error> {
+    let skipped = io::copy(&mut reader.take(count as u64), &mut io::sink())?;
+    if skipped < count as u64 {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!("Skipped only {} of {} bytes", skipped, count),
+        ));
+    }
+    Ok(())
+}
+
+pub fn parse_nalus_annex_b(
+    annex_b_byte_stream: &[u8],
+    nalu_value_context: NaluValueContext,
+) -> Vec<ByteStreamContent<Nalu>> {
+    AnnexBByteStreamNaluReader::new(annex_b_byte_stream, nalu_value_context)
+        .read_contents_until_eof()
+        .unwrap()
+}
+
 pub fn parse_nalus_length_prefixed(
     length_prefixed_byte_stream: &[u8],
     length_size_minus_one: usize,
@@ -180,8 +523,7 @@ pub fn parse_nalus_length_prefixed(
 ) -> Vec<ByteStreamContent<Nalu>> {
     LengthPrefixedByteStreamNaluReader::with_length_size_minus_one(
         length_size_minus_one,
-        // `Seek` is currently a requirement, which we hope to lift.
-        io::Cursor::new(&length_prefixed_byte_stream),
+        length_prefixed_byte_stream,
         nalu_value_context,
     )
     .read_contents_until_eof()
@@ -242,3 +584,416 @@ pub fn convert_length_prefixed_to_annex_b<'a>(
 
     (slice_segment_offsets, annex_b_byte_stream)
 }
+
+/// The result of `convert_annex_b_to_length_prefixed()`.
+#[derive(Debug, Clone, Default)]
+pub struct LengthPrefixedConversion {
+    /// The offsets of the NAL unit lengths in `length_prefixed_byte_stream`.
+    pub nalu_offsets: Vec<u32>,
+    pub length_prefixed_byte_stream: Vec<u8>,
+    /// The stripped VPS, SPS and PPS NAL units, in decoding order.
+    pub parameter_sets: Vec<Vec<u8>>,
+}
+
+/// Converts an _Annex B_ byte stream, e.g. an access unit, into length-prefixed NAL units as in MP4 samples.
+///
+/// With `strip_parameter_sets_and_auds`, VPS, SPS and PPS NAL units are moved to the returned parameter sets, e.g. for
+/// an `hvcC` sample entry, and AUD NAL units are dropped.
+///
+/// `length_size_minus_one` must be `0`, `1` or `3`, as `lengthSizeMinusOne` of `hvcC`.
+pub fn convert_annex_b_to_length_prefixed(
+    annex_b_byte_stream: &[u8],
+    length_size_minus_one: usize,
+    strip_parameter_sets_and_auds: bool,
+) -> Result<LengthPrefixedConversion, io::Error> {
+    if !matches!(length_size_minus_one, 0 | 1 | 3) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "length_size_minus_one must be 0, 1 or 3: {}",
+                length_size_minus_one
+            ),
+        ));
+    }
+
+    let mut nalu_offsets: Vec<u32> = Default::default();
+    let mut length_prefixed_byte_stream: Vec<u8> = Vec::with_capacity(annex_b_byte_stream.len());
+    let mut parameter_sets: Vec<Vec<u8>> = Default::default();
+
+    let mut reader =
+        AnnexBByteStreamNaluRefReader::new(annex_b_byte_stream, NaluValueContext::default());
+    let mut current_offset: usize = 0;
+    loop {
+        let nalu_ref_content = match reader.read_content::<NaluRef>(current_offset) {
+            Ok(nalu_ref_content) => nalu_ref_content,
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => break,
+            Err(err) => return Err(err),
+        };
+        current_offset += nalu_ref_content.consumed;
+        // `nal_unit(NumBytesInNalUnit)`: NAL unit header + NAL unit value EBSP.
+        let nal_unit_bytes = &annex_b_byte_stream[nalu_ref_content.offset..current_offset];
+
+        if strip_parameter_sets_and_auds {
+            match nalu_ref_content.value.nal_unit_type {
+                NaluType::VpsNut | NaluType::SpsNut | NaluType::PpsNut => {
+                    parameter_sets.push(nal_unit_bytes.to_vec());
+                    continue;
+                }
+                NaluType::AudNut => continue,
+                _ => {}
+            }
+        }
+
+        let length_size = length_size_minus_one + 1;
+        if nal_unit_bytes.len() >> (length_size * 8) != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "NAL unit of {} bytes @{:#x} does not fit in a {}-byte length",
+                    nal_unit_bytes.len(),
+                    nalu_ref_content.offset,
+                    length_size
+                ),
+            ));
+        }
+
+        nalu_offsets.push(length_prefixed_byte_stream.len() as u32);
+        length_prefixed_byte_stream.extend_from_slice(
+            &nal_unit_bytes.len().to_be_bytes()[size_of::<usize>() - length_size..],
+        );
+        length_prefixed_byte_stream.extend_from_slice(nal_unit_bytes);
+    }
+
+    Ok(LengthPrefixedConversion {
+        nalu_offsets,
+        length_prefixed_byte_stream,
+        parameter_sets,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use bitstream_io::{BigEndian, BitWrite, BitWriter};
+
+    use crate::base::{rbsp_to_ebsp, test_sps_rbsp, write_exp_golomb_ue};
+    use crate::h265::nalu::{NaluType, NaluValue};
+
+    #[test]
+    fn annex_b_byte_stream_works() {
+        let annex_b_byte_stream: Vec<u8> = [
+            // `leading_zero_8bits`, `zero_byte` + `start_code_prefix_one_3bytes`, AUD
+            &[0x00, 0x00, 0x00, 0x00, 0x01, 0x46, 0x01, 0x50][..],
+            // `start_code_prefix_one_3bytes`, FD_NUT ending with `0x00 0x03`, `trailing_zero_8bits`
+            &[
+                0x00, 0x00, 0x01, 0x4C, 0x01, 0xFF, 0x00, 0x00, 0x03, 0x80, 0x00, 0x00,
+            ],
+            // `zero_byte` + `start_code_prefix_one_3bytes`, EOB_NUT, `trailing_zero_8bits`
+            &[0x00, 0x00, 0x00, 0x01, 0x4A, 0x01, 0x00],
+        ]
+        .concat();
+
+        let nalu_refs = AnnexBByteStreamNaluRefReader::new(
+            annex_b_byte_stream.as_slice(),
+            NaluValueContext::default(),
+        )
+        .read_contents_until_eof()
+        .unwrap();
+        assert_eq!(
+            nalu_refs
+                .iter()
+                .map(|x| (x.offset, x.value.offset, x.consumed, x.value.nal_unit_type))
+                .collect::<Vec<_>>(),
+            [
+                (5, 5, 8, NaluType::AudNut),
+                (11, 11, 10, NaluType::FdNut),
+                (24, 24, 8, NaluType::EobNut),
+            ]
+        );
+
+        // Reading in chunks smaller than the NAL units yields the same.
+        struct OneByteReader<'a>(&'a [u8]);
+        impl Read for OneByteReader<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                let n = self.0.len().min(buf.len()).min(1);
+                buf[..n].copy_from_slice(&self.0[..n]);
+                self.0 = &self.0[n..];
+                Ok(n)
+            }
+        }
+        let nalus = AnnexBByteStreamNaluReader::new(
+            OneByteReader(&annex_b_byte_stream),
+            NaluValueContext::default(),
+        )
+        .read_contents_until_eof()
+        .unwrap();
+        assert_eq!(nalus.len(), 3);
+        assert!(matches!(nalus[1].value.value, NaluValue::FdNut));
+
+        // Garbage before the first start code.
+        assert!(AnnexBByteStreamNaluRefReader::new(
+            &[0x12, 0x00, 0x00, 0x01, 0x46, 0x01, 0x50][..],
+            NaluValueContext::default(),
+        )
+        .read_contents_until_eof::<NaluRef>()
+        .is_err());
+
+        // A truncated SPS followed by an AUD is an error rather than the end of the byte stream.
+        let truncated_sps = [
+            0x00, 0x00, 0x00, 0x01, 0x42, 0x01, 0x01, 0x00, 0x00, 0x00, 0x01, 0x46, 0x01, 0x50,
+        ];
+        let mut nalu_reader =
+            AnnexBByteStreamNaluReader::new(&truncated_sps[..], NaluValueContext::default());
+        let results: Vec<_> = nalu_reader.contents().collect();
+        assert_eq!(results.len(), 1);
+        assert_eq!(
+            results[0].as_ref().unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn annex_b_to_length_prefixed_works() {
+        let annex_b_byte_stream: Vec<u8> = [
+            // AUD, VPS, FD_NUT, EOB_NUT
+            &[0x00, 0x00, 0x00, 0x01, 0x46, 0x01, 0x50][..],
+            &[0x00, 0x00, 0x01, 0x40, 0x01, 0x0C],
+            &[0x00, 0x00, 0x01, 0x4C, 0x01, 0xFF, 0x80, 0x00],
+            &[0x00, 0x00, 0x01, 0x4A, 0x01],
+        ]
+        .concat();
+
+        let LengthPrefixedConversion {
+            nalu_offsets,
+            length_prefixed_byte_stream,
+            parameter_sets,
+        } = convert_annex_b_to_length_prefixed(&annex_b_byte_stream, 3, false).unwrap();
+        assert_eq!(nalu_offsets, [0, 7, 14, 22]);
+        assert!(parameter_sets.is_empty());
+
+        assert_eq!(
+            length_prefixed_byte_stream[..14],
+            [0, 0, 0, 3, 0x46, 0x01, 0x50, 0, 0, 0, 3, 0x40, 0x01, 0x0C]
+        );
+        // `trailing_zero_8bits` are not part of the NAL unit.
+        assert_eq!(length_prefixed_byte_stream[14..18], [0, 0, 0, 4]);
+
+        let LengthPrefixedConversion {
+            nalu_offsets,
+            length_prefixed_byte_stream,
+            parameter_sets,
+        } = convert_annex_b_to_length_prefixed(&annex_b_byte_stream, 1, true).unwrap();
+        assert_eq!(nalu_offsets, [0, 6]);
+        assert_eq!(
+            length_prefixed_byte_stream,
+            [0x00, 0x04, 0x4C, 0x01, 0xFF, 0x80, 0x00, 0x02, 0x4A, 0x01]
+        );
+        assert_eq!(parameter_sets, [vec![0x40, 0x01, 0x0C]]);
+
+        // A 300-byte NAL unit does not fit in a 1-byte length.
+        let mut annex_b_byte_stream: Vec<u8> = vec![0x00, 0x00, 0x01, 0x4C, 0x01];
+        annex_b_byte_stream.resize(5 + 298, 0xFF);
+        assert!(convert_annex_b_to_length_prefixed(&annex_b_byte_stream, 0, false).is_err());
+
+        // Lengths of 3 bytes are not allowed, nor those longer than 4 bytes.
+        for length_size_minus_one in [2, 7] {
+            let err =
+                convert_annex_b_to_length_prefixed(&[], length_size_minus_one, false).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        }
+    }
+
+    #[test]
+    fn streaming_byte_stream_works() {
+        // A reader that is neither `Seek` nor returns more than 3 bytes at a time, like a pipe.
+        struct PipeReader<'a>(&'a [u8]);
+        impl Read for PipeReader<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                let n = self.0.len().min(buf.len()).min(3);
+                buf[..n].copy_from_slice(&self.0[..n]);
+                self.0 = &self.0[n..];
+                Ok(n)
+            }
+        }
+
+        // AUD, FD_NUT and EOB_NUT, of which `NaluRefReader` reads only the NAL unit headers.
+        let length_prefixed_byte_stream: Vec<u8> = [
+            &[0x00, 0x03, 0x46, 0x01, 0x50][..],
+            &[0x00, 0x06, 0x4C, 0x01, 0xFF, 0xFF, 0xFF, 0x80],
+            &[0x00, 0x02, 0x4A, 0x01],
+        ]
+        .concat();
+        let mut reader = LengthPrefixedByteStreamNaluRefReader::with_length_size_minus_one(
+            1,
+            PipeReader(&length_prefixed_byte_stream),
+            NaluValueContext::default(),
+        );
+        let mut contents = reader.contents::<NaluRef>();
+        let aud = contents.next().unwrap().unwrap();
+        assert_eq!((aud.offset, aud.value.nal_unit_type), (2, NaluType::AudNut));
+        assert_eq!(
+            contents
+                .map(|x| x.map(|x| (x.offset, x.consumed, x.value.nal_unit_type)))
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap(),
+            [(7, 8, NaluType::FdNut), (15, 4, NaluType::EobNut)]
+        );
+
+        // The iterator ends after the first error.
+        let annex_b_byte_stream = [0x00, 0x00, 0x01, 0x46, 0x01, 0x50, 0x12, 0x00, 0x00, 0x01];
+        let mut reader = AnnexBByteStreamNaluRefReader::new(
+            PipeReader(&annex_b_byte_stream),
+            NaluValueContext::default(),
+        );
+        let results: Vec<_> = reader.contents::<NaluRef>().collect();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].as_ref().unwrap().offset, 3);
+        assert!(results[1].is_err());
+
+        // Only the end of the byte stream right before a length is not an error, i.e. not within a length or a NAL unit.
+        for length in [6, 7, 9] {
+            let mut reader = LengthPrefixedByteStreamNaluReader::with_length_size_minus_one(
+                1,
+                PipeReader(&length_prefixed_byte_stream[..length]),
+                NaluValueContext::default(),
+            );
+            let results: Vec<_> = reader.contents::<Nalu>().collect();
+            assert_eq!(results.len(), 2);
+            assert!(matches!(
+                results[0].as_ref().unwrap().value.value,
+                NaluValue::AudNut(_)
+            ));
+            assert_eq!(
+                results[1].as_ref().unwrap_err().kind(),
+                io::ErrorKind::InvalidData
+            );
+        }
+        let mut reader = LengthPrefixedByteStreamNaluReader::with_length_size_minus_one(
+            1,
+            PipeReader(&length_prefixed_byte_stream[..5]),
+            NaluValueContext::default(),
+        );
+        assert_eq!(reader.read_contents_until_eof::<Nalu>().unwrap().len(), 1);
+
+        // A NAL unit shorter than its header, and an AUD and an SPS whose lengths cut off their RBSPs followed by
+        // another NAL unit, which must not be read into. `NaluRefReader` reads only the header of the AUD.
+        for (length_prefixed_byte_stream, nalu_ref_count) in [
+            (&[0x00, 0x01, 0x46][..], 0),
+            (&[0x00, 0x02, 0x46, 0x01, 0x00, 0x03, 0x46, 0x01, 0x50], 2),
+            (
+                &[0x00, 0x03, 0x42, 0x01, 0x01, 0x00, 0x03, 0x46, 0x01, 0x50],
+                0,
+            ),
+        ] {
+            let mut reader = LengthPrefixedByteStreamNaluReader::with_length_size_minus_one(
+                1,
+                PipeReader(length_prefixed_byte_stream),
+                NaluValueContext::default(),
+            );
+            let err = reader.read_contents_until_eof::<Nalu>().unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+            let mut reader = LengthPrefixedByteStreamNaluRefReader::with_length_size_minus_one(
+                1,
+                PipeReader(length_prefixed_byte_stream),
+                NaluValueContext::default(),
+            );
+            match reader.read_contents_until_eof::<NaluRef>() {
+                Ok(nalu_refs) => assert_eq!(nalu_refs.len(), nalu_ref_count),
+                Err(err) => {
+                    assert_eq!(nalu_ref_count, 0);
+                    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn active_sps_is_tracked() {
+        // SPS 0 with only frame_field_info_present_flag = 1 in vui_parameters(), and SPS 1 without vui_parameters().
+        let sps_0_rbsp = test_sps_rbsp(
+            0,
+            false,
+            Some(&|bit_writer| bit_writer.write(10, 0b00_0000_1000u16).unwrap()),
+        );
+        let sps_1_rbsp = test_sps_rbsp(1, false, None);
+
+        // `buffering_period()` of SPS 1: bp_seq_parameter_set_id = 1, irap_cpb_params_present_flag = 0,
+        // concatenation_flag = 0, au_cpb_removal_delay_delta_minus1 = 0 of the inferred 24 bits
+        let mut buffering_period: Vec<u8> = Vec::new();
+        {
+            let mut bit_writer = BitWriter::endian(&mut buffering_period, BigEndian);
+            write_exp_golomb_ue(&mut bit_writer, 1);
+            bit_writer.write(2, 0u8).unwrap();
+            bit_writer.write(24, 0u32).unwrap();
+            // `payload_bit_equal_to_one`
+            bit_writer.write_bit(true).unwrap();
+            bit_writer.byte_align().unwrap();
+        }
+
+        let mut annex_b_byte_stream: Vec<u8> = Vec::new();
+        for (nal_unit_header, rbsp) in [
+            ([0x42, 0x01], sps_0_rbsp),
+            // Prefix SEI with a `pic_timing()` of `pic_struct` 1 (top field)
+            ([0x4E, 0x01], vec![0x01, 0x01, 0x11, 0x80]),
+            ([0x42, 0x01], sps_1_rbsp),
+            // Prefix SEI starting a CVS of SPS 1, with a `buffering_period()` and an empty `pic_timing()`
+            (
+                [0x4E, 0x01],
+                [
+                    &[0x00, buffering_period.len() as u8][..],
+                    &buffering_period,
+                    &[0x01, 0x00, 0x80],
+                ]
+                .concat(),
+            ),
+            // Prefix SEI with an empty `pic_timing()`
+            ([0x4E, 0x01], vec![0x01, 0x00, 0x80]),
+        ] {
+            annex_b_byte_stream.extend([0x00, 0x00, 0x00, 0x01]);
+            annex_b_byte_stream.extend(nal_unit_header);
+            annex_b_byte_stream.extend(rbsp_to_ebsp(&rbsp));
+        }
+
+        let mut reader =
+            AnnexBByteStreamNaluReader::new(&annex_b_byte_stream[..], NaluValueContext::default());
+        let nalus = reader.read_contents_until_eof().unwrap();
+        assert_eq!(nalus.len(), 5);
+        assert!(nalus[0].value.value.as_sps_nut().is_some());
+        let sei = nalus[1].value.value.as_sei().unwrap();
+        let pic_timing = sei.sei_messages[0].payload.as_pic_timing().unwrap();
+        assert_eq!(pic_timing.frame_field_info.unwrap().pic_struct, 1);
+        assert!(pic_timing.cpb_dpb_delays.is_none());
+
+        // SPS 1 is not active until the `buffering_period()` referring to it.
+        let sei = nalus[3].value.value.as_sei().unwrap();
+        let buffering_period = sei.sei_messages[0].payload.as_buffering_period().unwrap();
+        assert_eq!(buffering_period.bp_seq_parameter_set_id, 1);
+        let pic_timing = sei.sei_messages[1].payload.as_pic_timing().unwrap();
+        assert!(pic_timing.frame_field_info.is_none());
+        let sei = nalus[4].value.value.as_sei().unwrap();
+        assert!(sei.sei_messages[0].payload.as_pic_timing().is_some());
+        let active_sps = reader
+            .content_reader()
+            .nalu_value_context()
+            .active_sps
+            .as_ref();
+        assert_eq!(active_sps.unwrap().sps_seq_parameter_set_id, 1);
+
+        // `NaluRefReader` keeps track of the SPSs as well.
+        let mut reader = AnnexBByteStreamNaluRefReader::new(
+            &annex_b_byte_stream[..],
+            NaluValueContext::default(),
+        );
+        assert_eq!(reader.read_contents_until_eof().unwrap().len(), 5);
+        let nalu_value_context = reader.content_reader().nalu_value_context();
+        assert_eq!(
+            nalu_value_context
+                .sequence_parameter_sets
+                .keys()
+                .collect::<Vec<_>>(),
+            [&0, &1]
+        );
+    }
+}
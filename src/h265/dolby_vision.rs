@@ -0,0 +1,246 @@
+//! Dolby Vision RPU(Reference Processing Unit)s, carried in `UNSPEC62` NAL units.
+
+use std::io::{self, Read};
+
+use bitstream_io::BitRead as _;
+use bitstream_io::{BigEndian, BitReader};
+
+use crate::base::read_exp_golomb_ue;
+
+/// `rpu_nal_prefix`: The first byte of an RPU in an `UNSPEC62` NAL unit.
+pub const DOLBY_VISION_RPU_NAL_PREFIX: u8 = 0x19;
+
+/// A Dolby Vision RPU, with its header fields parsed and the rest kept as-is for a dedicated parser to consume.
+#[derive(Debug, Clone)]
+pub struct DolbyVisionRpu {
+    /// The RPU with emulation prevention bytes removed, from `rpu_nal_prefix` up to and including the `0x80` terminator.
+    pub payload: Vec<u8>,
+    pub header: DolbyVisionRpuHeader,
+    /// `rpu_data_crc32`: CRC-32 of the RPU following `rpu_nal_prefix`.
+    pub rpu_data_crc32: u32,
+}
+
+/// The leading fields of `rpu_data()`, up to `vdr_rpu_id`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DolbyVisionRpuHeader {
+    /// Shall be `2`.
+    pub rpu_type: u8,
+    pub rpu_format: u16,
+    pub vdr_rpu_profile: u8,
+    pub vdr_rpu_level: u8,
+    /// `Some` means `vdr_seq_info_present_flag == true`.
+    pub vdr_seq_info: Option<VdrSeqInfo>,
+    pub vdr_dm_metadata_present_flag: bool,
+    pub use_prev_vdr_rpu_flag: bool,
+    /// `prev_vdr_rpu_id` if `use_prev_vdr_rpu_flag == true`, `vdr_rpu_id` otherwise.
+    pub vdr_rpu_id: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VdrSeqInfo {
+    pub chroma_resampling_explicit_filter_flag: bool,
+    /// `0` for fixed point and `1` for floating point coefficients.
+    pub coefficient_data_type: u8,
+    /// Present when `coefficient_data_type == 0`.
+    pub coefficient_log2_denom: Option<u32>,
+    pub vdr_rpu_normalized_idc: u8,
+    pub bl_video_full_range_flag: bool,
+    /// Present when `(rpu_format & 0x700) == 0`.
+    pub bit_depths: Option<VdrBitDepths>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VdrBitDepths {
+    pub bl_bit_depth_minus8: u32,
+    pub el_bit_depth_minus8: u32,
+    pub vdr_bit_depth_minus8: u32,
+    pub spatial_resampling_filter_flag: bool,
+    pub el_spatial_resampling_filter_flag: bool,
+    pub disable_residual_flag: bool,
+}
+
+impl DolbyVisionRpu {
+    /// Reads the RBSP of an `UNSPEC62` NAL unit to its end.
+    pub fn from_rbsp_reader<R: Read>(reader: &mut R) -> Result<Self, io::Error> {
+        let mut payload: Vec<u8> = Vec::new();
+        reader.read_to_end(&mut payload)?;
+
+        if payload.first() != Some(&DOLBY_VISION_RPU_NAL_PREFIX) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Not a Dolby Vision RPU: rpu_nal_prefix != 0x19",
+            ));
+        }
+
+        // `rpu_data_crc32` followed by the `0x80` terminator, ignoring any trailing zero bytes.
+        let end = payload
+            .iter()
+            .rposition(|&x| x != 0)
+            .filter(|&i| payload[i] == 0x80 && i >= 5)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Dolby Vision RPU not terminated by 0x80",
+                )
+            })?;
+        let rpu_data_crc32 = u32::from_be_bytes(payload[end - 4..end].try_into().unwrap());
+        payload.truncate(end + 1);
+
+        let header = DolbyVisionRpuHeader::from_bytes(&payload[1..end - 4])?;
+
+        Ok(Self {
+            payload,
+            header,
+            rpu_data_crc32,
+        })
+    }
+}
+
+impl DolbyVisionRpuHeader {
+    fn from_bytes(bytes: &[u8]) -> Result<Self, io::Error> {
+        let mut bit_reader = BitReader::endian(bytes, BigEndian);
+
+        let rpu_type: u8 = bit_reader.read(6)?;
+        if rpu_type != 2 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Unsupported Dolby Vision rpu_type: {}", rpu_type),
+            ));
+        }
+        let rpu_format: u16 = bit_reader.read(11)?;
+        let vdr_rpu_profile: u8 = bit_reader.read(4)?;
+        let vdr_rpu_level: u8 = bit_reader.read(4)?;
+
+        let vdr_seq_info_present_flag = bit_reader.read_bit()?;
+        let vdr_seq_info = if vdr_seq_info_present_flag {
+            let chroma_resampling_explicit_filter_flag = bit_reader.read_bit()?;
+            let coefficient_data_type: u8 = bit_reader.read(2)?;
+            let coefficient_log2_denom = if coefficient_data_type == 0 {
+                Some(read_exp_golomb_ue(&mut bit_reader)?)
+            } else {
+                None
+            };
+            let vdr_rpu_normalized_idc: u8 = bit_reader.read(2)?;
+            let bl_video_full_range_flag = bit_reader.read_bit()?;
+
+            let bit_depths = if rpu_format & 0x700 == 0 {
+                let bl_bit_depth_minus8 = read_exp_golomb_ue(&mut bit_reader)?;
+                let el_bit_depth_minus8 = read_exp_golomb_ue(&mut bit_reader)?;
+                let vdr_bit_depth_minus8 = read_exp_golomb_ue(&mut bit_reader)?;
+                let spatial_resampling_filter_flag = bit_reader.read_bit()?;
+                // `reserved_zero_3bits`
+                bit_reader.read::<u8>(3)?;
+                let el_spatial_resampling_filter_flag = bit_reader.read_bit()?;
+                let disable_residual_flag = bit_reader.read_bit()?;
+
+                Some(VdrBitDepths {
+                    bl_bit_depth_minus8,
+                    el_bit_depth_minus8,
+                    vdr_bit_depth_minus8,
+                    spatial_resampling_filter_flag,
+                    el_spatial_resampling_filter_flag,
+                    disable_residual_flag,
+                })
+            } else {
+                None
+            };
+
+            Some(VdrSeqInfo {
+                chroma_resampling_explicit_filter_flag,
+                coefficient_data_type,
+                coefficient_log2_denom,
+                vdr_rpu_normalized_idc,
+                bl_video_full_range_flag,
+                bit_depths,
+            })
+        } else {
+            None
+        };
+
+        let vdr_dm_metadata_present_flag = bit_reader.read_bit()?;
+        let use_prev_vdr_rpu_flag = bit_reader.read_bit()?;
+        let vdr_rpu_id = read_exp_golomb_ue(&mut bit_reader)?;
+
+        Ok(Self {
+            rpu_type,
+            rpu_format,
+            vdr_rpu_profile,
+            vdr_rpu_level,
+            vdr_seq_info,
+            vdr_dm_metadata_present_flag,
+            use_prev_vdr_rpu_flag,
+            vdr_rpu_id,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use bitstream_io::{BitWrite, BitWriter};
+
+    use crate::base::write_exp_golomb_ue;
+    use crate::h265::nalu::{Nalu, NaluType, NaluValueContext};
+
+    #[test]
+    fn dolby_vision_rpu_works() {
+        let mut rpu_data: Vec<u8> = Vec::new();
+        {
+            let mut bit_writer = BitWriter::endian(&mut rpu_data, BigEndian);
+            // rpu_type = 2, rpu_format = 18, vdr_rpu_profile = 1, vdr_rpu_level = 0
+            bit_writer.write(6, 2u8).unwrap();
+            bit_writer.write(11, 18u16).unwrap();
+            bit_writer.write(4, 1u8).unwrap();
+            bit_writer.write(4, 0u8).unwrap();
+            // vdr_seq_info_present_flag = 1, chroma_resampling_explicit_filter_flag = 0, coefficient_data_type = 0,
+            // coefficient_log2_denom = 23, vdr_rpu_normalized_idc = 1, bl_video_full_range_flag = 0
+            bit_writer.write(4, 0b1000u8).unwrap();
+            write_exp_golomb_ue(&mut bit_writer, 23);
+            bit_writer.write(3, 0b010u8).unwrap();
+            // bl_bit_depth_minus8 = 2, el_bit_depth_minus8 = 2, vdr_bit_depth_minus8 = 4
+            for value in [2, 2, 4] {
+                write_exp_golomb_ue(&mut bit_writer, value);
+            }
+            // spatial_resampling_filter_flag = 0, reserved_zero_3bits, el_spatial_resampling_filter_flag = 0,
+            // disable_residual_flag = 1
+            bit_writer.write(6, 0b000001u8).unwrap();
+            // vdr_dm_metadata_present_flag = 1, use_prev_vdr_rpu_flag = 0, vdr_rpu_id = 0
+            bit_writer.write(2, 0b10u8).unwrap();
+            write_exp_golomb_ue(&mut bit_writer, 0);
+            bit_writer.byte_align().unwrap();
+        }
+
+        // NAL unit header of `UNSPEC62`, `rpu_nal_prefix`, `rpu_data()`, `rpu_data_crc32` and the terminator.
+        let mut bytes: Vec<u8> = vec![0x7C, 0x01, DOLBY_VISION_RPU_NAL_PREFIX];
+        bytes.extend_from_slice(&rpu_data);
+        bytes.extend_from_slice(&[0xDE, 0xAD, 0xBE, 0xEF, 0x80]);
+
+        let nalu = Nalu::from_bytes(&bytes, &NaluValueContext::default()).unwrap();
+        assert_eq!(nalu.header.nal_unit_type, NaluType::Unspec62);
+
+        let dolby_vision_rpu = nalu.value.as_dolby_vision_rpu().unwrap();
+        assert_eq!(dolby_vision_rpu.payload, bytes[2..]);
+        assert_eq!(dolby_vision_rpu.rpu_data_crc32, 0xDEADBEEF);
+
+        let header = &dolby_vision_rpu.header;
+        assert_eq!(header.rpu_type, 2);
+        assert_eq!(header.rpu_format, 18);
+        assert_eq!(header.vdr_rpu_profile, 1);
+        let vdr_seq_info = header.vdr_seq_info.unwrap();
+        assert_eq!(vdr_seq_info.coefficient_log2_denom, Some(23));
+        assert_eq!(vdr_seq_info.vdr_rpu_normalized_idc, 1);
+        let bit_depths = vdr_seq_info.bit_depths.unwrap();
+        assert_eq!(bit_depths.bl_bit_depth_minus8, 2);
+        assert_eq!(bit_depths.vdr_bit_depth_minus8, 4);
+        assert!(bit_depths.disable_residual_flag);
+        assert!(header.vdr_dm_metadata_present_flag);
+        assert!(!header.use_prev_vdr_rpu_flag);
+        assert_eq!(header.vdr_rpu_id, 0);
+
+        // A vendor payload starting with the prefix but without the terminator is not an error.
+        let bytes = [0x7C, 0x01, DOLBY_VISION_RPU_NAL_PREFIX, 0x12, 0x34];
+        let nalu = Nalu::from_bytes(&bytes, &NaluValueContext::default()).unwrap();
+        assert_eq!(nalu.value.as_unknown(), Some(&bytes[2..]));
+    }
+}
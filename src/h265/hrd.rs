@@ -0,0 +1,394 @@
+//! HRD(Hypothetical Reference Decoder) parameters
+
+use std::io::{self, Read};
+
+use bitstream_io::BitRead as _;
+use bitstream_io::{BigEndian, BitReader};
+
+use crate::base::read_exp_golomb_ue;
+
+/// See `hrd_parameters()` in _E.2.2 HRD parameters syntax_ in the spec.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HrdParameters {
+    /// `commonInfPresentFlag`.
+    pub common_inf_present_flag: bool,
+    /// The information common to all sub-layers.
+    ///
+    /// When `common_inf_present_flag == false`, copied from the preceding `hrd_parameters()` in the VPS.
+    pub common_info: HrdCommonInfo,
+    /// Indexed by the sub-layer `i` in `[0, maxNumSubLayersMinus1]`.
+    pub sub_layers: Vec<SubLayerHrdInfo>,
+}
+
+/// The part of `hrd_parameters()` conditioned on `commonInfPresentFlag`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HrdCommonInfo {
+    /// Specifies that NAL HRD parameters (pertaining to Type II bitstream conformance) are present.
+    pub nal_hrd_parameters_present_flag: bool,
+    /// Specifies that VCL HRD parameters (pertaining to all bitstream conformance) are present.
+    pub vcl_hrd_parameters_present_flag: bool,
+    /// `Some` means `sub_pic_hrd_params_present_flag == true`.
+    pub sub_pic_hrd_params: Option<SubPicHrdParams>,
+    /// Specifies (together with `bit_rate_value_minus1[i]`) the maximum input bit rate of the i-th CPB.
+    pub bit_rate_scale: u8,
+    /// Specifies (together with `cpb_size_value_minus1[i]`) the CPB size of the i-th CPB.
+    pub cpb_size_scale: u8,
+    /// Inferred to be `23` when not present.
+    pub initial_cpb_removal_delay_length_minus1: u8,
+    /// Inferred to be `23` when not present.
+    pub au_cpb_removal_delay_length_minus1: u8,
+    /// Inferred to be `23` when not present.
+    pub dpb_output_delay_length_minus1: u8,
+}
+
+/// The sub-picture level HRD parameters, present when `sub_pic_hrd_params_present_flag == true`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SubPicHrdParams {
+    /// Specifies the clock sub-tick, i.e. `ClockTick ÷ (tick_divisor_minus2 + 2)`.
+    pub tick_divisor_minus2: u8,
+    pub du_cpb_removal_delay_increment_length_minus1: u8,
+    /// Specifies that sub-picture level CPB removal delay parameters are present in picture timing SEI messages.
+    pub sub_pic_cpb_params_in_pic_timing_sei_flag: bool,
+    pub dpb_output_delay_du_length_minus1: u8,
+    /// Specifies (together with `cpb_size_du_value_minus1[i]`) the CPB size of the i-th CPB when operating at sub-picture level.
+    pub cpb_size_du_scale: u8,
+}
+
+/// The per-sub-layer part of `hrd_parameters()`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SubLayerHrdInfo {
+    pub fixed_pic_rate_general_flag: bool,
+    /// Inferred to be `true` when `fixed_pic_rate_general_flag == true`.
+    pub fixed_pic_rate_within_cvs_flag: bool,
+    /// Specifies the temporal distance in clock ticks between the HRD output times of consecutive pictures in output order. `[0, 2047]`.
+    ///
+    /// `Some` means `fixed_pic_rate_within_cvs_flag == true`.
+    pub elemental_duration_in_tc_minus1: Option<u16>,
+    /// Specifies the HRD operational mode: `false` for the normal-delay mode, `true` for the low-delay mode.
+    ///
+    /// Inferred to be `false` when not present.
+    pub low_delay_hrd_flag: bool,
+    /// The number of alternative CPB specifications in the bitstream of the CVS. `[0, 31]`.
+    ///
+    /// Inferred to be `0` when not present.
+    pub cpb_cnt_minus1: u8,
+    /// `Some` means `nal_hrd_parameters_present_flag == true`.
+    pub nal_hrd_parameters: Option<SubLayerHrdParameters>,
+    /// `Some` means `vcl_hrd_parameters_present_flag == true`.
+    pub vcl_hrd_parameters: Option<SubLayerHrdParameters>,
+}
+
+/// See `sub_layer_hrd_parameters()` in _E.2.3 Sub-layer HRD parameters syntax_ in the spec.
+///
+/// Each `Vec` has `CpbCnt + 1` entries, where `CpbCnt = cpb_cnt_minus1`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct SubLayerHrdParameters {
+    pub bit_rate_value_minus1: Vec<u32>,
+    pub cpb_size_value_minus1: Vec<u32>,
+    /// Empty when `sub_pic_hrd_params_present_flag == false`.
+    pub cpb_size_du_value_minus1: Vec<u32>,
+    /// Empty when `sub_pic_hrd_params_present_flag == false`.
+    pub bit_rate_du_value_minus1: Vec<u32>,
+    /// `true` specifies that the HRD operates in constant bit rate mode for the CPB specification.
+    pub cbr_flag: Vec<bool>,
+}
+
+impl Default for HrdCommonInfo {
+    fn default() -> Self {
+        Self {
+            nal_hrd_parameters_present_flag: false,
+            vcl_hrd_parameters_present_flag: false,
+            sub_pic_hrd_params: None,
+            bit_rate_scale: 0,
+            cpb_size_scale: 0,
+            initial_cpb_removal_delay_length_minus1: 23,
+            au_cpb_removal_delay_length_minus1: 23,
+            dpb_output_delay_length_minus1: 23,
+        }
+    }
+}
+
+impl HrdParameters {
+    /// Reads `hrd_parameters(common_inf_present_flag, max_num_sub_layers_minus1)`.
+    ///
+    /// `inherited_common_info` is used when `common_inf_present_flag == false`, and should be the common information of the
+    /// preceding `hrd_parameters()` in the VPS.
+    pub fn from_bit_reader<R: Read>(
+        bit_reader: &mut BitReader<R, BigEndian>,
+        common_inf_present_flag: bool,
+        max_num_sub_layers_minus1: u8,
+        inherited_common_info: Option<&HrdCommonInfo>,
+    ) -> Result<Self, io::Error> {
+        let common_info = if common_inf_present_flag {
+            HrdCommonInfo::from_bit_reader(bit_reader)?
+        } else {
+            inherited_common_info.copied().unwrap_or_default()
+        };
+
+        let mut sub_layers: Vec<SubLayerHrdInfo> = Vec::new();
+        for _ in 0..=max_num_sub_layers_minus1 {
+            let fixed_pic_rate_general_flag = bit_reader.read_bit()?;
+            let fixed_pic_rate_within_cvs_flag = if !fixed_pic_rate_general_flag {
+                bit_reader.read_bit()?
+            } else {
+                true
+            };
+
+            let mut low_delay_hrd_flag = false;
+            let elemental_duration_in_tc_minus1 = if fixed_pic_rate_within_cvs_flag {
+                Some(read_exp_golomb_ue(bit_reader)? as u16)
+            } else {
+                low_delay_hrd_flag = bit_reader.read_bit()?;
+                None
+            };
+
+            let cpb_cnt_minus1 = if !low_delay_hrd_flag {
+                read_exp_golomb_ue(bit_reader)?
+            } else {
+                0
+            };
+            if cpb_cnt_minus1 > 31 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("cpb_cnt_minus1 out of range: {}", cpb_cnt_minus1),
+                ));
+            }
+            let cpb_cnt_minus1 = cpb_cnt_minus1 as u8;
+
+            let sub_pic_hrd_params_present_flag = common_info.sub_pic_hrd_params.is_some();
+            let nal_hrd_parameters = if common_info.nal_hrd_parameters_present_flag {
+                Some(SubLayerHrdParameters::from_bit_reader(
+                    bit_reader,
+                    cpb_cnt_minus1,
+                    sub_pic_hrd_params_present_flag,
+                )?)
+            } else {
+                None
+            };
+            let vcl_hrd_parameters = if common_info.vcl_hrd_parameters_present_flag {
+                Some(SubLayerHrdParameters::from_bit_reader(
+                    bit_reader,
+                    cpb_cnt_minus1,
+                    sub_pic_hrd_params_present_flag,
+                )?)
+            } else {
+                None
+            };
+
+            sub_layers.push(SubLayerHrdInfo {
+                fixed_pic_rate_general_flag,
+                fixed_pic_rate_within_cvs_flag,
+                elemental_duration_in_tc_minus1,
+                low_delay_hrd_flag,
+                cpb_cnt_minus1,
+                nal_hrd_parameters,
+                vcl_hrd_parameters,
+            });
+        }
+
+        Ok(Self {
+            common_inf_present_flag,
+            common_info,
+            sub_layers,
+        })
+    }
+}
+
+impl HrdCommonInfo {
+    fn from_bit_reader<R: Read>(
+        bit_reader: &mut BitReader<R, BigEndian>,
+    ) -> Result<Self, io::Error> {
+        let mut value = Self {
+            nal_hrd_parameters_present_flag: bit_reader.read_bit()?,
+            vcl_hrd_parameters_present_flag: bit_reader.read_bit()?,
+            ..Default::default()
+        };
+
+        if value.nal_hrd_parameters_present_flag || value.vcl_hrd_parameters_present_flag {
+            let sub_pic_hrd_params_present_flag = bit_reader.read_bit()?;
+            let mut sub_pic_hrd_params = if sub_pic_hrd_params_present_flag {
+                Some(SubPicHrdParams {
+                    tick_divisor_minus2: bit_reader.read(8)?,
+                    du_cpb_removal_delay_increment_length_minus1: bit_reader.read(5)?,
+                    sub_pic_cpb_params_in_pic_timing_sei_flag: bit_reader.read_bit()?,
+                    dpb_output_delay_du_length_minus1: bit_reader.read(5)?,
+                    cpb_size_du_scale: 0,
+                })
+            } else {
+                None
+            };
+
+            value.bit_rate_scale = bit_reader.read(4)?;
+            value.cpb_size_scale = bit_reader.read(4)?;
+            if let Some(sub_pic_hrd_params) = sub_pic_hrd_params.as_mut() {
+                sub_pic_hrd_params.cpb_size_du_scale = bit_reader.read(4)?;
+            }
+            value.sub_pic_hrd_params = sub_pic_hrd_params;
+
+            value.initial_cpb_removal_delay_length_minus1 = bit_reader.read(5)?;
+            value.au_cpb_removal_delay_length_minus1 = bit_reader.read(5)?;
+            value.dpb_output_delay_length_minus1 = bit_reader.read(5)?;
+        }
+
+        Ok(value)
+    }
+}
+
+impl SubLayerHrdParameters {
+    /// `BitRate[i] = (bit_rate_value_minus1[i] + 1) * 2^(6 + bit_rate_scale)`: The maximum input bit rate of the `i`-th CPB in bits per second.
+    pub fn bit_rate(&self, i: usize, common_info: &HrdCommonInfo) -> Option<u64> {
+        self.bit_rate_value_minus1
+            .get(i)
+            .map(|&x| (x as u64 + 1) << (6 + common_info.bit_rate_scale))
+    }
+
+    /// `CpbSize[i] = (cpb_size_value_minus1[i] + 1) * 2^(4 + cpb_size_scale)`: The size of the `i`-th CPB in bits.
+    pub fn cpb_size(&self, i: usize, common_info: &HrdCommonInfo) -> Option<u64> {
+        self.cpb_size_value_minus1
+            .get(i)
+            .map(|&x| (x as u64 + 1) << (4 + common_info.cpb_size_scale))
+    }
+
+    /// `BitRate[i] = (bit_rate_du_value_minus1[i] + 1) * 2^(6 + bit_rate_scale)` when operating at sub-picture level.
+    pub fn bit_rate_du(&self, i: usize, common_info: &HrdCommonInfo) -> Option<u64> {
+        self.bit_rate_du_value_minus1
+            .get(i)
+            .map(|&x| (x as u64 + 1) << (6 + common_info.bit_rate_scale))
+    }
+
+    /// `CpbSize[i] = (cpb_size_du_value_minus1[i] + 1) * 2^(4 + cpb_size_du_scale)` when operating at sub-picture level.
+    pub fn cpb_size_du(&self, i: usize, common_info: &HrdCommonInfo) -> Option<u64> {
+        let cpb_size_du_scale = common_info.sub_pic_hrd_params?.cpb_size_du_scale;
+        self.cpb_size_du_value_minus1
+            .get(i)
+            .map(|&x| (x as u64 + 1) << (4 + cpb_size_du_scale))
+    }
+
+    fn from_bit_reader<R: Read>(
+        bit_reader: &mut BitReader<R, BigEndian>,
+        cpb_cnt_minus1: u8,
+        sub_pic_hrd_params_present_flag: bool,
+    ) -> Result<Self, io::Error> {
+        let mut value = Self::default();
+        for _ in 0..=cpb_cnt_minus1 {
+            value
+                .bit_rate_value_minus1
+                .push(read_exp_golomb_ue(bit_reader)?);
+            value
+                .cpb_size_value_minus1
+                .push(read_exp_golomb_ue(bit_reader)?);
+            if sub_pic_hrd_params_present_flag {
+                value
+                    .cpb_size_du_value_minus1
+                    .push(read_exp_golomb_ue(bit_reader)?);
+                value
+                    .bit_rate_du_value_minus1
+                    .push(read_exp_golomb_ue(bit_reader)?);
+            }
+            value.cbr_flag.push(bit_reader.read_bit()?);
+        }
+
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use bitstream_io::{BitWrite, BitWriter};
+
+    use crate::base::write_exp_golomb_ue;
+
+    #[test]
+    fn hrd_parameters_work() {
+        let mut bytes: Vec<u8> = Vec::new();
+        {
+            let mut bit_writer = BitWriter::endian(&mut bytes, BigEndian);
+            // nal_hrd_parameters_present_flag = 0, vcl_hrd_parameters_present_flag = 1, sub_pic_hrd_params_present_flag = 1
+            bit_writer.write(3, 0b011u8).unwrap();
+            // tick_divisor_minus2 = 88, du_cpb_removal_delay_increment_length_minus1 = 7,
+            // sub_pic_cpb_params_in_pic_timing_sei_flag = 1, dpb_output_delay_du_length_minus1 = 9
+            bit_writer.write(8, 88u8).unwrap();
+            bit_writer.write(5, 7u8).unwrap();
+            bit_writer.write_bit(true).unwrap();
+            bit_writer.write(5, 9u8).unwrap();
+            // bit_rate_scale = 2, cpb_size_scale = 3, cpb_size_du_scale = 1
+            bit_writer.write(4, 2u8).unwrap();
+            bit_writer.write(4, 3u8).unwrap();
+            bit_writer.write(4, 1u8).unwrap();
+            for length_minus1 in [15u8, 16, 17] {
+                bit_writer.write(5, length_minus1).unwrap();
+            }
+            // Sub-layer 0: fixed_pic_rate_general_flag = 0, fixed_pic_rate_within_cvs_flag = 0, low_delay_hrd_flag = 0,
+            // cpb_cnt_minus1 = 1
+            bit_writer.write(3, 0u8).unwrap();
+            write_exp_golomb_ue(&mut bit_writer, 1);
+            for (bit_rate_value_minus1, cpb_size_value_minus1, cbr_flag) in
+                [(99, 199, false), (9, 19, true)]
+            {
+                write_exp_golomb_ue(&mut bit_writer, bit_rate_value_minus1);
+                write_exp_golomb_ue(&mut bit_writer, cpb_size_value_minus1);
+                // cpb_size_du_value_minus1, bit_rate_du_value_minus1
+                write_exp_golomb_ue(&mut bit_writer, 4);
+                write_exp_golomb_ue(&mut bit_writer, 5);
+                bit_writer.write_bit(cbr_flag).unwrap();
+            }
+            // Sub-layer 1: fixed_pic_rate_general_flag = 0, fixed_pic_rate_within_cvs_flag = 0, low_delay_hrd_flag = 1
+            bit_writer.write(3, 0b001u8).unwrap();
+            write_exp_golomb_ue(&mut bit_writer, 0);
+            write_exp_golomb_ue(&mut bit_writer, 0);
+            write_exp_golomb_ue(&mut bit_writer, 0);
+            write_exp_golomb_ue(&mut bit_writer, 0);
+            bit_writer.write_bit(false).unwrap();
+
+            // The second `hrd_parameters()` with `commonInfPresentFlag == false`:
+            // fixed_pic_rate_general_flag = 1, elemental_duration_in_tc_minus1 = 1, cpb_cnt_minus1 = 0
+            for _ in 0..2 {
+                bit_writer.write_bit(true).unwrap();
+                write_exp_golomb_ue(&mut bit_writer, 1);
+                write_exp_golomb_ue(&mut bit_writer, 0);
+                for _ in 0..4 {
+                    write_exp_golomb_ue(&mut bit_writer, 0);
+                }
+                bit_writer.write_bit(false).unwrap();
+            }
+            bit_writer.byte_align().unwrap();
+        }
+
+        let mut reader = io::Cursor::new(bytes);
+        let mut bit_reader = BitReader::endian(&mut reader, BigEndian);
+        let hrd_parameters =
+            HrdParameters::from_bit_reader(&mut bit_reader, true, 1, None).unwrap();
+
+        let common_info = &hrd_parameters.common_info;
+        assert!(!common_info.nal_hrd_parameters_present_flag);
+        assert!(common_info.vcl_hrd_parameters_present_flag);
+        assert_eq!(
+            common_info.sub_pic_hrd_params.unwrap().tick_divisor_minus2,
+            88
+        );
+        assert_eq!(common_info.dpb_output_delay_length_minus1, 17);
+
+        assert_eq!(hrd_parameters.sub_layers.len(), 2);
+        let vcl_hrd_parameters = hrd_parameters.sub_layers[0]
+            .vcl_hrd_parameters
+            .as_ref()
+            .unwrap();
+        assert_eq!(vcl_hrd_parameters.cbr_flag, [false, true]);
+        // 100 * 2^(6 + 2), 200 * 2^(4 + 3), 5 * 2^(4 + 1)
+        assert_eq!(vcl_hrd_parameters.bit_rate(0, common_info), Some(25600));
+        assert_eq!(vcl_hrd_parameters.cpb_size(0, common_info), Some(25600));
+        assert_eq!(vcl_hrd_parameters.cpb_size_du(1, common_info), Some(160));
+        assert_eq!(vcl_hrd_parameters.bit_rate(2, common_info), None);
+        assert!(hrd_parameters.sub_layers[1].low_delay_hrd_flag);
+
+        let inherited =
+            HrdParameters::from_bit_reader(&mut bit_reader, false, 1, Some(common_info)).unwrap();
+        assert_eq!(inherited.common_info, *common_info);
+        assert_eq!(
+            inherited.sub_layers[1].elemental_duration_in_tc_minus1,
+            Some(1)
+        );
+    }
+}
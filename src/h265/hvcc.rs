@@ -0,0 +1,345 @@
+//! `HEVCDecoderConfigurationRecord` of the `hvcC` box, see _8.3.3.1 HEVC decoder configuration record_ in
+//! ISO/IEC 14496-15.
+
+use std::io::{self, Read, Write};
+
+use bitstream_io::{BigEndian, BitReader, BitWriter};
+use bitstream_io::{BitRead as _, BitWrite as _};
+
+use crate::h265::nalu::{Nalu, NaluHeader, NaluType, NaluValueContext};
+use crate::h265::sps::SequenceParameterSet;
+
+/// The only `configurationVersion` defined.
+pub const HVCC_CONFIGURATION_VERSION: u8 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HevcDecoderConfigurationRecord {
+    pub general_profile_space: u8,
+    pub general_tier_flag: bool,
+    pub general_profile_idc: u8,
+    /// `general_profile_compatibility_flag[0]` as the MSB.
+    pub general_profile_compatibility_flags: u32,
+    /// 48 bits, see [`ProfileTierLevelCommon::constraint_indicator_flags`].
+    ///
+    /// [`ProfileTierLevelCommon::constraint_indicator_flags`]: crate::h265::ptl::ProfileTierLevelCommon::constraint_indicator_flags
+    pub general_constraint_indicator_flags: u64,
+    pub general_level_idc: u8,
+    pub min_spatial_segmentation_idc: u16,
+    /// `0`: Unknown or mixed, `1`: Slice-based, `2`: Tile-based and `3`: Entropy coding synchronization.
+    pub parallelism_type: u8,
+    /// `chroma_format_idc`.
+    pub chroma_format: u8,
+    pub bit_depth_luma_minus8: u8,
+    pub bit_depth_chroma_minus8: u8,
+    /// In frames per 256 seconds, `0` if unspecified.
+    pub avg_frame_rate: u16,
+    /// `0`: Unknown, `1`: Constant and `2`: Constant for each temporal layer.
+    pub constant_frame_rate: u8,
+    /// `0`: Unknown, otherwise `sps_max_sub_layers_minus1 + 1`.
+    pub num_temporal_layers: u8,
+    /// `sps_temporal_id_nesting_flag`, or `false` if unknown.
+    pub temporal_id_nested: bool,
+    /// Size in bytes minus one of the NAL unit lengths in samples, for `LengthPrefixedByteStreamNaluReader`.
+    pub length_size_minus_one: u8,
+    pub nal_unit_arrays: Vec<NalUnitArray>,
+}
+
+/// The NAL units of a single type in a [`HevcDecoderConfigurationRecord`], e.g. the SPSs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NalUnitArray {
+    /// `true` means all NAL units of `nal_unit_type` are in this array and none in the stream.
+    pub array_completeness: bool,
+    pub nal_unit_type: NaluType,
+    /// Each with its NAL unit header, without emulation prevention bytes removed.
+    pub nal_units: Vec<Vec<u8>>,
+}
+
+impl HevcDecoderConfigurationRecord {
+    /// Builds a record for muxing from `sps`, and from the VUI for `min_spatial_segmentation_idc` and
+    /// `avg_frame_rate` if any.
+    ///
+    /// `parallelism_type` and `constant_frame_rate` are left unknown.
+    pub fn from_sequence_parameter_set(
+        sps: &SequenceParameterSet,
+        length_size_minus_one: u8,
+        nal_unit_arrays: Vec<NalUnitArray>,
+    ) -> Self {
+        let general = &sps.profile_tier_level.general;
+        let vui = sps.vui.as_ref();
+
+        let min_spatial_segmentation_idc = vui
+            .and_then(|vui| vui.bitstream_restriction.as_ref())
+            .map_or(0, |bitstream_restriction| {
+                bitstream_restriction.min_spatial_segmentation_idc
+            });
+        let avg_frame_rate = vui
+            .and_then(|vui| vui.vui_timing_info.as_ref())
+            .filter(|vui_timing_info| vui_timing_info.vui_num_units_in_tick != 0)
+            .map_or(0, |vui_timing_info| {
+                let avg_frame_rate = vui_timing_info.vui_time_scale as u64 * 256
+                    / vui_timing_info.vui_num_units_in_tick as u64;
+                avg_frame_rate.min(u16::MAX as u64) as u16
+            });
+
+        Self {
+            general_profile_space: general.profile_space,
+            general_tier_flag: general.tier_flag,
+            general_profile_idc: general.profile_idc,
+            general_profile_compatibility_flags: general.profile_compatibility_flags_u32(),
+            general_constraint_indicator_flags: general.constraint_indicator_flags(),
+            general_level_idc: general.level_idc.unwrap_or(0),
+            min_spatial_segmentation_idc,
+            parallelism_type: 0,
+            chroma_format: sps.chroma_format_idc,
+            bit_depth_luma_minus8: sps.bit_depth_luma_minus8,
+            bit_depth_chroma_minus8: sps.bit_depth_chroma_minus8,
+            avg_frame_rate,
+            constant_frame_rate: 0,
+            num_temporal_layers: sps.sps_max_sub_layers_minus1 + 1,
+            temporal_id_nested: sps.sps_temporal_id_nesting_flag,
+            length_size_minus_one,
+            nal_unit_arrays,
+        }
+    }
+
+    /// Reads from the payload of an `hvcC` box.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, io::Error> {
+        Self::from_reader(&mut io::Cursor::new(bytes))
+    }
+
+    pub fn from_reader<R: Read>(reader: &mut R) -> Result<Self, io::Error> {
+        let mut bit_reader = BitReader::endian(reader, BigEndian);
+
+        let configuration_version: u8 = bit_reader.read(8)?;
+        if configuration_version != HVCC_CONFIGURATION_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Unsupported hvcC configurationVersion: {}",
+                    configuration_version
+                ),
+            ));
+        }
+
+        let general_profile_space: u8 = bit_reader.read(2)?;
+        let general_tier_flag = bit_reader.read_bit()?;
+        let general_profile_idc: u8 = bit_reader.read(5)?;
+        let general_profile_compatibility_flags: u32 = bit_reader.read(32)?;
+        let general_constraint_indicator_flags: u64 = bit_reader.read(48)?;
+        let general_level_idc: u8 = bit_reader.read(8)?;
+        bit_reader.read::<u8>(4)?; // `reserved = '1111'b`
+        let min_spatial_segmentation_idc: u16 = bit_reader.read(12)?;
+        bit_reader.read::<u8>(6)?; // `reserved = '111111'b`
+        let parallelism_type: u8 = bit_reader.read(2)?;
+        bit_reader.read::<u8>(6)?; // `reserved = '111111'b`
+        let chroma_format: u8 = bit_reader.read(2)?;
+        bit_reader.read::<u8>(5)?; // `reserved = '11111'b`
+        let bit_depth_luma_minus8: u8 = bit_reader.read(3)?;
+        bit_reader.read::<u8>(5)?; // `reserved = '11111'b`
+        let bit_depth_chroma_minus8: u8 = bit_reader.read(3)?;
+        let avg_frame_rate: u16 = bit_reader.read(16)?;
+        let constant_frame_rate: u8 = bit_reader.read(2)?;
+        let num_temporal_layers: u8 = bit_reader.read(3)?;
+        let temporal_id_nested = bit_reader.read_bit()?;
+        let length_size_minus_one: u8 = bit_reader.read(2)?;
+
+        let num_of_arrays: u8 = bit_reader.read(8)?;
+        let mut nal_unit_arrays: Vec<NalUnitArray> = Vec::with_capacity(num_of_arrays as _);
+        for _ in 0..num_of_arrays {
+            let array_completeness = bit_reader.read_bit()?;
+            bit_reader.read_bit()?; // `reserved = 0`
+            let nal_unit_type: u8 = bit_reader.read(6)?;
+            let nal_unit_type: NaluType = nal_unit_type
+                .try_into()
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+            let num_nalus: u16 = bit_reader.read(16)?;
+            let mut nal_units: Vec<Vec<u8>> = Vec::with_capacity(num_nalus as _);
+            for _ in 0..num_nalus {
+                let nal_unit_length: u16 = bit_reader.read(16)?;
+                let mut nal_unit = vec![0u8; nal_unit_length as usize];
+                bit_reader.read_bytes(&mut nal_unit)?;
+                nal_units.push(nal_unit);
+            }
+
+            nal_unit_arrays.push(NalUnitArray {
+                array_completeness,
+                nal_unit_type,
+                nal_units,
+            });
+        }
+
+        Ok(Self {
+            general_profile_space,
+            general_tier_flag,
+            general_profile_idc,
+            general_profile_compatibility_flags,
+            general_constraint_indicator_flags,
+            general_level_idc,
+            min_spatial_segmentation_idc,
+            parallelism_type,
+            chroma_format,
+            bit_depth_luma_minus8,
+            bit_depth_chroma_minus8,
+            avg_frame_rate,
+            constant_frame_rate,
+            num_temporal_layers,
+            temporal_id_nested,
+            length_size_minus_one,
+            nal_unit_arrays,
+        })
+    }
+
+    /// Writes the payload of an `hvcC` box.
+    pub fn to_writer<W: Write>(&self, writer: &mut W) -> Result<(), io::Error> {
+        let mut bit_writer = BitWriter::endian(writer, BigEndian);
+
+        bit_writer.write(8, HVCC_CONFIGURATION_VERSION)?;
+        bit_writer.write(2, self.general_profile_space)?;
+        bit_writer.write_bit(self.general_tier_flag)?;
+        bit_writer.write(5, self.general_profile_idc)?;
+        bit_writer.write(32, self.general_profile_compatibility_flags)?;
+        bit_writer.write(48, self.general_constraint_indicator_flags)?;
+        bit_writer.write(8, self.general_level_idc)?;
+        bit_writer.write(4, 0b1111u8)?;
+        bit_writer.write(12, self.min_spatial_segmentation_idc)?;
+        bit_writer.write(6, 0b111111u8)?;
+        bit_writer.write(2, self.parallelism_type)?;
+        bit_writer.write(6, 0b111111u8)?;
+        bit_writer.write(2, self.chroma_format)?;
+        bit_writer.write(5, 0b11111u8)?;
+        bit_writer.write(3, self.bit_depth_luma_minus8)?;
+        bit_writer.write(5, 0b11111u8)?;
+        bit_writer.write(3, self.bit_depth_chroma_minus8)?;
+        bit_writer.write(16, self.avg_frame_rate)?;
+        bit_writer.write(2, self.constant_frame_rate)?;
+        bit_writer.write(3, self.num_temporal_layers)?;
+        bit_writer.write_bit(self.temporal_id_nested)?;
+        bit_writer.write(2, self.length_size_minus_one)?;
+
+        let num_of_arrays: u8 = self.nal_unit_arrays.len().try_into().map_err(|_| {
+            io::Error::new(io::ErrorKind::InvalidInput, "Too many hvcC NAL unit arrays")
+        })?;
+        bit_writer.write(8, num_of_arrays)?;
+        for nal_unit_array in &self.nal_unit_arrays {
+            bit_writer.write_bit(nal_unit_array.array_completeness)?;
+            bit_writer.write_bit(false)?; // `reserved = 0`
+            bit_writer.write(6, nal_unit_array.nal_unit_type as u8)?;
+
+            let num_nalus: u16 = nal_unit_array.nal_units.len().try_into().map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "Too many NAL units in an hvcC array",
+                )
+            })?;
+            bit_writer.write(16, num_nalus)?;
+            for nal_unit in &nal_unit_array.nal_units {
+                let nal_unit_length: u16 = nal_unit.len().try_into().map_err(|_| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("NAL unit too large for hvcC: {} bytes", nal_unit.len()),
+                    )
+                })?;
+                bit_writer.write(16, nal_unit_length)?;
+                bit_writer.write_bytes(nal_unit)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Parses the NAL units of all arrays, e.g. the VPSs, SPSs and PPSs, in order.
+    pub fn nalus(&self, nalu_value_context: &NaluValueContext) -> Result<Vec<Nalu>, io::Error> {
+        self.nal_unit_arrays
+            .iter()
+            .flat_map(|nal_unit_array| &nal_unit_array.nal_units)
+            .map(|nal_unit| Nalu::from_bytes(nal_unit, nalu_value_context))
+            .collect()
+    }
+}
+
+impl NalUnitArray {
+    /// Groups NAL units, e.g. the parameter sets stripped by `convert_annex_b_to_length_prefixed`, into complete arrays
+    /// by type, in the order each type first appears.
+    pub fn group_by_type(nal_units: &[Vec<u8>]) -> Result<Vec<Self>, io::Error> {
+        let mut nal_unit_arrays: Vec<Self> = Vec::new();
+        for nal_unit in nal_units {
+            let header = NaluHeader::from_reader(&mut nal_unit.as_slice())?;
+            match nal_unit_arrays
+                .iter_mut()
+                .find(|nal_unit_array| nal_unit_array.nal_unit_type == header.nal_unit_type)
+            {
+                Some(nal_unit_array) => nal_unit_array.nal_units.push(nal_unit.clone()),
+                None => nal_unit_arrays.push(Self {
+                    array_completeness: true,
+                    nal_unit_type: header.nal_unit_type,
+                    nal_units: vec![nal_unit.clone()],
+                }),
+            }
+        }
+        Ok(nal_unit_arrays)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::base::{rbsp_to_ebsp, test_sps_rbsp, write_exp_golomb_ue};
+
+    #[test]
+    fn hvcc_works() {
+        // A Main profile 1920x1080 SPS with 60000/1001 Hz VUI timing and `min_spatial_segmentation_idc = 4`.
+        let rbsp = test_sps_rbsp(
+            0,
+            false,
+            Some(&|bit_writer| {
+                // All VUI flags but vui_timing_info_present_flag = 0
+                bit_writer.write(9, 0b0_0000_0001u16).unwrap();
+                bit_writer.write(32, 1001u32).unwrap();
+                bit_writer.write(32, 60000u32).unwrap();
+                // vui_poc_proportional_to_timing_flag = 0, vui_hrd_parameters_present_flag = 0,
+                // bitstream_restriction_flag = 1, tiles_fixed_structure_flag = 0,
+                // motion_vectors_over_pic_boundaries_flag = 1, restricted_ref_pic_lists_flag = 0
+                bit_writer.write(6, 0b001010u8).unwrap();
+                for value in [4, 2, 1, 15, 15] {
+                    write_exp_golomb_ue(bit_writer, value);
+                }
+            }),
+        );
+        let sps_nal_unit: Vec<u8> = [&[0x42, 0x01][..], &rbsp_to_ebsp(&rbsp)].concat();
+
+        let nalu = Nalu::from_bytes(&sps_nal_unit, &NaluValueContext::default()).unwrap();
+        let sps = nalu.value.as_sps_nut().unwrap();
+        let nal_unit_arrays =
+            NalUnitArray::group_by_type(std::slice::from_ref(&sps_nal_unit)).unwrap();
+        let record =
+            HevcDecoderConfigurationRecord::from_sequence_parameter_set(sps, 3, nal_unit_arrays);
+
+        assert_eq!(record.general_profile_idc, 1);
+        assert_eq!(record.general_profile_compatibility_flags, 0x6000_0000);
+        assert_eq!(record.general_constraint_indicator_flags, 0x9000_0000_0000);
+        assert_eq!(record.general_level_idc, 93);
+        assert_eq!(record.min_spatial_segmentation_idc, 4);
+        assert_eq!(record.chroma_format, 1);
+        assert_eq!(record.avg_frame_rate, 15344);
+        assert_eq!(record.num_temporal_layers, 1);
+        assert!(record.temporal_id_nested);
+        assert_eq!(record.nal_unit_arrays[0].nal_unit_type, NaluType::SpsNut);
+
+        let mut bytes: Vec<u8> = Vec::new();
+        record.to_writer(&mut bytes).unwrap();
+        assert_eq!(bytes.len(), 23 + 3 + 2 + sps_nal_unit.len());
+        assert_eq!(&bytes[..2], [1, 0x01]);
+        assert_eq!(bytes[13..15], [0xF0, 0x04]);
+        // constantFrameRate = 0, numTemporalLayers = 1, temporalIdNested = 1, lengthSizeMinusOne = 3
+        assert_eq!(bytes[21], 0b0000_1111);
+
+        let parsed = HevcDecoderConfigurationRecord::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed, record);
+        let nalus = parsed.nalus(&NaluValueContext::default()).unwrap();
+        assert_eq!(nalus.len(), 1);
+        let parsed_sps = nalus[0].value.as_sps_nut().unwrap();
+        assert_eq!(parsed_sps.pic_width_in_luma_samples, 1920);
+        assert_eq!(parsed_sps.pic_height_in_luma_samples, 1080);
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::io::{self, Read, Write};
 
 use bitstream_io::{BigEndian, BitReader, BitWriter};
@@ -6,6 +7,8 @@ use bitstream_io::{BitRead as _, BitWrite as _};
 use crate::h265::vps::VideoParameterSet;
 use crate::h265::sps::SequenceParameterSet;
 use crate::h265::pps::PictureParameterSet;
+use crate::h265::sei::{Sei, SeiPayloadRegistry};
+use crate::h265::dolby_vision::{DolbyVisionRpu, DOLBY_VISION_RPU_NAL_PREFIX};
 use crate::h265::slice::{SliceSegmentContext, SliceSegmentLayer};
 
 #[derive(Debug, Clone)]
@@ -38,6 +41,18 @@ pub enum NaluType {
     ///
     /// `R` signifies a reference picture.
     TrailR = 1,
+    /// `TSA_N`. _Coded slice segment of a TSA picture_.
+    ///
+    /// A TSA(Temporal Sub-layer Access) picture.
+    TsaN = 2,
+    /// `TSA_R`. _Coded slice segment of a TSA picture_.
+    TsaR = 3,
+    /// `STSA_N`. _Coded slice segment of an STSA picture_.
+    ///
+    /// An STSA(Step-wise Temporal Sub-layer Access) picture.
+    StsaN = 4,
+    /// `STSA_R`. _Coded slice segment of an STSA picture_.
+    StsaR = 5,
     /// `RADL_N`. _Coded slice segment of a RADL picture_.
     ///
     /// `N` signifies a non-reference picture.
@@ -60,10 +75,16 @@ pub enum NaluType {
     RaslR = 9,
     /// Reserved non-IRAP SLNR VCL NAL unit type.
     RsvVclN10 = 10,
+    /// Reserved non-IRAP sub-layer reference VCL NAL unit type.
+    RsvVclR11 = 11,
     /// Reserved non-IRAP SLNR VCL NAL unit type.
     RsvVclN12 = 12,
+    /// Reserved non-IRAP sub-layer reference VCL NAL unit type.
+    RsvVclR13 = 13,
     /// Reserved non-IRAP SLNR VCL NAL unit type.
     RsvVclN14 = 14,
+    /// Reserved non-IRAP sub-layer reference VCL NAL unit type.
+    RsvVclR15 = 15,
     /// `BLA_W_LP`. _Coded slice segment of a BLA picture_.
     ///
     /// `W_LP` signifies both RADL and RADL LPs may be present.
@@ -96,6 +117,22 @@ pub enum NaluType {
     RsvIrapVcl22 = 22,
     /// Reserved IRAP VCL unit type.
     RsvIrapVcl23 = 23,
+    /// Reserved non-IRAP VCL NAL unit type.
+    RsvVcl24 = 24,
+    /// Reserved non-IRAP VCL NAL unit type.
+    RsvVcl25 = 25,
+    /// Reserved non-IRAP VCL NAL unit type.
+    RsvVcl26 = 26,
+    /// Reserved non-IRAP VCL NAL unit type.
+    RsvVcl27 = 27,
+    /// Reserved non-IRAP VCL NAL unit type.
+    RsvVcl28 = 28,
+    /// Reserved non-IRAP VCL NAL unit type.
+    RsvVcl29 = 29,
+    /// Reserved non-IRAP VCL NAL unit type.
+    RsvVcl30 = 30,
+    /// Reserved non-IRAP VCL NAL unit type.
+    RsvVcl31 = 31,
     /// `VPS_NUT`. _Video parameter set_.
     VpsNut = 32,
     /// `SPS_NUT`. _Sequence parameter set_.
@@ -104,20 +141,105 @@ pub enum NaluType {
     PpsNut = 34,
     /// `AUD_NUT`. _Access unit delimiter_.
     AudNut = 35,
+    /// `EOS_NUT`. _End of sequence_.
+    EosNut = 36,
+    /// `EOB_NUT`. _End of bitstream_.
+    EobNut = 37,
+    /// `FD_NUT`. _Filler data_.
+    FdNut = 38,
+    /// `PREFIX_SEI_NUT`. _Supplemental enhancement information_.
+    PrefixSeiNut = 39,
+    /// `SUFFIX_SEI_NUT`. _Supplemental enhancement information_.
+    SuffixSeiNut = 40,
+    /// Reserved non-VCL NAL unit type.
+    RsvNvcl41 = 41,
+    /// Reserved non-VCL NAL unit type.
+    RsvNvcl42 = 42,
+    /// Reserved non-VCL NAL unit type.
+    RsvNvcl43 = 43,
+    /// Reserved non-VCL NAL unit type.
+    RsvNvcl44 = 44,
+    /// Reserved non-VCL NAL unit type.
+    RsvNvcl45 = 45,
+    /// Reserved non-VCL NAL unit type.
+    RsvNvcl46 = 46,
+    /// Reserved non-VCL NAL unit type.
+    RsvNvcl47 = 47,
+    /// `UNSPEC48`. Unspecified non-VCL NAL unit type.
+    Unspec48 = 48,
+    /// `UNSPEC49`. Unspecified non-VCL NAL unit type.
+    Unspec49 = 49,
+    /// `UNSPEC50`. Unspecified non-VCL NAL unit type.
+    Unspec50 = 50,
+    /// `UNSPEC51`. Unspecified non-VCL NAL unit type.
+    Unspec51 = 51,
+    /// `UNSPEC52`. Unspecified non-VCL NAL unit type.
+    Unspec52 = 52,
+    /// `UNSPEC53`. Unspecified non-VCL NAL unit type.
+    Unspec53 = 53,
+    /// `UNSPEC54`. Unspecified non-VCL NAL unit type.
+    Unspec54 = 54,
+    /// `UNSPEC55`. Unspecified non-VCL NAL unit type.
+    Unspec55 = 55,
+    /// `UNSPEC56`. Unspecified non-VCL NAL unit type.
+    Unspec56 = 56,
+    /// `UNSPEC57`. Unspecified non-VCL NAL unit type.
+    Unspec57 = 57,
+    /// `UNSPEC58`. Unspecified non-VCL NAL unit type.
+    Unspec58 = 58,
+    /// `UNSPEC59`. Unspecified non-VCL NAL unit type.
+    Unspec59 = 59,
+    /// `UNSPEC60`. Unspecified non-VCL NAL unit type.
+    Unspec60 = 60,
+    /// `UNSPEC61`. Unspecified non-VCL NAL unit type.
+    Unspec61 = 61,
+    /// `UNSPEC62`. Unspecified, carrying Dolby Vision RPUs in practice.
+    Unspec62 = 62,
+    /// `UNSPEC63`. Unspecified non-VCL NAL unit type.
+    Unspec63 = 63,
 }
 
+/// The payload of a NAL unit, decoded according to `nal_unit_type`.
+///
+/// The slice segment and parameter set variants are boxed so that the size of a `NaluValue` does not follow that of the
+/// largest of them, e.g. with `scaling_list_data()`. Match on them with e.g. `NaluValue::SpsNut(sps)` and dereference
+/// `sps`, or use the `as_xxx()` accessors, which return plain references.
 #[derive(Debug, Clone)]
 pub enum NaluValue {
-    CodedSliceSegment(SliceSegmentLayer),
+    CodedSliceSegment(Box<SliceSegmentLayer>),
     AudNut(AccessUnitDelimiter),
-    VpsNut(VideoParameterSet),
-    SpsNut(SequenceParameterSet),
-    PpsNut(PictureParameterSet),
+    VpsNut(Box<VideoParameterSet>),
+    SpsNut(Box<SequenceParameterSet>),
+    PpsNut(Box<PictureParameterSet>),
+    PrefixSeiNut(Sei),
+    SuffixSeiNut(Sei),
+    /// A Dolby Vision RPU in an `UNSPEC62` NAL unit.
+    DolbyVisionRpu(DolbyVisionRpu),
+    /// `end_of_seq_rbsp()`, which is empty.
+    EosNut,
+    /// `end_of_bitstream_rbsp()`, which is empty.
+    EobNut,
+    /// `filler_data_rbsp()`, which carries no information.
+    FdNut,
+    /// The EBSP, i.e. with emulation prevention bytes, of a NAL unit of a reserved or unspecified type, or of an
+    /// `UNSPEC62` NAL unit which is not a Dolby Vision RPU.
+    Unknown(Vec<u8>),
 }
 
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct NaluValueContext {
     pub slice_segment_context: Option<SliceSegmentContext>,
+    /// User-provided decoders for SEI payloads.
+    pub sei_payload_registry: SeiPayloadRegistry,
+    /// The SPS active for the access unit.
+    ///
+    /// Required for `buffering_period()` and `pic_timing()` SEI messages, which are otherwise left undecoded. The byte
+    /// stream readers keep it up to date by `update_parameter_sets()`.
+    pub active_sps: Option<SequenceParameterSet>,
+    /// The SPSs received so far, by `sps_seq_parameter_set_id`.
+    pub sequence_parameter_sets: BTreeMap<u8, SequenceParameterSet>,
+    /// `pps_seq_parameter_set_id` of the PPSs received so far, by `pps_pic_parameter_set_id`.
+    pub pps_seq_parameter_set_ids: BTreeMap<u8, u8>,
 }
 
 /// See `access_unit_delimiter_rbsp()` in _7.3.2.5 Access unit delimiter RBSP syntax_ in the spec.
@@ -184,11 +306,22 @@ impl NaluType {
         }
     }
 
+    /// VCL(Video Coding Layer) type, including the reserved ones ?
+    pub fn is_vcl(&self) -> bool {
+        (*self as u8) < 32
+    }
+
+    /// `slice_segment_layer_rbsp()` type ? Unlike `is_vcl()`, the reserved types are excluded as their syntax is not
+    /// specified.
     pub fn is_coded_slice_segment(&self) -> bool {
         matches!(
             self,
             Self::TrailN
                 | Self::TrailR
+                | Self::TsaN
+                | Self::TsaR
+                | Self::StsaN
+                | Self::StsaR
                 | Self::RadlN
                 | Self::RadlR
                 | Self::RaslN
@@ -199,16 +332,67 @@ impl NaluType {
                 | Self::IdrWRadl
                 | Self::IdrNLp
                 | Self::CraNut
-                | Self::RsvIrapVcl22
-                | Self::RsvIrapVcl23
         )
     }
 }
 
+impl NaluValueContext {
+    /// Keeps track of the parameter sets in `nalu`, which has just been read, and resolves `active_sps`.
+    ///
+    /// The SPS referred to by the first slice segment of a picture becomes active. As the SEI messages of an access unit
+    /// precede its slice segments, the SPS referred to by a `buffering_period()` becomes active as well. A received SPS
+    /// also becomes active right away if there is no active SPS yet, or if it replaces the active SPS, which may only
+    /// happen at the start of a CVS.
+    pub fn update_parameter_sets(&mut self, nalu: &Nalu) {
+        match &nalu.value {
+            NaluValue::SpsNut(sps) => {
+                let sps_seq_parameter_set_id = sps.sps_seq_parameter_set_id;
+                if self.active_sps.as_ref().is_none_or(|active_sps| {
+                    active_sps.sps_seq_parameter_set_id == sps_seq_parameter_set_id
+                }) {
+                    self.active_sps = Some(sps.as_ref().clone());
+                }
+                self.sequence_parameter_sets
+                    .insert(sps_seq_parameter_set_id, sps.as_ref().clone());
+            }
+            NaluValue::PpsNut(pps) => {
+                self.pps_seq_parameter_set_ids
+                    .insert(pps.pps_pic_parameter_set_id, pps.pps_seq_parameter_set_id);
+            }
+            NaluValue::PrefixSeiNut(sei) => {
+                // A `buffering_period()` starting a CVS refers to its SPS before any slice segment does.
+                let sps = sei
+                    .sei_messages
+                    .iter()
+                    .filter_map(|sei_message| sei_message.payload.as_buffering_period())
+                    .find_map(|value| {
+                        self.sequence_parameter_sets
+                            .get(&value.bp_seq_parameter_set_id)
+                    });
+                if let Some(sps) = sps {
+                    self.active_sps = Some(sps.clone());
+                }
+            }
+            NaluValue::CodedSliceSegment(slice_segment_layer)
+                if slice_segment_layer.header.first_slice_segment_in_pic_flag =>
+            {
+                let sps = self
+                    .pps_seq_parameter_set_ids
+                    .get(&slice_segment_layer.header.slice_pic_parameter_set_id)
+                    .and_then(|sps_id| self.sequence_parameter_sets.get(sps_id));
+                if let Some(sps) = sps {
+                    self.active_sps = Some(sps.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
 impl Nalu {
     pub fn from_bytes(
         bytes: &[u8],
-        nalue_value_context: NaluValueContext,
+        nalue_value_context: &NaluValueContext,
     ) -> Result<Self, io::Error> {
         let mut reader = io::Cursor::new(bytes);
         Self::from_reader(&mut reader, bytes.len(), nalue_value_context)
@@ -217,11 +401,16 @@ impl Nalu {
     pub fn from_reader<R: Read>(
         reader: &mut R,
         length: usize,
-        nalue_value_context: NaluValueContext,
+        nalue_value_context: &NaluValueContext,
     ) -> Result<Self, io::Error> {
         let header = NaluHeader::from_reader(reader)?;
 
-        let value_length = length - 2;
+        let value_length = length.checked_sub(2).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("NAL unit of {} bytes is shorter than its header", length),
+            )
+        })?;
         let value = NaluValue::from_ebsp_reader(reader, header, value_length, nalue_value_context)?;
 
         Ok(Self { header, value })
@@ -267,8 +456,20 @@ impl TryFrom<u8> for NaluType {
         match value {
             0 => Ok(Self::TrailN),
             1 => Ok(Self::TrailR),
+            2 => Ok(Self::TsaN),
+            3 => Ok(Self::TsaR),
+            4 => Ok(Self::StsaN),
+            5 => Ok(Self::StsaR),
+            6 => Ok(Self::RadlN),
+            7 => Ok(Self::RadlR),
             8 => Ok(Self::RaslN),
             9 => Ok(Self::RaslR),
+            10 => Ok(Self::RsvVclN10),
+            11 => Ok(Self::RsvVclR11),
+            12 => Ok(Self::RsvVclN12),
+            13 => Ok(Self::RsvVclR13),
+            14 => Ok(Self::RsvVclN14),
+            15 => Ok(Self::RsvVclR15),
             16 => Ok(Self::BlaWLp),
             17 => Ok(Self::BlaWRadl),
             18 => Ok(Self::BlaNLp),
@@ -277,11 +478,47 @@ impl TryFrom<u8> for NaluType {
             21 => Ok(Self::CraNut),
             22 => Ok(Self::RsvIrapVcl22),
             23 => Ok(Self::RsvIrapVcl23),
+            24 => Ok(Self::RsvVcl24),
+            25 => Ok(Self::RsvVcl25),
+            26 => Ok(Self::RsvVcl26),
+            27 => Ok(Self::RsvVcl27),
+            28 => Ok(Self::RsvVcl28),
+            29 => Ok(Self::RsvVcl29),
+            30 => Ok(Self::RsvVcl30),
+            31 => Ok(Self::RsvVcl31),
             32 => Ok(Self::VpsNut),
             33 => Ok(Self::SpsNut),
             34 => Ok(Self::PpsNut),
             35 => Ok(Self::AudNut),
-            _ => Err(format!("Unknown NAL unit type: {}", value)),
+            36 => Ok(Self::EosNut),
+            37 => Ok(Self::EobNut),
+            38 => Ok(Self::FdNut),
+            39 => Ok(Self::PrefixSeiNut),
+            40 => Ok(Self::SuffixSeiNut),
+            41 => Ok(Self::RsvNvcl41),
+            42 => Ok(Self::RsvNvcl42),
+            43 => Ok(Self::RsvNvcl43),
+            44 => Ok(Self::RsvNvcl44),
+            45 => Ok(Self::RsvNvcl45),
+            46 => Ok(Self::RsvNvcl46),
+            47 => Ok(Self::RsvNvcl47),
+            48 => Ok(Self::Unspec48),
+            49 => Ok(Self::Unspec49),
+            50 => Ok(Self::Unspec50),
+            51 => Ok(Self::Unspec51),
+            52 => Ok(Self::Unspec52),
+            53 => Ok(Self::Unspec53),
+            54 => Ok(Self::Unspec54),
+            55 => Ok(Self::Unspec55),
+            56 => Ok(Self::Unspec56),
+            57 => Ok(Self::Unspec57),
+            58 => Ok(Self::Unspec58),
+            59 => Ok(Self::Unspec59),
+            60 => Ok(Self::Unspec60),
+            61 => Ok(Self::Unspec61),
+            62 => Ok(Self::Unspec62),
+            63 => Ok(Self::Unspec63),
+            _ => Err(format!("Invalid NAL unit type: {}", value)),
         }
     }
 }
@@ -289,28 +526,50 @@ impl TryFrom<u8> for NaluType {
 impl NaluValue {
     pub fn as_coded_slice_segment(&self) -> Option<&SliceSegmentLayer> {
         match self {
-            Self::CodedSliceSegment(value) => Some(value),
+            Self::CodedSliceSegment(value) => Some(value.as_ref()),
             _ => None,
         }
     }
 
     pub fn as_vps_nut(&self) -> Option<&VideoParameterSet> {
         match self {
-            Self::VpsNut(value) => Some(value),
+            Self::VpsNut(value) => Some(value.as_ref()),
             _ => None,
         }
     }
 
     pub fn as_sps_nut(&self) -> Option<&SequenceParameterSet> {
         match self {
-            Self::SpsNut(value) => Some(value),
+            Self::SpsNut(value) => Some(value.as_ref()),
             _ => None,
         }
     }
 
     pub fn as_pps_nut(&self) -> Option<&PictureParameterSet> {
         match self {
-            Self::PpsNut(value) => Some(value),
+            Self::PpsNut(value) => Some(value.as_ref()),
+            _ => None,
+        }
+    }
+
+    /// Either `PrefixSeiNut` or `SuffixSeiNut`.
+    pub fn as_sei(&self) -> Option<&Sei> {
+        match self {
+            Self::PrefixSeiNut(value) | Self::SuffixSeiNut(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_dolby_vision_rpu(&self) -> Option<&DolbyVisionRpu> {
+        match self {
+            Self::DolbyVisionRpu(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_unknown(&self) -> Option<&[u8]> {
+        match self {
+            Self::Unknown(value) => Some(value),
             _ => None,
         }
     }
@@ -322,7 +581,7 @@ impl NaluValue {
         reader: &mut R,
         nalu_header: NaluHeader,
         value_length: usize,
-        nalu_value_context: NaluValueContext,
+        nalu_value_context: &NaluValueContext,
     ) -> Result<Self, io::Error> {
         // EBSP(Encapsulated Byte Sequence Payload).
         let ebsp = {
@@ -363,44 +622,86 @@ impl NaluValue {
         let rbsp_reader = &mut rbsp_reader;
 
         match nalu_header.nal_unit_type {
-            NaluType::TrailR
-            | NaluType::TrailN
+            NaluType::TrailN
+            | NaluType::TrailR
+            | NaluType::TsaN
+            | NaluType::TsaR
+            | NaluType::StsaN
+            | NaluType::StsaR
+            | NaluType::RadlN
+            | NaluType::RadlR
+            | NaluType::RaslN
+            | NaluType::RaslR
+            | NaluType::BlaWLp
+            | NaluType::BlaWRadl
+            | NaluType::BlaNLp
             | NaluType::IdrWRadl
             | NaluType::IdrNLp
-            | NaluType::CraNut
-            | NaluType::RaslN
-            | NaluType::RaslR => {
+            | NaluType::CraNut => {
                 let value = SliceSegmentLayer::from_rbsp_reader(
                     rbsp_reader,
                     rbsp_length,
                     nalu_header.nal_unit_type,
                     nalu_value_context
                         .slice_segment_context
+                        .as_ref()
                         .expect("SliceSegmentContext is required for coded slice segments"),
                 )?;
 
-                Ok(Self::CodedSliceSegment(value))
+                Ok(Self::CodedSliceSegment(Box::new(value)))
             }
             NaluType::VpsNut => {
                 let value = VideoParameterSet::from_rbsp_reader(rbsp_reader)?;
-                Ok(Self::VpsNut(value))
+                Ok(Self::VpsNut(Box::new(value)))
             }
             NaluType::SpsNut => {
                 let value = SequenceParameterSet::from_rbsp_reader(rbsp_reader)?;
-                Ok(Self::SpsNut(value))
+                Ok(Self::SpsNut(Box::new(value)))
             }
             NaluType::PpsNut => {
                 let value = PictureParameterSet::from_rbsp_reader(
                     rbsp_reader,
                     nalu_header.nuh_temporal_id_plus1,
                 )?;
-                Ok(Self::PpsNut(value))
+                Ok(Self::PpsNut(Box::new(value)))
             }
             NaluType::AudNut => {
                 let value = AccessUnitDelimiter::from_rbso_reader(rbsp_reader)?;
                 Ok(Self::AudNut(value))
             }
-            nal_unit_type => panic!("Unsupported NAL unit type: {:?}", nal_unit_type),
+            NaluType::PrefixSeiNut => {
+                let value = Sei::from_rbsp_reader(
+                    rbsp_reader,
+                    NaluType::PrefixSeiNut,
+                    &nalu_value_context.sei_payload_registry,
+                    nalu_value_context.active_sps.as_ref(),
+                    &nalu_value_context.sequence_parameter_sets,
+                )?;
+                Ok(Self::PrefixSeiNut(value))
+            }
+            NaluType::SuffixSeiNut => {
+                let value = Sei::from_rbsp_reader(
+                    rbsp_reader,
+                    NaluType::SuffixSeiNut,
+                    &nalu_value_context.sei_payload_registry,
+                    nalu_value_context.active_sps.as_ref(),
+                    &nalu_value_context.sequence_parameter_sets,
+                )?;
+                Ok(Self::SuffixSeiNut(value))
+            }
+            NaluType::EosNut => Ok(Self::EosNut),
+            NaluType::EobNut => Ok(Self::EobNut),
+            NaluType::FdNut => Ok(Self::FdNut),
+            NaluType::Unspec62
+                if rbsp_reader.get_ref().first() == Some(&DOLBY_VISION_RPU_NAL_PREFIX) =>
+            {
+                // Other payloads may start with the prefix as well, which are left as they are.
+                match DolbyVisionRpu::from_rbsp_reader(&mut rbsp_reader.get_ref().as_slice()) {
+                    Ok(value) => Ok(Self::DolbyVisionRpu(value)),
+                    Err(_) => Ok(Self::Unknown(ebsp)),
+                }
+            }
+            _ => Ok(Self::Unknown(ebsp)),
         }
     }
 }
@@ -430,3 +731,38 @@ impl AccessUnitDelimiter {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nalu_types_work() {
+        for value in 0..64u8 {
+            let nal_unit_type = NaluType::try_from(value).unwrap();
+            assert_eq!(nal_unit_type as u8, value);
+            assert_eq!(nal_unit_type.is_vcl(), value < 32);
+        }
+        assert!(NaluType::try_from(64).is_err());
+
+        let context = NaluValueContext::default();
+        // EOS_NUT, FD_NUT
+        let nalu = Nalu::from_bytes(&[0x48, 0x01], &context).unwrap();
+        assert!(matches!(nalu.value, NaluValue::EosNut));
+        let nalu = Nalu::from_bytes(&[0x4C, 0x01, 0xFF, 0xFF, 0x80], &context).unwrap();
+        assert!(matches!(nalu.value, NaluValue::FdNut));
+
+        // RSV_NVCL41 with an emulation prevention byte, and UNSPEC62 which is not a Dolby Vision RPU.
+        let nalu = Nalu::from_bytes(&[0x52, 0x01, 0x00, 0x00, 0x03, 0x01], &context).unwrap();
+        assert_eq!(nalu.header.nal_unit_type, NaluType::RsvNvcl41);
+        assert_eq!(nalu.value.as_unknown(), Some(&[0x00, 0x00, 0x03, 0x01][..]));
+        let nalu = Nalu::from_bytes(&[0x7C, 0x01, 0x42], &context).unwrap();
+        assert_eq!(nalu.value.as_unknown(), Some(&[0x42][..]));
+
+        // RSV_IRAP_VCL22 is an IRAP type, but not a coded slice segment.
+        let nalu = Nalu::from_bytes(&[0x2C, 0x01, 0xAF], &context).unwrap();
+        assert!(nalu.header.nal_unit_type.is_irap());
+        assert!(!nalu.header.nal_unit_type.is_coded_slice_segment());
+        assert_eq!(nalu.value.as_unknown(), Some(&[0xAF][..]));
+    }
+}
@@ -0,0 +1,208 @@
+use std::io;
+
+use bitstream_io::{BigEndian, BitReader};
+
+use crate::base::RbspReader;
+use crate::h265::nalu::{Nalu, NaluHeader, NaluValueContext};
+use crate::h265::slice::{SliceSegmentContext, SliceSegmentHeader};
+
+/// A NAL unit borrowing its bytes from a buffer holding the byte stream, e.g. an MP4 sample.
+///
+/// Unlike `Nalu`, nothing but the NAL unit header is parsed up front and the EBSP is never copied, so that e.g. only the
+/// slice segment header of a coded slice segment is read.
+#[derive(Debug, Clone, Copy)]
+pub struct NaluSlice<'a> {
+    /// The offset of the NAL unit header in bytes.
+    pub offset: usize,
+    pub header: NaluHeader,
+    /// The NAL unit header followed by the EBSP.
+    pub bytes: &'a [u8],
+}
+
+impl<'a> NaluSlice<'a> {
+    /// `bytes` is the whole NAL unit, found at `offset`.
+    pub fn new(bytes: &'a [u8], offset: usize) -> Result<Self, io::Error> {
+        let header = NaluHeader::from_reader(&mut &bytes[..])?;
+        Ok(Self {
+            offset,
+            header,
+            bytes,
+        })
+    }
+
+    /// Iterates over the NAL units of a length-prefixed byte stream, e.g. an MP4 sample.
+    pub fn iter_length_prefixed(
+        length_prefixed_byte_stream: &'a [u8],
+        length_size_minus_one: usize,
+    ) -> impl Iterator<Item = Result<Self, io::Error>> + 'a {
+        let length_size = length_size_minus_one + 1;
+        let mut current_offset: usize = 0;
+        std::iter::from_fn(move || {
+            let rest = &length_prefixed_byte_stream[current_offset..];
+            if rest.is_empty() {
+                return None;
+            }
+
+            let nalu_slice = match rest.get(..length_size) {
+                Some(length_bytes) => {
+                    let length = length_bytes.iter().fold(0, |acc, &x| acc << 8 | x as usize);
+                    match rest.get(length_size..length_size + length) {
+                        Some(bytes) => Self::new(bytes, current_offset + length_size),
+                        None => Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!(
+                                "NAL unit of {} bytes @{:#x} exceeds the byte stream",
+                                length,
+                                current_offset + length_size
+                            ),
+                        )),
+                    }
+                }
+                None => Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Truncated NAL unit length @{:#x}", current_offset),
+                )),
+            };
+
+            // Stop after an error.
+            current_offset = match &nalu_slice {
+                Ok(nalu_slice) => nalu_slice.offset + nalu_slice.length(),
+                Err(_) => length_prefixed_byte_stream.len(),
+            };
+            Some(nalu_slice)
+        })
+    }
+
+    /// The size of the NAL unit in bytes, i.e. `NumBytesInNalUnit`.
+    pub fn length(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// The NAL unit following the header, with emulation prevention bytes.
+    pub fn ebsp(&self) -> &'a [u8] {
+        &self.bytes[2..]
+    }
+
+    /// Reads the RBSP, skipping emulation prevention bytes on the fly.
+    pub fn rbsp_reader(&self) -> RbspReader<'a> {
+        RbspReader::new(self.ebsp())
+    }
+
+    pub fn rbsp_bit_reader(&self) -> BitReader<RbspReader<'a>, BigEndian> {
+        BitReader::endian(self.rbsp_reader(), BigEndian)
+    }
+
+    /// Reads only the slice segment header, or returns `None` if this is not a coded slice segment.
+    pub fn slice_segment_header(
+        &self,
+        slice_segment_context: &SliceSegmentContext,
+    ) -> Result<Option<SliceSegmentHeader>, io::Error> {
+        if !self.header.nal_unit_type.is_coded_slice_segment() {
+            return Ok(None);
+        }
+
+        let header = SliceSegmentHeader::from_rbsp_reader(
+            &mut self.rbsp_reader(),
+            self.header.nal_unit_type,
+            slice_segment_context,
+        )?;
+        Ok(Some(header))
+    }
+
+    /// Parses the whole NAL unit, copying its payload.
+    pub fn to_nalu(&self, nalu_value_context: &NaluValueContext) -> Result<Nalu, io::Error> {
+        Nalu::from_bytes(self.bytes, nalu_value_context)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Read as _;
+
+    use bitstream_io::BitRead as _;
+
+    use crate::base::ebsp_to_rbsp;
+    use crate::h265::nalu::{NaluType, NaluValue};
+    use crate::h265::slice::{test_slice_segment_context, SliceType};
+
+    #[test]
+    fn nalu_slice_works() {
+        let length_prefixed_byte_stream: Vec<u8> = [
+            // AUD
+            &[0x00, 0x03, 0x46, 0x01, 0x50][..],
+            // An unspecified NAL unit with emulation prevention bytes.
+            &[
+                0x00, 0x0B, 0x7E, 0x01, 0x12, 0x00, 0x00, 0x03, 0x01, 0x00, 0x00, 0x03, 0x03,
+            ],
+            // Truncated
+            &[0x00, 0x04, 0x4A, 0x01],
+        ]
+        .concat();
+
+        let results: Vec<_> =
+            NaluSlice::iter_length_prefixed(&length_prefixed_byte_stream, 1).collect();
+        assert_eq!(results.len(), 3);
+        assert_eq!(
+            results[2].as_ref().unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+
+        let aud = results[0].as_ref().unwrap();
+        assert_eq!((aud.offset, aud.length()), (2, 3));
+        assert_eq!(aud.header.nal_unit_type, NaluType::AudNut);
+        assert_eq!(aud.rbsp_bit_reader().read::<u8>(3).unwrap(), 2);
+        assert!(matches!(
+            aud.to_nalu(&NaluValueContext::default()).unwrap().value,
+            NaluValue::AudNut(_)
+        ));
+
+        let unspecified = results[1].as_ref().unwrap();
+        assert_eq!((unspecified.offset, unspecified.length()), (7, 11));
+        assert_eq!(unspecified.header.nal_unit_type, NaluType::Unspec63);
+
+        let mut rbsp: Vec<u8> = Vec::new();
+        unspecified.rbsp_reader().read_to_end(&mut rbsp).unwrap();
+        assert_eq!(rbsp, [0x12, 0x00, 0x00, 0x01, 0x00, 0x00, 0x03]);
+        assert_eq!(rbsp, ebsp_to_rbsp(unspecified.ebsp()));
+
+        // Truncated within a length, and the iteration ends after it.
+        let results: Vec<_> =
+            NaluSlice::iter_length_prefixed(&length_prefixed_byte_stream[..6], 1).collect();
+        assert_eq!(results.len(), 2);
+        assert_eq!(
+            results[1].as_ref().unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn slice_segment_header_works() {
+        let slice_segment_context = test_slice_segment_context();
+
+        // IDR_W_RADL of an I slice with 2 entry points, followed by `slice_segment_data()` with an emulation prevention
+        // byte.
+        let bytes = [
+            0x26, 0x01, 0xAE, 0x39, 0x88, 0x64, 0xC8, 0x80, 0x00, 0x00, 0x03, 0x01,
+        ];
+        let idr = NaluSlice::new(&bytes, 0).unwrap();
+        let header = idr
+            .slice_segment_header(&slice_segment_context)
+            .unwrap()
+            .unwrap();
+        assert_eq!(header.nal_unit_type, NaluType::IdrWRadl);
+        assert!(header.first_slice_segment_in_pic_flag);
+        assert_eq!(header.slice_type, Some(SliceType::I));
+        assert_eq!(header.slice_qp_delta, -3);
+        assert_eq!(header.entry_point_offset_minus1, [100, 200]);
+        assert_eq!(header.slice_segment_data_offset, 6);
+
+        // Not a coded slice segment.
+        let aud = NaluSlice::new(&[0x46, 0x01, 0x50], 0).unwrap();
+        assert!(aud
+            .slice_segment_header(&slice_segment_context)
+            .unwrap()
+            .is_none());
+    }
+}
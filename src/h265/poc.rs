@@ -35,6 +35,16 @@ impl PocComputer {
         self.ref_pic_order_cnt_lsb = 0;
     }
 
+    /// Handles a non-VCL NAL unit in decoding order.
+    ///
+    /// After an `EOS_NUT` or `EOB_NUT`, the next picture is handled like the first picture in the bitstream, i.e. with
+    /// `NoRaslOutputFlag` equal to 1.
+    pub fn handle_non_vcl_nal_unit(&mut self, nal_unit_type: NaluType) {
+        if matches!(nal_unit_type, NaluType::EosNut | NaluType::EobNut) {
+            self.is_first_picture = true;
+        }
+    }
+
     /// Computes and returns `PicOrderCntVal`.
     pub fn compute_poc(
         &mut self,
@@ -126,3 +136,24 @@ impl PocComputer {
         pic_order_cnt
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn poc_after_end_of_sequence_works() {
+        let mut poc_computer = PocComputer::default();
+        assert_eq!(poc_computer.compute_poc_ex(0, 1, NaluType::CraNut, 12), 12);
+        assert_eq!(poc_computer.compute_poc_ex(0, 1, NaluType::TrailR, 14), 14);
+
+        // A CRA picture continuing the CVS wraps `slice_pic_order_cnt_lsb` around.
+        let mut continued = poc_computer.clone();
+        assert_eq!(continued.compute_poc_ex(0, 1, NaluType::CraNut, 2), 18);
+
+        // The first picture after an end of sequence starts a new CVS.
+        poc_computer.handle_non_vcl_nal_unit(NaluType::EosNut);
+        assert_eq!(poc_computer.compute_poc_ex(0, 1, NaluType::CraNut, 2), 2);
+        assert_eq!(poc_computer.compute_poc_ex(0, 1, NaluType::TrailR, 3), 3);
+    }
+}
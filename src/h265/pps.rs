@@ -6,8 +6,10 @@ use bitstream_io::BitRead as _;
 use bitstream_io::{BigEndian, BitReader};
 
 use crate::base::{read_exp_golomb_ue, read_exp_golomb_se};
+use crate::h265::scaling_list::ScalingListData;
+use crate::h265::sps::SequenceParameterSet;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct PictureParameterSet {
     /// From the NAL unit header.
     pub nuh_temporal_id_plus1: u8,
@@ -40,22 +42,97 @@ pub struct PictureParameterSet {
     /// `Some` means `deblocking_filter_control_present_flag == true`.
     pub deblocking_filter_control: Option<DeblockingFilterControl>,
     pub pps_scaling_list_data_present_flag: bool,
+    /// `Some` means `pps_scaling_list_data_present_flag == true`.
+    pub pps_scaling_list_data: Option<ScalingListData>,
     pub lists_modification_present_flag: bool,
     pub log2_parallel_merge_level_minus2: u8,
     pub slice_segment_header_extension_present_flag: bool,
     pub pps_extension_present_flag: bool,
+    /// `Some` means `pps_range_extension_flag == true`.
+    pub pps_range_extension: Option<PpsRangeExtension>,
+    /// `pps_multilayer_extension()` is not parsed, as it only applies to layers other than the base layer.
+    pub pps_multilayer_extension_flag: bool,
+    /// `pps_3d_extension()` is not parsed, as it only applies to layers other than the base layer.
+    pub pps_3d_extension_flag: bool,
+    /// `pps_scc_extension()` is not parsed. `SliceSegmentContext::new()` rejects SCC streams, whose slice segment headers
+    /// are not supported.
+    pub pps_scc_extension_flag: bool,
+    /// `pps_extension_data_flag`s following are ignored.
+    pub pps_extension_4bits: u8,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// See `pps_range_extension()` in _7.3.2.3.2 Picture parameter set range extension syntax_ in the spec.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PpsRangeExtension {
+    /// Specifies the maximum transform block size for which `transform_skip_flag` may be present.
+    ///
+    /// `Some` means `transform_skip_enabled_flag == true`.
+    pub log2_max_transform_skip_block_size_minus2: Option<u8>,
+    /// Specifies that `log2_res_scale_abs_plus1` and `res_scale_sign_flag` may be present in the transform unit syntax.
+    pub cross_component_prediction_enabled_flag: bool,
+    /// Specifies that `cu_chroma_qp_offset_flag` may be present in the transform unit syntax.
+    pub chroma_qp_offset_list_enabled_flag: bool,
+    /// `0` when `chroma_qp_offset_list_enabled_flag == false`.
+    pub diff_cu_chroma_qp_offset_depth: u8,
+    /// `[0, 5]`. `0` when `chroma_qp_offset_list_enabled_flag == false`.
+    pub chroma_qp_offset_list_len_minus1: u8,
+    /// `cb_qp_offset_list[i]` for `i` in `[0, chroma_qp_offset_list_len_minus1]`. `[-12, 12]`.
+    pub cb_qp_offset_list: [i8; 6],
+    /// `cr_qp_offset_list[i]` for `i` in `[0, chroma_qp_offset_list_len_minus1]`. `[-12, 12]`.
+    pub cr_qp_offset_list: [i8; 6],
+    /// The base 2 logarithm of the scaling parameter used to scale sample adaptive offset values for luma samples.
+    pub log2_sao_offset_scale_luma: u8,
+    /// The base 2 logarithm of the scaling parameter used to scale sample adaptive offset values for chroma samples.
+    pub log2_sao_offset_scale_chroma: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Tiles {
-    pub num_tile_columns_minus1: u8,
-    pub num_tile_rows_minus1: u8,
+    /// Less than `PicWidthInCtbsY`, which `TileLayout` checks. The level limits of `MaxTileCols` are not enforced.
+    pub num_tile_columns_minus1: u16,
+    /// Less than `PicHeightInCtbsY`, which `TileLayout` checks. The level limits of `MaxTileRows` are not enforced.
+    pub num_tile_rows_minus1: u16,
+    /// Specifies that tile column boundaries and likewise tile row boundaries are distributed uniformly across the picture.
     pub uniform_spacing_flag: bool,
+    /// `column_width_minus1[i]` for `i` in `[0, num_tile_columns_minus1)`, in units of CTBs.
+    ///
+    /// Empty when `uniform_spacing_flag == true`.
+    pub column_width_minus1: Vec<u16>,
+    /// `row_height_minus1[i]` for `i` in `[0, num_tile_rows_minus1)`, in units of CTBs.
+    ///
+    /// Empty when `uniform_spacing_flag == true`.
+    pub row_height_minus1: Vec<u16>,
     pub loop_filter_across_tiles_enabled_flag: bool,
 }
 
+/// The tile layout and the CTB raster and tile scanning conversion, derived as specified in
+/// _6.5.1 CTB raster and tile scanning conversion process_ in the spec.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TileLayout {
+    /// `PicWidthInCtbsY`.
+    pub pic_width_in_ctbs_y: u32,
+    /// `PicHeightInCtbsY`.
+    pub pic_height_in_ctbs_y: u32,
+    /// `colWidth[i]`: The width of the `i`-th tile column in units of CTBs.
+    pub col_width: Vec<u32>,
+    /// `rowHeight[j]`: The height of the `j`-th tile row in units of CTBs.
+    pub row_height: Vec<u32>,
+    /// `colBd[i]`: The location of the left boundary of the `i`-th tile column in units of CTBs. Has `col_width.len() + 1` entries.
+    pub col_bd: Vec<u32>,
+    /// `rowBd[j]`: The location of the top boundary of the `j`-th tile row in units of CTBs. Has `row_height.len() + 1` entries.
+    pub row_bd: Vec<u32>,
+    /// `CtbAddrRsToTs[ctbAddrRs]`: Converts a CTB address in CTB raster scan of a picture to a CTB address in tile scan.
+    pub ctb_addr_rs_to_ts: Vec<u32>,
+    /// `CtbAddrTsToRs[ctbAddrTs]`: Converts a CTB address in tile scan to a CTB address in CTB raster scan of a picture.
+    pub ctb_addr_ts_to_rs: Vec<u32>,
+    /// `TileId[ctbAddrTs]`: Converts a CTB address in tile scan to a tile ID.
+    pub tile_id: Vec<u32>,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct DeblockingFilterControl {
+    /// Specifies the presence of `deblocking_filter_override_flag` in the slice segment headers for pictures referring to the PPS.
+    pub deblocking_filter_override_enabled_flag: bool,
     /// Specifies that the deblocking filter is disabled for pictures referring to the PPS unless overriden by information present in the slice header.
     pub pps_deblocking_filter_disabled_flag: bool,
     /// Specifies the default deblocking parameter offset for β that is applied for slices referring to the PPS, unless overriden by information present in the slice header.
@@ -74,6 +151,8 @@ impl Default for Tiles {
             num_tile_columns_minus1: 0,
             num_tile_rows_minus1: 0,
             uniform_spacing_flag: true,
+            column_width_minus1: Vec::new(),
+            row_height_minus1: Vec::new(),
             loop_filter_across_tiles_enabled_flag: true,
         }
     }
@@ -124,11 +203,7 @@ impl PictureParameterSet {
         let entropy_coding_sync_enabled_flag = bit_reader.read_bit()?;
 
         let tiles: Option<Tiles> = if tiles_enabled_flag {
-            let uniform_spacing_flag = bit_reader.read_bit()?;
-            let num_tile_columns_minus1: u8 = read_exp_golomb_ue(&mut bit_reader)? as _;
-            let num_tile_rows_minus1: u8 = read_exp_golomb_ue(&mut bit_reader)? as _;
-
-            todo!("tiles_enabled_flag == true not supported");
+            Some(Tiles::from_bit_reader(&mut bit_reader)?)
         } else {
             None
         };
@@ -138,10 +213,6 @@ impl PictureParameterSet {
         let deblocking_filter_control_present_flag = bit_reader.read_bit()?;
         let deblocking_filter_control = if deblocking_filter_control_present_flag {
             let deblocking_filter_override_enabled_flag = bit_reader.read_bit()?;
-            if deblocking_filter_override_enabled_flag {
-                todo!("deblocking_filter_override_enabled_flag == true not supported");
-            }
-
             let pps_deblocking_filter_disabled_flag = bit_reader.read_bit()?;
             let pps_deblocking_filter_params = if !pps_deblocking_filter_disabled_flag {
                 let pps_beta_offset_div2: i8 = read_exp_golomb_se(&mut bit_reader)? as _;
@@ -152,6 +223,7 @@ impl PictureParameterSet {
             };
 
             Some(DeblockingFilterControl {
+                deblocking_filter_override_enabled_flag,
                 pps_deblocking_filter_disabled_flag,
                 pps_beta_offset_div2: pps_deblocking_filter_params.map(|x| x.0),
                 pps_tc_offset_div2: pps_deblocking_filter_params.map(|x| x.1),
@@ -161,18 +233,46 @@ impl PictureParameterSet {
         };
 
         let pps_scaling_list_data_present_flag = bit_reader.read_bit()?;
-        if pps_scaling_list_data_present_flag {
-            todo!("pps_scaling_list_data_present_flag == true not supported");
-        }
+        let pps_scaling_list_data = if pps_scaling_list_data_present_flag {
+            Some(ScalingListData::from_bit_reader(&mut bit_reader)?)
+        } else {
+            None
+        };
 
         let lists_modification_present_flag = bit_reader.read_bit()?;
         let log2_parallel_merge_level_minus2: u8 = read_exp_golomb_ue(&mut bit_reader)? as _;
 
         let slice_segment_header_extension_present_flag = bit_reader.read_bit()?;
         let pps_extension_present_flag = bit_reader.read_bit()?;
-        if pps_extension_present_flag {
-            todo!("pps_extension_present_flag == true not supported");
-        }
+        let (
+            pps_range_extension_flag,
+            pps_multilayer_extension_flag,
+            pps_3d_extension_flag,
+            pps_scc_extension_flag,
+            pps_extension_4bits,
+        ) = if pps_extension_present_flag {
+            (
+                bit_reader.read_bit()?,
+                bit_reader.read_bit()?,
+                bit_reader.read_bit()?,
+                bit_reader.read_bit()?,
+                bit_reader.read::<u8>(4)?,
+            )
+        } else {
+            (false, false, false, false, 0)
+        };
+
+        let pps_range_extension = if pps_range_extension_flag {
+            Some(PpsRangeExtension::from_bit_reader(
+                &mut bit_reader,
+                transform_skip_enabled_flag,
+            )?)
+        } else {
+            None
+        };
+
+        // The remaining extensions, i.e. from `pps_scc_extension()` on, as well as `pps_extension_data_flag`s for
+        // `pps_extension_4bits != 0`, are ignored since nothing follows them but `rbsp_trailing_bits()`.
 
         Ok(Self {
             nuh_temporal_id_plus1,
@@ -201,10 +301,372 @@ impl PictureParameterSet {
             pps_loop_filter_across_slices_enabled_flag,
             deblocking_filter_control,
             pps_scaling_list_data_present_flag,
+            pps_scaling_list_data,
             lists_modification_present_flag,
             log2_parallel_merge_level_minus2,
             slice_segment_header_extension_present_flag,
             pps_extension_present_flag,
+            pps_range_extension,
+            pps_multilayer_extension_flag,
+            pps_3d_extension_flag,
+            pps_scc_extension_flag,
+            pps_extension_4bits,
         })
     }
 }
+
+impl PpsRangeExtension {
+    fn from_bit_reader<R: Read>(
+        bit_reader: &mut BitReader<R, BigEndian>,
+        transform_skip_enabled_flag: bool,
+    ) -> Result<Self, io::Error> {
+        let mut value = Self::default();
+        if transform_skip_enabled_flag {
+            value.log2_max_transform_skip_block_size_minus2 =
+                Some(read_exp_golomb_ue(bit_reader)? as _);
+        }
+        value.cross_component_prediction_enabled_flag = bit_reader.read_bit()?;
+        value.chroma_qp_offset_list_enabled_flag = bit_reader.read_bit()?;
+        if value.chroma_qp_offset_list_enabled_flag {
+            value.diff_cu_chroma_qp_offset_depth = read_exp_golomb_ue(bit_reader)? as _;
+
+            let chroma_qp_offset_list_len_minus1 = read_exp_golomb_ue(bit_reader)?;
+            if chroma_qp_offset_list_len_minus1 > 5 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "chroma_qp_offset_list_len_minus1 out of range: {}",
+                        chroma_qp_offset_list_len_minus1
+                    ),
+                ));
+            }
+            value.chroma_qp_offset_list_len_minus1 = chroma_qp_offset_list_len_minus1 as u8;
+
+            for i in 0..=chroma_qp_offset_list_len_minus1 as usize {
+                value.cb_qp_offset_list[i] = read_exp_golomb_se(bit_reader)? as _;
+                value.cr_qp_offset_list[i] = read_exp_golomb_se(bit_reader)? as _;
+            }
+        }
+        value.log2_sao_offset_scale_luma = read_exp_golomb_ue(bit_reader)? as _;
+        value.log2_sao_offset_scale_chroma = read_exp_golomb_ue(bit_reader)? as _;
+
+        Ok(value)
+    }
+}
+
+impl Tiles {
+    fn from_bit_reader<R: Read>(
+        bit_reader: &mut BitReader<R, BigEndian>,
+    ) -> Result<Self, io::Error> {
+        // Each is less than the picture size in CTBs, which is checked against the SPS by `TileLayout`.
+        let read_u16 = |bit_reader: &mut BitReader<R, BigEndian>, name: &str| {
+            let value = read_exp_golomb_ue(bit_reader)?;
+            u16::try_from(value).map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{} out of range: {}", name, value),
+                )
+            })
+        };
+
+        let mut value = Self {
+            num_tile_columns_minus1: read_u16(bit_reader, "num_tile_columns_minus1")?,
+            num_tile_rows_minus1: read_u16(bit_reader, "num_tile_rows_minus1")?,
+            uniform_spacing_flag: bit_reader.read_bit()?,
+            ..Default::default()
+        };
+        if !value.uniform_spacing_flag {
+            for _ in 0..value.num_tile_columns_minus1 {
+                let column_width_minus1 = read_u16(bit_reader, "column_width_minus1")?;
+                value.column_width_minus1.push(column_width_minus1);
+            }
+            for _ in 0..value.num_tile_rows_minus1 {
+                let row_height_minus1 = read_u16(bit_reader, "row_height_minus1")?;
+                value.row_height_minus1.push(row_height_minus1);
+            }
+        }
+        value.loop_filter_across_tiles_enabled_flag = bit_reader.read_bit()?;
+
+        Ok(value)
+    }
+}
+
+impl TileLayout {
+    /// Derives the tile layout of pictures referring to `pps`, which in turn refers to `sps`.
+    ///
+    /// A picture consists of a single tile when `pps.tiles` is `None`.
+    pub fn new(sps: &SequenceParameterSet, pps: &PictureParameterSet) -> Result<Self, io::Error> {
+        Self::from_tiles(
+            sps.pic_width_in_ctbs_y(),
+            sps.pic_height_in_ctbs_y(),
+            pps.tiles.as_ref().unwrap_or(&Tiles::default()),
+        )
+    }
+
+    fn from_tiles(
+        pic_width_in_ctbs_y: u32,
+        pic_height_in_ctbs_y: u32,
+        tiles: &Tiles,
+    ) -> Result<Self, io::Error> {
+        let num_tile_columns = tiles.num_tile_columns_minus1 as u32 + 1;
+        let num_tile_rows = tiles.num_tile_rows_minus1 as u32 + 1;
+        if !tiles.uniform_spacing_flag
+            && (tiles.column_width_minus1.len() + 1 < num_tile_columns as usize
+                || tiles.row_height_minus1.len() + 1 < num_tile_rows as usize)
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Fewer explicit tile column widths or row heights than tiles",
+            ));
+        }
+        if num_tile_columns > pic_width_in_ctbs_y || num_tile_rows > pic_height_in_ctbs_y {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "{}x{} tiles exceed {}x{} CTBs",
+                    num_tile_columns, num_tile_rows, pic_width_in_ctbs_y, pic_height_in_ctbs_y
+                ),
+            ));
+        }
+
+        // (6-3), (6-4)
+        let derive_sizes = |num_tiles: u32, pic_size_in_ctbs: u32, size_minus1: &[u16]| {
+            if tiles.uniform_spacing_flag {
+                (0..num_tiles)
+                    .map(|i| {
+                        ((i + 1) * pic_size_in_ctbs) / num_tiles
+                            - (i * pic_size_in_ctbs) / num_tiles
+                    })
+                    .collect::<Vec<u32>>()
+            } else {
+                let mut sizes: Vec<u32> = size_minus1[..num_tiles as usize - 1]
+                    .iter()
+                    .map(|&x| x as u32 + 1)
+                    .collect();
+                let sum: u32 = sizes.iter().sum();
+                sizes.push(pic_size_in_ctbs.saturating_sub(sum));
+                sizes
+            }
+        };
+        let col_width = derive_sizes(
+            num_tile_columns,
+            pic_width_in_ctbs_y,
+            &tiles.column_width_minus1,
+        );
+        let row_height = derive_sizes(
+            num_tile_rows,
+            pic_height_in_ctbs_y,
+            &tiles.row_height_minus1,
+        );
+        if col_width.contains(&0) || row_height.contains(&0) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Explicit tile column widths or row heights exceed the picture size",
+            ));
+        }
+
+        // (6-5), (6-6)
+        let accumulate = |sizes: &[u32]| {
+            std::iter::once(0)
+                .chain(sizes.iter().scan(0, |bd, &size| {
+                    *bd += size;
+                    Some(*bd)
+                }))
+                .collect::<Vec<u32>>()
+        };
+        let col_bd = accumulate(&col_width);
+        let row_bd = accumulate(&row_height);
+
+        // (6-7)
+        let pic_size_in_ctbs_y = pic_width_in_ctbs_y * pic_height_in_ctbs_y;
+        let mut ctb_addr_rs_to_ts = vec![0; pic_size_in_ctbs_y as usize];
+        for (ctb_addr_rs, ctb_addr_ts) in ctb_addr_rs_to_ts.iter_mut().enumerate() {
+            let ctb_addr_rs = ctb_addr_rs as u32;
+            let tb_x = ctb_addr_rs % pic_width_in_ctbs_y;
+            let tb_y = ctb_addr_rs / pic_width_in_ctbs_y;
+            // The last column/row whose boundary does not exceed `tb_x`/`tb_y`.
+            let tile_x = col_bd[..col_width.len()].partition_point(|&bd| bd <= tb_x) - 1;
+            let tile_y = row_bd[..row_height.len()].partition_point(|&bd| bd <= tb_y) - 1;
+
+            *ctb_addr_ts = col_width[..tile_x]
+                .iter()
+                .map(|&width| row_height[tile_y] * width)
+                .sum::<u32>()
+                + row_height[..tile_y]
+                    .iter()
+                    .map(|&height| pic_width_in_ctbs_y * height)
+                    .sum::<u32>()
+                + (tb_y - row_bd[tile_y]) * col_width[tile_x]
+                + tb_x
+                - col_bd[tile_x];
+        }
+
+        // (6-8)
+        let mut ctb_addr_ts_to_rs = vec![0; pic_size_in_ctbs_y as usize];
+        for (ctb_addr_rs, &ctb_addr_ts) in ctb_addr_rs_to_ts.iter().enumerate() {
+            ctb_addr_ts_to_rs[ctb_addr_ts as usize] = ctb_addr_rs as u32;
+        }
+
+        // (6-9)
+        let mut tile_id = vec![0; pic_size_in_ctbs_y as usize];
+        let mut tile_idx = 0;
+        for j in 0..row_height.len() {
+            for i in 0..col_width.len() {
+                for y in row_bd[j]..row_bd[j + 1] {
+                    for x in col_bd[i]..col_bd[i + 1] {
+                        tile_id
+                            [ctb_addr_rs_to_ts[(y * pic_width_in_ctbs_y + x) as usize] as usize] =
+                            tile_idx;
+                    }
+                }
+                tile_idx += 1;
+            }
+        }
+
+        Ok(Self {
+            pic_width_in_ctbs_y,
+            pic_height_in_ctbs_y,
+            col_width,
+            row_height,
+            col_bd,
+            row_bd,
+            ctb_addr_rs_to_ts,
+            ctb_addr_ts_to_rs,
+            tile_id,
+        })
+    }
+
+    /// The number of tiles in a picture.
+    pub fn num_tiles(&self) -> usize {
+        self.col_width.len() * self.row_height.len()
+    }
+
+    /// `TileId[CtbAddrRsToTs[ctb_addr_rs]]`: The ID of the tile containing the CTB at `ctb_addr_rs` in CTB raster scan,
+    /// such as `slice_segment_address`.
+    pub fn tile_id_of_ctb_addr_rs(&self, ctb_addr_rs: u32) -> Option<u32> {
+        self.ctb_addr_rs_to_ts
+            .get(ctb_addr_rs as usize)
+            .map(|&ctb_addr_ts| self.tile_id[ctb_addr_ts as usize])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use bitstream_io::{BitWrite, BitWriter};
+
+    #[test]
+    fn tile_layout_works() {
+        // 5x3 CTBs split into 2x2 tiles with explicit spacing:
+        // |  0  1 |  2  3  4 |
+        // |  5  6 |  7  8  9 |
+        // |-------+----------|
+        // | 10 11 | 12 13 14 |
+        let mut bytes: Vec<u8> = Vec::new();
+        {
+            let mut bit_writer = BitWriter::endian(&mut bytes, BigEndian);
+            // num_tile_columns_minus1 = 1, num_tile_rows_minus1 = 1, uniform_spacing_flag = 0,
+            // column_width_minus1[0] = 1, row_height_minus1[0] = 1, loop_filter_across_tiles_enabled_flag = 1
+            for bit in [0, 1, 0, 0, 1, 0, 0, 0, 1, 0, 0, 1, 0, 1] {
+                bit_writer.write_bit(bit == 1).unwrap();
+            }
+            bit_writer.byte_align().unwrap();
+        }
+        let mut bit_reader = BitReader::endian(io::Cursor::new(bytes), BigEndian);
+        let tiles = Tiles::from_bit_reader(&mut bit_reader).unwrap();
+        assert_eq!(tiles.column_width_minus1, [1]);
+        assert_eq!(tiles.row_height_minus1, [1]);
+        assert!(tiles.loop_filter_across_tiles_enabled_flag);
+
+        let layout = TileLayout::from_tiles(5, 3, &tiles).unwrap();
+        assert_eq!(layout.col_width, [2, 3]);
+        assert_eq!(layout.row_height, [2, 1]);
+        assert_eq!(layout.col_bd, [0, 2, 5]);
+        assert_eq!(layout.row_bd, [0, 2, 3]);
+        assert_eq!(
+            layout.ctb_addr_rs_to_ts,
+            [0, 1, 4, 5, 6, 2, 3, 7, 8, 9, 10, 11, 12, 13, 14]
+        );
+        assert_eq!(
+            layout.ctb_addr_ts_to_rs,
+            [0, 1, 5, 6, 2, 3, 4, 7, 8, 9, 10, 11, 12, 13, 14]
+        );
+        assert_eq!(
+            layout.tile_id,
+            [0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 2, 2, 3, 3, 3]
+        );
+        assert_eq!(layout.num_tiles(), 4);
+        assert_eq!(layout.tile_id_of_ctb_addr_rs(7), Some(1));
+        assert_eq!(layout.tile_id_of_ctb_addr_rs(11), Some(2));
+
+        let layout = TileLayout::from_tiles(5, 3, &Tiles::default()).unwrap();
+        assert_eq!(layout.ctb_addr_rs_to_ts, (0..15).collect::<Vec<u32>>());
+        assert_eq!(layout.tile_id, [0; 15]);
+
+        let mut bytes: Vec<u8> = Vec::new();
+        {
+            let mut bit_writer = BitWriter::endian(&mut bytes, BigEndian);
+            // num_tile_columns_minus1 = 1, num_tile_rows_minus1 = 0, uniform_spacing_flag = 1,
+            // loop_filter_across_tiles_enabled_flag = 0
+            for bit in [0, 1, 0, 1, 1, 0] {
+                bit_writer.write_bit(bit == 1).unwrap();
+            }
+            bit_writer.byte_align().unwrap();
+        }
+        let mut bit_reader = BitReader::endian(io::Cursor::new(bytes), BigEndian);
+        let tiles = Tiles::from_bit_reader(&mut bit_reader).unwrap();
+        assert!(tiles.uniform_spacing_flag);
+        assert!(tiles.column_width_minus1.is_empty());
+        assert!(!tiles.loop_filter_across_tiles_enabled_flag);
+
+        let layout = TileLayout::from_tiles(5, 3, &tiles).unwrap();
+        assert_eq!(layout.col_width, [2, 3]);
+        assert_eq!(layout.row_height, [3]);
+        assert_eq!(layout.num_tiles(), 2);
+
+        // More tile columns than the level limit of 20 are not rejected by the syntax.
+        let tiles = Tiles {
+            num_tile_columns_minus1: 29,
+            ..Default::default()
+        };
+        let layout = TileLayout::from_tiles(30, 1, &tiles).unwrap();
+        assert_eq!(layout.col_width, [1; 30]);
+    }
+
+    #[test]
+    fn pps_range_extension_works() {
+        let mut bytes: Vec<u8> = Vec::new();
+        {
+            let mut bit_writer = BitWriter::endian(&mut bytes, BigEndian);
+            // log2_max_transform_skip_block_size_minus2 = 1, cross_component_prediction_enabled_flag = 1,
+            // chroma_qp_offset_list_enabled_flag = 1, diff_cu_chroma_qp_offset_depth = 0, chroma_qp_offset_list_len_minus1 = 1,
+            // cb_qp_offset_list = [-1, 0], cr_qp_offset_list = [2, 1],
+            // log2_sao_offset_scale_luma = 2, log2_sao_offset_scale_chroma = 0.
+            for bit in [
+                0, 1, 0, 1, 1, 1, 0, 1, 0, 0, 1, 1, 0, 0, 1, 0, 0, 1, 0, 1, 0, 0, 1, 1, 1,
+            ] {
+                bit_writer.write_bit(bit == 1).unwrap();
+            }
+            bit_writer.byte_align().unwrap();
+        }
+
+        let mut reader = io::Cursor::new(bytes);
+        let mut bit_reader = BitReader::endian(&mut reader, BigEndian);
+        let pps_range_extension =
+            PpsRangeExtension::from_bit_reader(&mut bit_reader, true).unwrap();
+
+        assert_eq!(
+            pps_range_extension.log2_max_transform_skip_block_size_minus2,
+            Some(1)
+        );
+        assert!(pps_range_extension.cross_component_prediction_enabled_flag);
+        assert!(pps_range_extension.chroma_qp_offset_list_enabled_flag);
+        assert_eq!(pps_range_extension.diff_cu_chroma_qp_offset_depth, 0);
+        assert_eq!(pps_range_extension.chroma_qp_offset_list_len_minus1, 1);
+        assert_eq!(pps_range_extension.cb_qp_offset_list[..2], [-1, 0]);
+        assert_eq!(pps_range_extension.cr_qp_offset_list[..2], [2, 1]);
+        assert_eq!(pps_range_extension.log2_sao_offset_scale_luma, 2);
+        assert_eq!(pps_range_extension.log2_sao_offset_scale_chroma, 0);
+    }
+}
@@ -10,7 +10,7 @@ pub struct ProfileTierLevel {
     pub sub_layers: [Option<ProfileTierLevelCommon>; 6],
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Default)]
 pub struct ProfileTierLevelCommon {
     /// `general_profile_space`: Specifies the context for the interpretation of `general_profile_idc` and `general_profile_compatibility_flags`. Shall be equal to 0.
     pub profile_space: u8,
@@ -22,6 +22,11 @@ pub struct ProfileTierLevelCommon {
     pub interlaced_source_flag: bool,
     pub non_packed_constraint_flag: bool,
     pub frame_only_constraint_flag: bool,
+    /// The 44 bits following `general_frame_only_constraint_flag`, from `general_max_12bit_constraint_flag` as the MSB
+    /// through `general_inbld_flag` as the LSB, with reserved bits in place of the flags a profile lacks.
+    ///
+    /// E.g. `general_one_picture_only_constraint_flag` is `constraint_flags >> 36 & 1` for profiles 2 and 4 to 11.
+    pub constraint_flags: u64,
     pub level_idc: Option<u8>,
 }
 
@@ -40,100 +45,28 @@ impl ProfileTierLevel {
         max_num_sub_layers_minus1: u8,
     ) -> Result<Self, io::Error> {
         let mut bit_reader = BitReader::endian(reader, BigEndian);
+        Self::from_bit_reader(
+            &mut bit_reader,
+            profile_present_flag,
+            max_num_sub_layers_minus1,
+        )
+    }
 
-        // VV Formally `if (profile_present_flag) {`.
-
-        let general_profile_space = bit_reader.read(2)?;
-        let general_tier_flag = bit_reader.read_bit()?;
-        let general_profile_idc = bit_reader.read(5)?;
-        let general_profile_compatibility_flags = {
-            let mut flags = [false; 32];
-            for flag in flags.iter_mut() {
-                *flag = bit_reader.read_bit()?;
-            }
-            flags
-        };
-        let general_progressive_source_flag = bit_reader.read_bit()?;
-        let general_interlaced_source_flag = bit_reader.read_bit()?;
-        let general_non_packed_constraint_flag = bit_reader.read_bit()?;
-        let general_frame_only_constraint_flag = bit_reader.read_bit()?;
-
-        let if_profile = |idc: u8| -> bool {
-            general_profile_idc == idc || general_profile_compatibility_flags[idc as usize]
-        };
-
-        if if_profile(4)
-            || if_profile(5)
-            || if_profile(6)
-            || if_profile(7)
-            || if_profile(8)
-            || if_profile(9)
-            || if_profile(10)
-            || if_profile(11)
-        {
-            let general_max_12bit_constraint_flag = bit_reader.read_bit()?;
-            let general_max_10bit_constraint_flag = bit_reader.read_bit()?;
-            let general_max_8bit_constraint_flag = bit_reader.read_bit()?;
-            let general_max_422chroma_constraint_flag = bit_reader.read_bit()?;
-            let general_max_420chroma_constraint_flag = bit_reader.read_bit()?;
-            let general_max_monochrome_constraint_flag = bit_reader.read_bit()?;
-            let general_intra_constraint_flag = bit_reader.read_bit()?;
-            let general_one_picture_only_constraint_flag = bit_reader.read_bit()?;
-            let general_lower_bit_rate_constraint_flag = bit_reader.read_bit()?;
-
-            if if_profile(5) || if_profile(9) || if_profile(10) || if_profile(11) {
-                let general_max_14bit_constraint_flag = bit_reader.read_bit()?;
-                // `vps_reserved_zero_33bits`: 33 bits
-                bit_reader.read::<u32>(32)?;
-                bit_reader.read_bit()?;
-            } else {
-                // `vps_reserved_zero_34bits`: 34 bits
-                bit_reader.read::<u32>(32)?;
-                bit_reader.read::<u32>(2)?;
-            }
-        } else if if_profile(2) {
-            // `general_reserved_zero_7bits`: 7 bits
-            bit_reader.read::<u32>(7)?;
-            let general_one_picture_only_constraint_flag = bit_reader.read_bit()?;
-            // `general_reserved_zero_35bits`: 35 bits
-            bit_reader.read::<u32>(32)?;
-            bit_reader.read::<u32>(3)?;
-        } else {
-            // `general_reserved_zero_43bits`: 43 bits
-            bit_reader.read::<u32>(32)?;
-            bit_reader.read::<u32>(11)?;
-        }
-
-        // > The number of bits in this syntax structure is not affected by this condition.
-        if if_profile(1)
-            || if_profile(2)
-            || if_profile(3)
-            || if_profile(4)
-            || if_profile(5)
-            || if_profile(9)
-            || if_profile(11)
-        {
-            let general_inbld_flag = bit_reader.read_bit()?;
+    /// See `profile_tier_level()` in _7.3.3 Profile, tier and level syntax_.
+    ///
+    /// When `profile_present_flag == false`, the profile information in `general` is left at its default and is to be
+    /// inferred by the caller.
+    pub fn from_bit_reader<R: Read>(
+        bit_reader: &mut BitReader<R, BigEndian>,
+        profile_present_flag: bool,
+        max_num_sub_layers_minus1: u8,
+    ) -> Result<Self, io::Error> {
+        let mut general = if profile_present_flag {
+            Self::read_profile(bit_reader)?
         } else {
-            // `general_reserved_zero_bit`: 1 bit
-            bit_reader.read_bit()?;
-        }
-
-        // ^^ Formally `if (profile_present_flag) {`.
-
-        let general_level_idc = bit_reader.read::<u8>(8)?;
-
-        let general = ProfileTierLevelCommon {
-            profile_space: general_profile_space,
-            tier_flag: general_tier_flag,
-            profile_idc: general_profile_idc,
-            profile_compatibility_flags: general_profile_compatibility_flags,
-            progressive_source_flag: general_progressive_source_flag,
-            interlaced_source_flag: general_interlaced_source_flag,
-            non_packed_constraint_flag: general_non_packed_constraint_flag,
-            frame_only_constraint_flag: general_frame_only_constraint_flag,
-            level_idc: Some(general_level_idc),
+            ProfileTierLevelCommon::default()
         };
+        general.level_idc = Some(bit_reader.read::<u8>(8)?);
 
         let mut sub_layer_profile_present_flags = [false; 7];
         let mut sub_layer_level_present_flags = [false; 7];
@@ -149,106 +82,23 @@ impl ProfileTierLevel {
         }
 
         let mut sub_layers = [None; 6];
-        for i in 0..max_num_sub_layers_minus1 {
-            sub_layers[i as usize] = if sub_layer_profile_present_flags[i as usize] {
-                let sub_layer_profile_space: u8 = bit_reader.read(2)?;
-                let sub_layer_tier_flag: bool = bit_reader.read_bit()?;
-                let sub_layer_profile_idc: u8 = bit_reader.read(5)?;
-                let sub_layer_profile_compatibility_flags: [bool; 32] = {
-                    let mut flags = [false; 32];
-                    for flag in flags.iter_mut() {
-                        *flag = bit_reader.read_bit()?;
-                    }
-                    flags
-                };
-                let sub_layer_progressive_source_flag: bool = bit_reader.read_bit()?;
-                let sub_layer_interlaced_source_flag: bool = bit_reader.read_bit()?;
-                let sub_layer_non_packed_constraint_flag: bool = bit_reader.read_bit()?;
-                let sub_layer_frame_only_constraint_flag: bool = bit_reader.read_bit()?;
-
-                let if_profile = |idc: u8| -> bool {
-                    sub_layer_profile_idc == idc
-                        || sub_layer_profile_compatibility_flags[idc as usize]
-                };
-
-                // > The number of bits in this syntax structure is not affected by this condition
-                if if_profile(4)
-                    || if_profile(5)
-                    || if_profile(6)
-                    || if_profile(7)
-                    || if_profile(8)
-                    || if_profile(9)
-                    || if_profile(10)
-                    || if_profile(11)
-                {
-                    let sub_layer_max_12bit_constraint_flag = bit_reader.read_bit()?;
-                    let sub_layer_max_10bit_constraint_flag = bit_reader.read_bit()?;
-                    let sub_layer_max_8bit_constraint_flag = bit_reader.read_bit()?;
-                    let sub_layer_max_422chroma_constraint_flag = bit_reader.read_bit()?;
-                    let sub_layer_max_420chroma_constraint_flag = bit_reader.read_bit()?;
-                    let sub_layer_max_monochrome_constraint_flag = bit_reader.read_bit()?;
-                    let sub_layer_intra_constraint_flag = bit_reader.read_bit()?;
-                    let sub_layer_one_picture_only_constraint_flag = bit_reader.read_bit()?;
-                    let sub_layer_lower_bit_rate_constraint_flag = bit_reader.read_bit()?;
-
-                    if if_profile(5) || if_profile(9) || if_profile(10) || if_profile(11) {
-                        let sub_layer_max_14bit_constraint_flag = bit_reader.read_bit()?;
-                        // `sub_layer_reserved_zero_33bits`: 33 bits
-                        bit_reader.read::<u32>(32)?;
-                        bit_reader.read_bit()?;
-                    } else {
-                        // `sub_layer_reserved_zero_34bits`: 34 bits
-                        bit_reader.read::<u32>(32)?;
-                        bit_reader.read::<u32>(2)?;
-                    }
-                } else if if_profile(2) {
-                    // `sub_layer_reserved_zero_7bits`: 7 bits
-                    bit_reader.read::<u32>(7)?;
-                    let sub_layer_one_picture_only_constraint_flag = bit_reader.read_bit()?;
-                    // `general_reserved_zero_35bits`: 35 bits
-                    bit_reader.read::<u32>(32)?;
-                    bit_reader.read::<u32>(3)?;
-                } else {
-                    // `sub_layer_reserved_zero_43bits`: 43 bits
-                    bit_reader.read::<u32>(32)?;
-                    bit_reader.read::<u32>(11)?;
-                }
-
-                // > The number of bits in this syntax structure is not affected by this condition.
-                if if_profile(1)
-                    || if_profile(2)
-                    || if_profile(3)
-                    || if_profile(4)
-                    || if_profile(5)
-                    || if_profile(9)
-                    || if_profile(11)
-                {
-                    let sub_layer_inbld_flag = bit_reader.read_bit()?;
-                } else {
-                    // `sub_layer_reserved_zero_bit`: 1 bit
-                    bit_reader.read_bit()?;
-                }
-
-                let sub_layer_level_idc = if sub_layer_level_present_flags[i as usize] {
-                    Some(bit_reader.read::<u8>(8)?)
-                } else {
-                    None
-                };
+        for i in 0..max_num_sub_layers_minus1 as usize {
+            if !sub_layer_profile_present_flags[i] && !sub_layer_level_present_flags[i] {
+                continue;
+            }
 
-                Some(ProfileTierLevelCommon {
-                    profile_space: sub_layer_profile_space,
-                    tier_flag: sub_layer_tier_flag,
-                    profile_idc: sub_layer_profile_idc,
-                    profile_compatibility_flags: sub_layer_profile_compatibility_flags,
-                    progressive_source_flag: sub_layer_progressive_source_flag,
-                    interlaced_source_flag: sub_layer_interlaced_source_flag,
-                    non_packed_constraint_flag: sub_layer_non_packed_constraint_flag,
-                    frame_only_constraint_flag: sub_layer_frame_only_constraint_flag,
-                    level_idc: sub_layer_level_idc,
-                })
+            // A sub-layer without its own profile information is given that of `general`.
+            let mut sub_layer = if sub_layer_profile_present_flags[i] {
+                Self::read_profile(bit_reader)?
+            } else {
+                general
+            };
+            sub_layer.level_idc = if sub_layer_level_present_flags[i] {
+                Some(bit_reader.read::<u8>(8)?)
             } else {
                 None
             };
+            sub_layers[i] = Some(sub_layer);
         }
 
         // 1: Main
@@ -264,4 +114,60 @@ impl ProfileTierLevel {
             sub_layers,
         })
     }
+
+    /// Reads the part of `profile_tier_level()` formally under `if (profilePresentFlag)`, or likewise for a sub-layer,
+    /// from `general_profile_space` up to `general_inbld_flag`.
+    fn read_profile<R: Read>(
+        bit_reader: &mut BitReader<R, BigEndian>,
+    ) -> Result<ProfileTierLevelCommon, io::Error> {
+        let general_profile_space = bit_reader.read(2)?;
+        let general_tier_flag = bit_reader.read_bit()?;
+        let general_profile_idc = bit_reader.read(5)?;
+        let general_profile_compatibility_flags = {
+            let mut flags = [false; 32];
+            for flag in flags.iter_mut() {
+                *flag = bit_reader.read_bit()?;
+            }
+            flags
+        };
+        let general_progressive_source_flag = bit_reader.read_bit()?;
+        let general_interlaced_source_flag = bit_reader.read_bit()?;
+        let general_non_packed_constraint_flag = bit_reader.read_bit()?;
+        let general_frame_only_constraint_flag = bit_reader.read_bit()?;
+
+        // From `general_max_12bit_constraint_flag`, or the reserved bits in its place, through `general_inbld_flag` or
+        // `general_reserved_zero_bit`. Which of them are flags depends on the profile, but their number doesn't.
+        let general_constraint_flags: u64 = bit_reader.read(44)?;
+
+        Ok(ProfileTierLevelCommon {
+            profile_space: general_profile_space,
+            tier_flag: general_tier_flag,
+            profile_idc: general_profile_idc,
+            profile_compatibility_flags: general_profile_compatibility_flags,
+            progressive_source_flag: general_progressive_source_flag,
+            interlaced_source_flag: general_interlaced_source_flag,
+            non_packed_constraint_flag: general_non_packed_constraint_flag,
+            frame_only_constraint_flag: general_frame_only_constraint_flag,
+            constraint_flags: general_constraint_flags,
+            level_idc: None,
+        })
+    }
+}
+
+impl ProfileTierLevelCommon {
+    /// The 32 `general_profile_compatibility_flag`s with `[0]` as the MSB, as stored in e.g. `hvcC`.
+    pub fn profile_compatibility_flags_u32(&self) -> u32 {
+        self.profile_compatibility_flags
+            .iter()
+            .fold(0, |flags, &flag| flags << 1 | flag as u32)
+    }
+
+    /// The 48 bits from `general_progressive_source_flag` through `general_inbld_flag`, as stored in e.g. `hvcC`.
+    pub fn constraint_indicator_flags(&self) -> u64 {
+        (self.progressive_source_flag as u64) << 47
+            | (self.interlaced_source_flag as u64) << 46
+            | (self.non_packed_constraint_flag as u64) << 45
+            | (self.frame_only_constraint_flag as u64) << 44
+            | self.constraint_flags
+    }
 }
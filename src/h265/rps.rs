@@ -3,7 +3,7 @@ use std::io::{self, Read};
 use bitstream_io::{BigEndian, BitReader};
 use bitstream_io::BitRead as _;
 
-use crate::base::{read_exp_golomb_ue, read_exp_golomb_ue_count_bits};
+use crate::base::read_exp_golomb_ue_count_bits;
 
 /// See _7.3.7 Short-term reference picture set syntax_ in the spec.
 #[derive(Debug, Clone, Copy)]
@@ -34,9 +34,16 @@ pub struct InterRefPicSetPrediction {
     pub delta_rps_sign: u32,
     pub abs_delta_rps_minus1: u16,
     /// `NumDeltaPocs[RefRpsIdx]`.
+    ///
+    /// `Some` for an RPS in a slice header.
     pub rps_idx_num_delta_pocs: Option<u8>,
-    pub used_by_curr_pic_flag: bool,
-    pub use_delta_flag: bool,
+    /// `used_by_curr_pic_flag[j]` for `j` in `[0, NumDeltaPocs[RefRpsIdx]]`.
+    pub used_by_curr_pic_flag: [bool; 17],
+    /// `use_delta_flag[j]` for `j` in `[0, NumDeltaPocs[RefRpsIdx]]`. Inferred to be `true` when not present.
+    pub use_delta_flag: [bool; 17],
+    /// The RPS derived from the reference RPS as specified in equations (7-61) and (7-62),
+    /// expressed in the same form as an explicitly signalled one.
+    pub derived: NonInterRefPicSetPrediction,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -78,7 +85,7 @@ impl ShortTermReferencePictureSet {
     }
 
     /// Calculates the variable `NumDeltaPocs[stRpsIdx]` as defined in _7.4.8 Short-term reference picture set semantics_ of the spec.
-    /// ```
+    /// ```text
     /// NumDeltaPocs[stRpsIdx] = NumNegativePics[stRpsIdx] + NumPositivePics[stRpsIdx]
     /// ```
     pub fn num_delta_pocs(&self) -> u8 {
@@ -87,7 +94,9 @@ impl ShortTermReferencePictureSet {
 
     pub fn num_negative_pics(&self) -> u8 {
         match &self.value {
-            ShortTermReferencePictureSetValue::InterRefPicSetPrediction(_) => todo!(),
+            ShortTermReferencePictureSetValue::InterRefPicSetPrediction(value) => {
+                value.derived.num_negative_pics
+            }
             ShortTermReferencePictureSetValue::NonInterRefPicSetPrediction(value) => {
                 value.num_negative_pics
             }
@@ -95,7 +104,9 @@ impl ShortTermReferencePictureSet {
     }
     pub fn num_positive_pics(&self) -> u8 {
         match &self.value {
-            ShortTermReferencePictureSetValue::InterRefPicSetPrediction(_) => todo!(),
+            ShortTermReferencePictureSetValue::InterRefPicSetPrediction(value) => {
+                value.derived.num_positive_pics
+            }
             ShortTermReferencePictureSetValue::NonInterRefPicSetPrediction(value) => {
                 value.num_positive_pics
             }
@@ -104,7 +115,9 @@ impl ShortTermReferencePictureSet {
 
     pub fn delta_poc_s0_minus1(&self) -> [u16; 16] {
         match &self.value {
-            ShortTermReferencePictureSetValue::InterRefPicSetPrediction(_) => todo!(),
+            ShortTermReferencePictureSetValue::InterRefPicSetPrediction(value) => {
+                value.derived.delta_poc_s0_minus1
+            }
             ShortTermReferencePictureSetValue::NonInterRefPicSetPrediction(value) => {
                 value.delta_poc_s0_minus1
             }
@@ -112,7 +125,9 @@ impl ShortTermReferencePictureSet {
     }
     pub fn used_by_curr_pic_s0_flag(&self) -> [bool; 16] {
         match &self.value {
-            ShortTermReferencePictureSetValue::InterRefPicSetPrediction(_) => todo!(),
+            ShortTermReferencePictureSetValue::InterRefPicSetPrediction(value) => {
+                value.derived.used_by_curr_pic_s0_flag
+            }
             ShortTermReferencePictureSetValue::NonInterRefPicSetPrediction(value) => {
                 value.used_by_curr_pic_s0_flag
             }
@@ -120,7 +135,9 @@ impl ShortTermReferencePictureSet {
     }
     pub fn delta_poc_s1_minus1(&self) -> [u16; 16] {
         match &self.value {
-            ShortTermReferencePictureSetValue::InterRefPicSetPrediction(_) => todo!(),
+            ShortTermReferencePictureSetValue::InterRefPicSetPrediction(value) => {
+                value.derived.delta_poc_s1_minus1
+            }
             ShortTermReferencePictureSetValue::NonInterRefPicSetPrediction(value) => {
                 value.delta_poc_s1_minus1
             }
@@ -128,7 +145,9 @@ impl ShortTermReferencePictureSet {
     }
     pub fn used_by_curr_pic_s1_flag(&self) -> [bool; 16] {
         match &self.value {
-            ShortTermReferencePictureSetValue::InterRefPicSetPrediction(_) => todo!(),
+            ShortTermReferencePictureSetValue::InterRefPicSetPrediction(value) => {
+                value.derived.used_by_curr_pic_s1_flag
+            }
             ShortTermReferencePictureSetValue::NonInterRefPicSetPrediction(value) => {
                 value.used_by_curr_pic_s1_flag
             }
@@ -143,37 +162,51 @@ impl ShortTermReferencePictureSet {
             .fold(0, |acc, (i, &flag)| acc | ((flag as u16) << i))
     }
     pub fn bitmask_used_by_curr_pic_s1_flag(&self) -> u16 {
-        // Convert the `used_by_curr_pic_s0_flag` array to a bitmask.
-        self.used_by_curr_pic_s0_flag()
+        // Convert the `used_by_curr_pic_s1_flag` array to a bitmask.
+        self.used_by_curr_pic_s1_flag()
             .iter()
             .enumerate()
             .fold(0, |acc, (i, &flag)| acc | ((flag as u16) << i))
     }
 
-    /// * `st_rps_index`: `stRpsIdx`; the index of the current `ShortTermReferencePictureSet`.
-    pub fn from_bit_reader<R: Read>(
-        bit_reader: &mut BitReader<R, BigEndian>,
-        st_rps_index: usize,
-        num_short_term_ref_pic_sets: usize,
-        bit_count: &mut u32,
-    ) -> Result<Self, io::Error> {
-        Self::from_bit_reader_impl(
-            bit_reader,
-            st_rps_index,
-            num_short_term_ref_pic_sets,
-            None,
-            bit_count,
-        )
+    /// `DeltaPocS0[stRpsIdx][i]`, i.e. the POC deltas of the negative pictures relative to the current picture.
+    ///
+    /// Only the first `num_negative_pics()` entries are meaningful.
+    pub fn delta_poc_s0(&self) -> [i32; 16] {
+        let delta_poc_s0_minus1 = self.delta_poc_s0_minus1();
+        let mut delta_poc_s0 = [0i32; 16];
+        let mut prev = 0;
+        for i in 0..self.num_negative_pics() as usize {
+            // DeltaPocS0[stRpsIdx][i] = DeltaPocS0[stRpsIdx][i - 1] - (delta_poc_s0_minus1[i] + 1)
+            delta_poc_s0[i] = prev - (delta_poc_s0_minus1[i] as i32 + 1);
+            prev = delta_poc_s0[i];
+        }
+        delta_poc_s0
+    }
+    /// `DeltaPocS1[stRpsIdx][i]`, i.e. the POC deltas of the positive pictures relative to the current picture.
+    ///
+    /// Only the first `num_positive_pics()` entries are meaningful.
+    pub fn delta_poc_s1(&self) -> [i32; 16] {
+        let delta_poc_s1_minus1 = self.delta_poc_s1_minus1();
+        let mut delta_poc_s1 = [0i32; 16];
+        let mut prev = 0;
+        for i in 0..self.num_positive_pics() as usize {
+            // DeltaPocS1[stRpsIdx][i] = DeltaPocS1[stRpsIdx][i - 1] + (delta_poc_s1_minus1[i] + 1)
+            delta_poc_s1[i] = prev + (delta_poc_s1_minus1[i] as i32 + 1);
+            prev = delta_poc_s1[i];
+        }
+        delta_poc_s1
     }
 
     /// * `st_rps_index`: `stRpsIdx`; the index of the current `ShortTermReferencePictureSet`.
-    /// * `sps_st_ref_pic_sets`: Required for when `inter_ref_pic_set_prediction_flag == true`.
-    /// * `slice_sps_st_ref_pic_sets`: Required for when parsing a slice segment header.
-    fn from_bit_reader_impl<R: Read>(
+    /// * `st_ref_pic_sets`: The candidate short-term RPSs preceding the current one, i.e. those in the SPS.
+    ///   Required for when `inter_ref_pic_set_prediction_flag == true`.
+    /// * `bit_count`: Incremented by the number of bits read.
+    pub fn from_bit_reader<R: Read>(
         bit_reader: &mut BitReader<R, BigEndian>,
         st_rps_index: usize,
         num_short_term_ref_pic_sets: usize,
-        slice_sps_st_ref_pic_sets: Option<&[ShortTermReferencePictureSet]>,
+        st_ref_pic_sets: &[ShortTermReferencePictureSet],
         bit_count: &mut u32,
     ) -> Result<Self, io::Error> {
         let inter_ref_pic_set_prediction_flag = if st_rps_index != 0 {
@@ -197,38 +230,74 @@ impl ShortTermReferencePictureSet {
             let abs_delta_rps_minus1: u16 =
                 read_exp_golomb_ue_count_bits(bit_reader, bit_count)? as _;
 
-            let rps_idx_num_delta_pocs = if let Some(delta_idx_minus1) = delta_idx_minus1 {
-                let slice_sps_st_ref_pic_sets = slice_sps_st_ref_pic_sets.expect(
-                    "st_ref_pic_set() in slice header. slice_sps_st_ref_pic_sets must be Some",
-                );
+            // RefRpsIdx = stRpsIdx - (delta_idx_minus1 + 1)
+            // `delta_idx_minus1` is inferred to be 0 when not present.
+            let ref_rps = st_rps_index
+                .checked_sub(delta_idx_minus1.unwrap_or(0) as usize + 1)
+                .and_then(|ref_rps_idx| st_ref_pic_sets.get(ref_rps_idx))
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "Invalid reference RPS for stRpsIdx {}: delta_idx_minus1 = {:?}, {} candidate RPSs available",
+                            st_rps_index,
+                            delta_idx_minus1,
+                            st_ref_pic_sets.len()
+                        ),
+                    )
+                })?;
+            let ref_num_delta_pocs = ref_rps.num_delta_pocs() as usize;
 
-                // refRpsIdx = stRpsIdx - (delta_idx_minus1 + 1)
-                let ref_rps_idx = st_rps_index - (delta_idx_minus1 as usize + 1);
-                let ref_rps = slice_sps_st_ref_pic_sets[ref_rps_idx];
-                Some(ref_rps.num_delta_pocs())
-            } else {
-                None
-            };
+            let mut used_by_curr_pic_flag = [false; 17];
+            let mut use_delta_flag = [true; 17];
+            for j in 0..=ref_num_delta_pocs {
+                *bit_count += 1;
+                used_by_curr_pic_flag[j] = bit_reader.read_bit()?;
+                if !used_by_curr_pic_flag[j] {
+                    *bit_count += 1;
+                    use_delta_flag[j] = bit_reader.read_bit()?;
+                }
+            }
 
-            todo!("inter_ref_pic_set_prediction_flag == true not supported");
+            // deltaRps = (1 - 2 * delta_rps_sign) * (abs_delta_rps_minus1 + 1)
+            let delta_rps = (1 - 2 * delta_rps_sign as i32) * (abs_delta_rps_minus1 as i32 + 1);
+            let derived = derive_inter_ref_pic_set(
+                ref_rps,
+                delta_rps,
+                &used_by_curr_pic_flag,
+                &use_delta_flag,
+            )?;
 
             ShortTermReferencePictureSetValue::InterRefPicSetPrediction(InterRefPicSetPrediction {
                 delta_idx_minus1,
                 delta_rps_sign,
                 abs_delta_rps_minus1,
-                rps_idx_num_delta_pocs,
-                use_delta_flag: true,
-                used_by_curr_pic_flag: false,
+                rps_idx_num_delta_pocs: delta_idx_minus1.map(|_| ref_num_delta_pocs as u8),
+                used_by_curr_pic_flag,
+                use_delta_flag,
+                derived,
             })
         } else {
-            let num_negative_pics: u8 = read_exp_golomb_ue_count_bits(bit_reader, bit_count)? as _;
-            let num_positive_pics: u8 = read_exp_golomb_ue_count_bits(bit_reader, bit_count)? as _;
+            let num_negative_pics = read_exp_golomb_ue_count_bits(bit_reader, bit_count)?;
+            let num_positive_pics = read_exp_golomb_ue_count_bits(bit_reader, bit_count)?;
+            if num_negative_pics as u64 + num_positive_pics as u64 > 16 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "num_negative_pics ({}) + num_positive_pics ({}) exceeds 16",
+                        num_negative_pics, num_positive_pics
+                    ),
+                ));
+            }
+            // Both are at most 16 now.
+            let num_negative_pics = num_negative_pics as u8;
+            let num_positive_pics = num_positive_pics as u8;
 
             let mut delta_poc_s0_minus1 = [0u16; 16];
             let mut used_by_curr_pic_s0_flag = [false; 16];
             for i in 0..num_negative_pics {
                 delta_poc_s0_minus1[i as usize] =
-                    read_exp_golomb_ue_count_bits(bit_reader, bit_count)? as _;
+                    read_delta_poc_minus1(bit_reader, bit_count, "delta_poc_s0_minus1", i)?;
                 *bit_count += 1;
                 used_by_curr_pic_s0_flag[i as usize] = bit_reader.read_bit()?;
             }
@@ -236,7 +305,7 @@ impl ShortTermReferencePictureSet {
             let mut used_by_curr_pic_s1_flag = [false; 16];
             for i in 0..num_positive_pics {
                 delta_poc_s1_minus1[i as usize] =
-                    read_exp_golomb_ue_count_bits(bit_reader, bit_count)? as _;
+                    read_delta_poc_minus1(bit_reader, bit_count, "delta_poc_s1_minus1", i)?;
                 *bit_count += 1;
                 used_by_curr_pic_s1_flag[i as usize] = bit_reader.read_bit()?;
             }
@@ -259,3 +328,227 @@ impl ShortTermReferencePictureSet {
         })
     }
 }
+
+/// Reads `delta_poc_s0_minus1[i]` or `delta_poc_s1_minus1[i]`, which shall be in the range of 0 to 2^15 − 1.
+fn read_delta_poc_minus1<R: Read>(
+    bit_reader: &mut BitReader<R, BigEndian>,
+    bit_count: &mut u32,
+    name: &str,
+    i: u8,
+) -> Result<u16, io::Error> {
+    let delta_poc_minus1 = read_exp_golomb_ue_count_bits(bit_reader, bit_count)?;
+    if delta_poc_minus1 > 0x7FFF {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}[{}] out of range: {}", name, i, delta_poc_minus1),
+        ));
+    }
+    Ok(delta_poc_minus1 as u16)
+}
+
+/// Derives `DeltaPocS0`, `UsedByCurrPicS0`, `DeltaPocS1` and `UsedByCurrPicS1` of the current RPS from the reference RPS
+/// as specified in equations (7-61) and (7-62) in _7.4.8 Short-term reference picture set semantics_ of the spec.
+fn derive_inter_ref_pic_set(
+    ref_rps: &ShortTermReferencePictureSet,
+    delta_rps: i32,
+    used_by_curr_pic_flag: &[bool; 17],
+    use_delta_flag: &[bool; 17],
+) -> Result<NonInterRefPicSetPrediction, io::Error> {
+    let ref_num_negative_pics = ref_rps.num_negative_pics() as usize;
+    let ref_num_positive_pics = ref_rps.num_positive_pics() as usize;
+    let ref_num_delta_pocs = ref_num_negative_pics + ref_num_positive_pics;
+    let ref_delta_poc_s0 = ref_rps.delta_poc_s0();
+    let ref_delta_poc_s1 = ref_rps.delta_poc_s1();
+
+    let mut delta_poc_s0: Vec<(i32, bool)> = Vec::with_capacity(16);
+    let mut delta_poc_s1: Vec<(i32, bool)> = Vec::with_capacity(16);
+
+    // (7-61)
+    for j in (0..ref_num_positive_pics).rev() {
+        let d_poc = ref_delta_poc_s1[j] + delta_rps;
+        if d_poc < 0 && use_delta_flag[ref_num_negative_pics + j] {
+            delta_poc_s0.push((d_poc, used_by_curr_pic_flag[ref_num_negative_pics + j]));
+        }
+    }
+    if delta_rps < 0 && use_delta_flag[ref_num_delta_pocs] {
+        delta_poc_s0.push((delta_rps, used_by_curr_pic_flag[ref_num_delta_pocs]));
+    }
+    for j in 0..ref_num_negative_pics {
+        let d_poc = ref_delta_poc_s0[j] + delta_rps;
+        if d_poc < 0 && use_delta_flag[j] {
+            delta_poc_s0.push((d_poc, used_by_curr_pic_flag[j]));
+        }
+    }
+
+    // (7-62)
+    for j in (0..ref_num_negative_pics).rev() {
+        let d_poc = ref_delta_poc_s0[j] + delta_rps;
+        if d_poc > 0 && use_delta_flag[j] {
+            delta_poc_s1.push((d_poc, used_by_curr_pic_flag[j]));
+        }
+    }
+    if delta_rps > 0 && use_delta_flag[ref_num_delta_pocs] {
+        delta_poc_s1.push((delta_rps, used_by_curr_pic_flag[ref_num_delta_pocs]));
+    }
+    for j in 0..ref_num_positive_pics {
+        let d_poc = ref_delta_poc_s1[j] + delta_rps;
+        if d_poc > 0 && use_delta_flag[ref_num_negative_pics + j] {
+            delta_poc_s1.push((d_poc, used_by_curr_pic_flag[ref_num_negative_pics + j]));
+        }
+    }
+
+    if delta_poc_s0.len() + delta_poc_s1.len() > 16 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Predicted RPS has too many pictures: {} negative, {} positive",
+                delta_poc_s0.len(),
+                delta_poc_s1.len()
+            ),
+        ));
+    }
+
+    // Express `DeltaPocS0`/`DeltaPocS1` in terms of `delta_poc_s0_minus1`/`delta_poc_s1_minus1`, which are the differences between adjacent entries.
+    let mut derived = NonInterRefPicSetPrediction {
+        num_negative_pics: delta_poc_s0.len() as u8,
+        num_positive_pics: delta_poc_s1.len() as u8,
+        delta_poc_s0_minus1: [0; 16],
+        used_by_curr_pic_s0_flag: [false; 16],
+        delta_poc_s1_minus1: [0; 16],
+        used_by_curr_pic_s1_flag: [false; 16],
+    };
+    let mut prev = 0;
+    for (i, &(d_poc, used)) in delta_poc_s0.iter().enumerate() {
+        derived.delta_poc_s0_minus1[i] = derived_delta_poc_minus1(prev - d_poc - 1)?;
+        derived.used_by_curr_pic_s0_flag[i] = used;
+        prev = d_poc;
+    }
+    let mut prev = 0;
+    for (i, &(d_poc, used)) in delta_poc_s1.iter().enumerate() {
+        derived.delta_poc_s1_minus1[i] = derived_delta_poc_minus1(d_poc - prev - 1)?;
+        derived.used_by_curr_pic_s1_flag[i] = used;
+        prev = d_poc;
+    }
+
+    Ok(derived)
+}
+
+fn derived_delta_poc_minus1(delta_poc_minus1: i32) -> Result<u16, io::Error> {
+    u16::try_from(delta_poc_minus1).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Predicted RPS has a gap between pictures out of range: {}",
+                delta_poc_minus1 + 1
+            ),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use bitstream_io::{BitWrite, BitWriter};
+
+    use crate::base::write_exp_golomb_ue;
+
+    #[test]
+    fn inter_ref_pic_set_prediction_works() {
+        let mut bytes: Vec<u8> = Vec::new();
+        {
+            let mut bit_writer = BitWriter::endian(&mut bytes, BigEndian);
+            // stRpsIdx 0: num_negative_pics = 2, num_positive_pics = 0, DeltaPocS0 = [-1, -2].
+            for bit in [0, 1, 1, 1, 1, 1, 1, 1] {
+                bit_writer.write_bit(bit == 1).unwrap();
+            }
+            // stRpsIdx 1: inter_ref_pic_set_prediction_flag = 1, delta_rps_sign = 1, abs_delta_rps_minus1 = 0,
+            // used_by_curr_pic_flag[0..=2] = [1, 0, 1] with use_delta_flag[1] = 1.
+            for bit in [1, 1, 1, 1, 0, 1, 1] {
+                bit_writer.write_bit(bit == 1).unwrap();
+            }
+            bit_writer.byte_align().unwrap();
+        }
+
+        let mut reader = io::Cursor::new(bytes);
+        let mut bit_reader = BitReader::endian(&mut reader, BigEndian);
+
+        let mut st_ref_pic_sets = Vec::new();
+        let mut bit_count = 0;
+        for st_rps_index in 0..2 {
+            let st_rps = ShortTermReferencePictureSet::from_bit_reader(
+                &mut bit_reader,
+                st_rps_index,
+                2,
+                &st_ref_pic_sets,
+                &mut bit_count,
+            )
+            .unwrap();
+            st_ref_pic_sets.push(st_rps);
+        }
+        assert_eq!(bit_count, 15);
+
+        let st_rps = &st_ref_pic_sets[1];
+        assert!(st_rps.as_inter_ref_pic_set_prediction().is_some());
+        assert_eq!(st_rps.num_negative_pics(), 3);
+        assert_eq!(st_rps.num_positive_pics(), 0);
+        assert_eq!(st_rps.delta_poc_s0()[..3], [-1, -2, -3]);
+        assert_eq!(st_rps.used_by_curr_pic_s0_flag()[..3], [true, true, false]);
+        assert_eq!(st_rps.bitmask_used_by_curr_pic_s0_flag(), 0b011);
+        assert_eq!(st_rps.rps_idx_num_delta_pocs(), None);
+
+        // stRpsIdx 0: DeltaPocS0 = [-32768, -65536, -98304].
+        // stRpsIdx 1: deltaRps = -1, keeping only the last picture of stRpsIdx 0, which is 98305 pictures away.
+        let mut bytes: Vec<u8> = Vec::new();
+        {
+            let mut bit_writer = BitWriter::endian(&mut bytes, BigEndian);
+            write_exp_golomb_ue(&mut bit_writer, 3);
+            write_exp_golomb_ue(&mut bit_writer, 0);
+            for _ in 0..3 {
+                write_exp_golomb_ue(&mut bit_writer, 0x7FFF);
+                bit_writer.write_bit(true).unwrap();
+            }
+            for bit in [1, 1, 1, 0, 0, 0, 0, 1, 0, 0] {
+                bit_writer.write_bit(bit == 1).unwrap();
+            }
+            bit_writer.byte_align().unwrap();
+        }
+        let mut bit_reader = BitReader::endian(io::Cursor::new(bytes), BigEndian);
+        let mut bit_count = 0;
+        let st_rps = ShortTermReferencePictureSet::from_bit_reader(
+            &mut bit_reader,
+            0,
+            2,
+            &[],
+            &mut bit_count,
+        )
+        .unwrap();
+        assert_eq!(st_rps.delta_poc_s0()[..3], [-32768, -65536, -98304]);
+        assert!(ShortTermReferencePictureSet::from_bit_reader(
+            &mut bit_reader,
+            1,
+            2,
+            &[st_rps],
+            &mut bit_count
+        )
+        .is_err());
+
+        // num_negative_pics = 256 would wrap around to 0 as a `u8`.
+        let mut bytes: Vec<u8> = Vec::new();
+        {
+            let mut bit_writer = BitWriter::endian(&mut bytes, BigEndian);
+            write_exp_golomb_ue(&mut bit_writer, 256);
+            write_exp_golomb_ue(&mut bit_writer, 0);
+            bit_writer.byte_align().unwrap();
+        }
+        let mut bit_reader = BitReader::endian(io::Cursor::new(bytes), BigEndian);
+        assert!(ShortTermReferencePictureSet::from_bit_reader(
+            &mut bit_reader,
+            0,
+            1,
+            &[],
+            &mut bit_count
+        )
+        .is_err());
+    }
+}
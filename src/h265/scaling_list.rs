@@ -0,0 +1,420 @@
+//! Scaling lists, a.k.a. quantization matrices.
+
+use std::io::{self, Read};
+
+use bitstream_io::BitRead as _;
+use bitstream_io::{BigEndian, BitReader};
+
+use crate::base::{read_exp_golomb_se, read_exp_golomb_ue};
+use crate::h265::sps::SequenceParameterSet;
+use crate::h265::pps::PictureParameterSet;
+
+/// _Table 7-6 – Specification of default values of `ScalingList[1..3][matrixId][i]` with `i = 0..63`_, for `matrixId = 0..2` (intra).
+const DEFAULT_SCALING_LIST_INTRA: [u8; 64] = [
+    16, 16, 16, 16, 16, 16, 16, 16, 16, 16, 17, 16, 17, 16, 17, 18, //
+    17, 18, 18, 17, 18, 21, 19, 20, 21, 20, 19, 21, 24, 22, 22, 24, //
+    24, 22, 22, 24, 25, 25, 27, 30, 27, 25, 25, 29, 31, 35, 35, 31, //
+    29, 36, 41, 44, 41, 36, 47, 54, 54, 47, 65, 70, 65, 88, 88, 115,
+];
+
+/// _Table 7-6 – Specification of default values of `ScalingList[1..3][matrixId][i]` with `i = 0..63`_, for `matrixId = 3..5` (inter).
+const DEFAULT_SCALING_LIST_INTER: [u8; 64] = [
+    16, 16, 16, 16, 16, 16, 16, 16, 16, 16, 17, 17, 17, 17, 17, 18, //
+    18, 18, 18, 18, 18, 20, 20, 20, 20, 20, 20, 20, 24, 24, 24, 24, //
+    24, 24, 24, 24, 25, 25, 25, 25, 25, 25, 25, 28, 28, 28, 28, 28, //
+    28, 33, 33, 33, 33, 33, 41, 41, 41, 41, 54, 54, 54, 71, 71, 91,
+];
+
+/// See `scaling_list_data()` in _7.3.4 Scaling list data syntax_ in the spec.
+///
+/// All inferred values are resolved during parsing, i.e. `scaling_list` and `scaling_list_dc_coef_minus8` hold the values
+/// for every `sizeId`/`matrixId` regardless of whether they were explicitly coded, predicted from a reference list, or
+/// inferred from the default lists in _Table 7-5_ and _Table 7-6_.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ScalingListData {
+    /// `scaling_list_pred_mode_flag[sizeId][matrixId]`.
+    ///
+    /// `false` specifies that the values of the scaling list are the same as the values of a reference scaling list.
+    pub scaling_list_pred_mode_flag: [[bool; 6]; 4],
+    /// `scaling_list_pred_matrix_id_delta[sizeId][matrixId]`.
+    ///
+    /// `0` specifies inference from the default scaling list, otherwise specifies the reference scaling list
+    /// `refMatrixId = matrixId - scaling_list_pred_matrix_id_delta[sizeId][matrixId] * (sizeId == 3 ? 3 : 1)`.
+    pub scaling_list_pred_matrix_id_delta: [[u8; 6]; 4],
+    /// `scaling_list_dc_coef_minus8[sizeId - 2][matrixId]`. `[-7, 247]`.
+    pub scaling_list_dc_coef_minus8: [[i16; 6]; 2],
+    /// `ScalingList[sizeId][matrixId][i]`, in up-right diagonal scan order.
+    ///
+    /// Only the first 16 coefficients are meaningful for `sizeId == 0`.
+    pub scaling_list: [[[u8; 64]; 6]; 4],
+}
+
+/// Scaling lists laid out like `StdVideoH265ScalingLists` in the Vulkan Video headers.
+///
+/// Coefficients are in up-right diagonal scan order, i.e. `ScalingList[sizeId][matrixId][i]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ScalingLists {
+    pub scaling_list_4x4: [[u8; 16]; 6],
+    pub scaling_list_8x8: [[u8; 64]; 6],
+    pub scaling_list_16x16: [[u8; 64]; 6],
+    /// Only `matrixId` 0 and 3 are coded for 32x32, which correspond to index 0 and 1 here.
+    pub scaling_list_32x32: [[u8; 64]; 2],
+    pub scaling_list_dc_coef_16x16: [u8; 6],
+    pub scaling_list_dc_coef_32x32: [u8; 2],
+}
+
+/// The scaling factor arrays `ScalingFactor[sizeId][matrixId][x][y]` as defined in _7.4.5 Scaling list data semantics_ in the spec.
+///
+/// Note that, as in the spec, each matrix is indexed as `[x][y]`, i.e. column first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScalingFactor {
+    pub scaling_factor_4x4: [[[u8; 4]; 4]; 6],
+    pub scaling_factor_8x8: [[[u8; 8]; 8]; 6],
+    pub scaling_factor_16x16: [[[u8; 16]; 16]; 6],
+    /// `matrixId` 1, 2, 4 and 5 are only derived when `ChromaArrayType == 3`, and are left as zeroes otherwise.
+    pub scaling_factor_32x32: [[[u8; 32]; 32]; 6],
+}
+
+impl Default for ScalingListData {
+    /// The scaling list data used when `scaling_list_enabled_flag == true` but no `scaling_list_data()` is present,
+    /// i.e. the default scaling lists specified in _Table 7-5_ and _Table 7-6_.
+    fn default() -> Self {
+        let mut scaling_list = [[[0u8; 64]; 6]; 4];
+        for (size_id, lists) in scaling_list.iter_mut().enumerate() {
+            for (matrix_id, list) in lists.iter_mut().enumerate() {
+                *list = default_scaling_list(size_id, matrix_id);
+            }
+        }
+
+        Self {
+            scaling_list_pred_mode_flag: [[false; 6]; 4],
+            scaling_list_pred_matrix_id_delta: [[0; 6]; 4],
+            scaling_list_dc_coef_minus8: [[8; 6]; 2],
+            scaling_list,
+        }
+    }
+}
+
+impl ScalingListData {
+    pub fn from_bit_reader<R: Read>(
+        bit_reader: &mut BitReader<R, BigEndian>,
+    ) -> Result<Self, io::Error> {
+        let mut value = Self::default();
+
+        for size_id in 0..4 {
+            let matrix_id_step = if size_id == 3 { 3 } else { 1 };
+            for matrix_id in (0..6).step_by(matrix_id_step) {
+                let scaling_list_pred_mode_flag = bit_reader.read_bit()?;
+                value.scaling_list_pred_mode_flag[size_id][matrix_id] = scaling_list_pred_mode_flag;
+
+                if !scaling_list_pred_mode_flag {
+                    let scaling_list_pred_matrix_id_delta =
+                        read_exp_golomb_ue(bit_reader)? as usize;
+                    if scaling_list_pred_matrix_id_delta * matrix_id_step > matrix_id {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!(
+                                "scaling_list_pred_matrix_id_delta[{}][{}] out of range: {}",
+                                size_id, matrix_id, scaling_list_pred_matrix_id_delta
+                            ),
+                        ));
+                    }
+                    value.scaling_list_pred_matrix_id_delta[size_id][matrix_id] =
+                        scaling_list_pred_matrix_id_delta as u8;
+
+                    if scaling_list_pred_matrix_id_delta == 0 {
+                        // Inferred from the default scaling list. `Self::default()` has already taken care of it.
+                        continue;
+                    }
+
+                    // refMatrixId = matrixId - scaling_list_pred_matrix_id_delta[sizeId][matrixId] * (sizeId == 3 ? 3 : 1)
+                    let ref_matrix_id =
+                        matrix_id - scaling_list_pred_matrix_id_delta * matrix_id_step;
+                    value.scaling_list[size_id][matrix_id] =
+                        value.scaling_list[size_id][ref_matrix_id];
+                    if size_id > 1 {
+                        value.scaling_list_dc_coef_minus8[size_id - 2][matrix_id] =
+                            value.scaling_list_dc_coef_minus8[size_id - 2][ref_matrix_id];
+                    }
+                } else {
+                    let mut next_coef: i32 = 8;
+                    let coef_num = usize::min(64, 1 << (4 + (size_id << 1)));
+                    if size_id > 1 {
+                        let scaling_list_dc_coef_minus8 = read_exp_golomb_se(bit_reader)?;
+                        if !(-7..=247).contains(&scaling_list_dc_coef_minus8) {
+                            return Err(io::Error::new(
+                                io::ErrorKind::InvalidData,
+                                format!(
+                                    "scaling_list_dc_coef_minus8[{}][{}] out of range: {}",
+                                    size_id - 2,
+                                    matrix_id,
+                                    scaling_list_dc_coef_minus8
+                                ),
+                            ));
+                        }
+                        value.scaling_list_dc_coef_minus8[size_id - 2][matrix_id] =
+                            scaling_list_dc_coef_minus8 as i16;
+                        next_coef = scaling_list_dc_coef_minus8 + 8;
+                    }
+
+                    let list = &mut value.scaling_list[size_id][matrix_id];
+                    list.fill(0);
+                    for coef in list.iter_mut().take(coef_num) {
+                        let scaling_list_delta_coef = read_exp_golomb_se(bit_reader)?;
+                        if !(-128..=127).contains(&scaling_list_delta_coef) {
+                            return Err(io::Error::new(
+                                io::ErrorKind::InvalidData,
+                                format!(
+                                    "scaling_list_delta_coef out of range: {}",
+                                    scaling_list_delta_coef
+                                ),
+                            ));
+                        }
+                        next_coef = (next_coef + scaling_list_delta_coef + 256).rem_euclid(256);
+                        *coef = next_coef as u8;
+                    }
+                }
+            }
+        }
+
+        Ok(value)
+    }
+
+    /// Selects the scaling list data in effect for pictures referring to `pps`, as specified in
+    /// _7.4.3.2 Sequence parameter set RBSP semantics_ and _7.4.3.3 Picture parameter set RBSP semantics_ in the spec.
+    ///
+    /// Returns `None` when `scaling_list_enabled_flag == false`, i.e. when a flat scaling factor of 16 is used.
+    pub fn select(sps: &SequenceParameterSet, pps: &PictureParameterSet) -> Option<Self> {
+        if !sps.scaling_list_enabled_flag {
+            return None;
+        }
+        Some(
+            pps.pps_scaling_list_data
+                .or(sps.sps_scaling_list_data)
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Returns the scaling lists in the layout of `StdVideoH265ScalingLists`.
+    pub fn to_scaling_lists(&self) -> ScalingLists {
+        let mut scaling_lists = ScalingLists {
+            scaling_list_4x4: [[0; 16]; 6],
+            scaling_list_8x8: self.scaling_list[1],
+            scaling_list_16x16: self.scaling_list[2],
+            scaling_list_32x32: [self.scaling_list[3][0], self.scaling_list[3][3]],
+            scaling_list_dc_coef_16x16: [0; 6],
+            scaling_list_dc_coef_32x32: [
+                (self.scaling_list_dc_coef_minus8[1][0] + 8) as u8,
+                (self.scaling_list_dc_coef_minus8[1][3] + 8) as u8,
+            ],
+        };
+        for matrix_id in 0..6 {
+            scaling_lists.scaling_list_4x4[matrix_id]
+                .copy_from_slice(&self.scaling_list[0][matrix_id][..16]);
+            scaling_lists.scaling_list_dc_coef_16x16[matrix_id] =
+                (self.scaling_list_dc_coef_minus8[0][matrix_id] + 8) as u8;
+        }
+        scaling_lists
+    }
+
+    /// Derives `ScalingFactor` as specified in _7.4.5 Scaling list data semantics_ in the spec.
+    ///
+    /// * `chroma_array_type`: `ChromaArrayType`. The 32x32 chroma matrices are only derived when it is equal to 3.
+    pub fn scaling_factor(&self, chroma_array_type: u8) -> ScalingFactor {
+        let scan_4x4 = up_right_diagonal_scan::<16>(4);
+        let scan_8x8 = up_right_diagonal_scan::<64>(8);
+
+        let mut scaling_factor = ScalingFactor {
+            scaling_factor_4x4: [[[0; 4]; 4]; 6],
+            scaling_factor_8x8: [[[0; 8]; 8]; 6],
+            scaling_factor_16x16: [[[0; 16]; 16]; 6],
+            scaling_factor_32x32: [[[0; 32]; 32]; 6],
+        };
+
+        for matrix_id in 0..6 {
+            for (i, &(x, y)) in scan_4x4.iter().enumerate() {
+                scaling_factor.scaling_factor_4x4[matrix_id][x][y] =
+                    self.scaling_list[0][matrix_id][i];
+            }
+            for (i, &(x, y)) in scan_8x8.iter().enumerate() {
+                scaling_factor.scaling_factor_8x8[matrix_id][x][y] =
+                    self.scaling_list[1][matrix_id][i];
+            }
+
+            upsample_8x8(
+                &mut scaling_factor.scaling_factor_16x16[matrix_id],
+                &scan_8x8,
+                &self.scaling_list[2][matrix_id],
+                self.scaling_list_dc_coef_minus8[0][matrix_id],
+            );
+
+            if matrix_id % 3 == 0 {
+                upsample_8x8(
+                    &mut scaling_factor.scaling_factor_32x32[matrix_id],
+                    &scan_8x8,
+                    &self.scaling_list[3][matrix_id],
+                    self.scaling_list_dc_coef_minus8[1][matrix_id],
+                );
+            } else if chroma_array_type == 3 {
+                // > When ChromaArrayType is equal to 3, the elements of the chroma quantization matrix of size 32x32,
+                // > ScalingFactor[3][matrixId][][], with matrixId = 1, 2, 4 and 5 are derived from the 16x16 scaling lists.
+                upsample_8x8(
+                    &mut scaling_factor.scaling_factor_32x32[matrix_id],
+                    &scan_8x8,
+                    &self.scaling_list[2][matrix_id],
+                    self.scaling_list_dc_coef_minus8[0][matrix_id],
+                );
+            }
+        }
+
+        scaling_factor
+    }
+}
+
+/// `ScalingList[sizeId][matrixId]` inferred from _Table 7-5_ and _Table 7-6_.
+fn default_scaling_list(size_id: usize, matrix_id: usize) -> [u8; 64] {
+    if size_id == 0 {
+        let mut list = [0; 64];
+        list[..16].fill(16);
+        list
+    } else if matrix_id < 3 {
+        DEFAULT_SCALING_LIST_INTRA
+    } else {
+        DEFAULT_SCALING_LIST_INTER
+    }
+}
+
+/// Fills an `N`x`N` scaling factor matrix by replicating each coefficient of an 8x8 scaling list, then replaces the DC.
+fn upsample_8x8<const N: usize>(
+    matrix: &mut [[u8; N]; N],
+    scan_8x8: &[(usize, usize); 64],
+    list: &[u8; 64],
+    dc_coef_minus8: i16,
+) {
+    let ratio = N / 8;
+    for (i, &(x, y)) in scan_8x8.iter().enumerate() {
+        for k in 0..ratio {
+            for j in 0..ratio {
+                matrix[x * ratio + k][y * ratio + j] = list[i];
+            }
+        }
+    }
+    matrix[0][0] = (dc_coef_minus8 + 8) as u8;
+}
+
+/// The up-right diagonal scan order array `diagScan[sPos][sComp]` for a block of `blk_size`x`blk_size`, as `(x, y)` tuples.
+///
+/// See _6.5.3 Up-right diagonal scan order array initialization process_ in the spec.
+fn up_right_diagonal_scan<const N: usize>(blk_size: usize) -> [(usize, usize); N] {
+    debug_assert_eq!(blk_size * blk_size, N);
+
+    let mut diag_scan = [(0, 0); N];
+    let mut i = 0;
+    let mut x: isize = 0;
+    let mut y: isize = 0;
+    while i < N {
+        while y >= 0 {
+            if (x as usize) < blk_size && (y as usize) < blk_size {
+                diag_scan[i] = (x as usize, y as usize);
+                i += 1;
+            }
+            y -= 1;
+            x += 1;
+        }
+        y = x;
+        x = 0;
+    }
+    diag_scan
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use bitstream_io::{BitWrite, BitWriter};
+
+    use crate::base::write_exp_golomb_ue;
+
+    #[test]
+    fn up_right_diagonal_scan_works() {
+        let scan = up_right_diagonal_scan::<16>(4);
+        assert_eq!(
+            &scan[..6],
+            &[(0, 0), (0, 1), (1, 0), (0, 2), (1, 1), (2, 0)]
+        );
+        assert_eq!(scan[15], (3, 3));
+    }
+
+    #[test]
+    fn scaling_list_data_prediction_works() {
+        let mut bytes: Vec<u8> = Vec::new();
+        {
+            let mut bit_writer = BitWriter::endian(&mut bytes, BigEndian);
+            for size_id in 0..4 {
+                let matrix_id_step = if size_id == 3 { 3 } else { 1 };
+                for matrix_id in (0..6).step_by(matrix_id_step) {
+                    if size_id == 2 && matrix_id == 0 {
+                        // scaling_list_pred_mode_flag = 1
+                        bit_writer.write_bit(true).unwrap();
+                        // scaling_list_dc_coef_minus8 = se(1): 010
+                        bit_writer.write(3, 0b010u8).unwrap();
+                        // scaling_list_delta_coef = se(-1): 011, then se(0): 1 (x63), i.e. all coefficients are 8.
+                        bit_writer.write(3, 0b011u8).unwrap();
+                        for _ in 0..63 {
+                            bit_writer.write_bit(true).unwrap();
+                        }
+                    } else if size_id == 2 && matrix_id == 1 {
+                        // scaling_list_pred_mode_flag = 0, scaling_list_pred_matrix_id_delta = ue(1): 010
+                        bit_writer.write_bit(false).unwrap();
+                        bit_writer.write(3, 0b010u8).unwrap();
+                    } else {
+                        // scaling_list_pred_mode_flag = 0, scaling_list_pred_matrix_id_delta = ue(0): 1
+                        bit_writer.write_bit(false).unwrap();
+                        bit_writer.write_bit(true).unwrap();
+                    }
+                }
+            }
+            bit_writer.byte_align().unwrap();
+        }
+
+        let mut reader = io::Cursor::new(bytes);
+        let mut bit_reader = BitReader::endian(&mut reader, BigEndian);
+        let data = ScalingListData::from_bit_reader(&mut bit_reader).unwrap();
+
+        assert_eq!(data.scaling_list[0][0][..16], [16; 16]);
+        assert_eq!(data.scaling_list[1][3], DEFAULT_SCALING_LIST_INTER);
+        assert_eq!(data.scaling_list[2][0], [8; 64]);
+        assert_eq!(data.scaling_list[2][1], [8; 64]);
+        assert_eq!(data.scaling_list_dc_coef_minus8[0][1], 1);
+        assert_eq!(data.scaling_list[2][2], DEFAULT_SCALING_LIST_INTRA);
+
+        let scaling_factor = data.scaling_factor(1);
+        assert_eq!(scaling_factor.scaling_factor_16x16[1][0][0], 9);
+        assert_eq!(scaling_factor.scaling_factor_16x16[1][15][15], 8);
+        assert_eq!(scaling_factor.scaling_factor_32x32[0][31][31], 115);
+        assert_eq!(scaling_factor.scaling_factor_32x32[1][31][31], 0);
+
+        let scaling_lists = data.to_scaling_lists();
+        assert_eq!(
+            scaling_lists.scaling_list_dc_coef_16x16,
+            [9, 9, 16, 16, 16, 16]
+        );
+
+        // scaling_list_pred_mode_flag = 1, scaling_list_delta_coef = se(128), which exceeds 127.
+        let mut bytes: Vec<u8> = Vec::new();
+        {
+            let mut bit_writer = BitWriter::endian(&mut bytes, BigEndian);
+            bit_writer.write_bit(true).unwrap();
+            write_exp_golomb_ue(&mut bit_writer, 255);
+            bit_writer.byte_align().unwrap();
+        }
+        let mut reader = io::Cursor::new(bytes);
+        let mut bit_reader = BitReader::endian(&mut reader, BigEndian);
+        assert_eq!(
+            ScalingListData::from_bit_reader(&mut bit_reader)
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+}
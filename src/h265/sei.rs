@@ -0,0 +1,525 @@
+//! SEI(Supplemental Enhancement Information) messages, as specified in _7.3.5 Supplemental enhancement information message syntax_ and _Annex D_.
+
+use std::any::Any;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::io::{self, Read};
+use std::sync::Arc;
+
+pub mod captions;
+pub mod hdr;
+pub mod hdr10plus;
+pub mod picture_hash;
+pub mod t35;
+pub mod time_code;
+pub mod timing;
+
+use crate::h265::sei::hdr::{
+    AlternativeTransferCharacteristics, ContentLightLevelInfo, MasteringDisplayColourVolume,
+};
+use crate::h265::sei::picture_hash::DecodedPictureHash;
+use crate::h265::sei::t35::UserDataRegisteredItuTT35;
+use crate::h265::sei::time_code::TimeCode;
+use crate::h265::sei::timing::{BufferingPeriod, PicTiming, RecoveryPoint, TimingSeiContext};
+use crate::h265::nalu::NaluType;
+use crate::h265::sps::SequenceParameterSet;
+
+/// See `sei_rbsp()` in _7.3.2.4 Supplemental enhancement information RBSP syntax_ in the spec.
+#[derive(Debug, Clone)]
+pub struct Sei {
+    pub sei_messages: Vec<SeiMessage>,
+}
+
+/// See `sei_message()` in _7.3.5 Supplemental enhancement information message syntax_ in the spec.
+#[derive(Debug, Clone)]
+pub struct SeiMessage {
+    /// `payloadType`: Accumulated from the `ff_byte`s and `last_payload_type_byte`.
+    pub payload_type: u32,
+    /// `payloadSize`: Accumulated from the `ff_byte`s and `last_payload_size_byte`.
+    pub payload_size: u32,
+    pub payload: SeiPayload,
+}
+
+/// `sei_payload()`, decoded according to `payloadType`.
+///
+/// See _D.2.1 General SEI message syntax_ in the spec.
+#[derive(Debug, Clone)]
+pub enum SeiPayload {
+    /// `buffering_period()`, `payloadType == 0`.
+    BufferingPeriod(BufferingPeriod),
+    /// `pic_timing()`, `payloadType == 1`.
+    PicTiming(PicTiming),
+    /// `user_data_registered_itu_t_t35()`, `payloadType == 4`.
+    UserDataRegisteredItuTT35(UserDataRegisteredItuTT35),
+    /// `user_data_unregistered()`, `payloadType == 5`.
+    UserDataUnregistered(UserDataUnregistered),
+    /// `recovery_point()`, `payloadType == 6`.
+    RecoveryPoint(RecoveryPoint),
+    /// `decoded_picture_hash()`, `payloadType == 132`.
+    DecodedPictureHash(DecodedPictureHash),
+    /// `time_code()`, `payloadType == 136`.
+    TimeCode(TimeCode),
+    /// `mastering_display_colour_volume()`, `payloadType == 137`.
+    MasteringDisplayColourVolume(MasteringDisplayColourVolume),
+    /// `content_light_level_info()`, `payloadType == 144`.
+    ContentLightLevelInfo(ContentLightLevelInfo),
+    /// `alternative_transfer_characteristics()`, `payloadType == 147`.
+    AlternativeTransferCharacteristics(AlternativeTransferCharacteristics),
+    /// Decoded by a decoder registered to `SeiPayloadRegistry`.
+    Custom(Arc<dyn CustomSeiPayload>),
+    /// The raw bytes of a payload for which there is no decoder.
+    Unknown(Vec<u8>),
+}
+
+/// See `user_data_unregistered()` in _D.2.7 User data unregistered SEI message syntax_ in the spec.
+#[derive(Debug, Clone)]
+pub struct UserDataUnregistered {
+    /// `uuid_iso_iec_11578`: A UUID identifying the type of the user data.
+    pub uuid_iso_iec_11578: [u8; 16],
+    pub user_data_payload_byte: Vec<u8>,
+}
+
+/// A payload decoded by a decoder registered to `SeiPayloadRegistry`.
+///
+/// Implemented for any `Debug + Send + Sync + 'static` type. Use `SeiPayload::downcast_ref()` to get the concrete type back.
+pub trait CustomSeiPayload: fmt::Debug + Send + Sync {
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: fmt::Debug + Send + Sync + 'static> CustomSeiPayload for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Decodes the bytes of `sei_payload()` into a `CustomSeiPayload`.
+pub type SeiPayloadDecoder =
+    dyn Fn(&[u8]) -> Result<Arc<dyn CustomSeiPayload>, io::Error> + Send + Sync;
+
+/// A registry of user-provided `sei_payload()` decoders, keyed by `payloadType`.
+///
+/// A registered decoder takes precedence over the built-in decoding of the same `payloadType`.
+#[derive(Clone, Default)]
+pub struct SeiPayloadRegistry {
+    decoders: HashMap<u32, Arc<SeiPayloadDecoder>>,
+}
+
+impl SeiPayloadRegistry {
+    /// Registers `decoder` for `payload_type`, replacing any decoder already registered for it.
+    pub fn register<T, F>(&mut self, payload_type: u32, decoder: F)
+    where
+        T: CustomSeiPayload + 'static,
+        F: Fn(&[u8]) -> Result<T, io::Error> + Send + Sync + 'static,
+    {
+        self.decoders.insert(
+            payload_type,
+            Arc::new(move |bytes| {
+                decoder(bytes).map(|value| Arc::new(value) as Arc<dyn CustomSeiPayload>)
+            }),
+        );
+    }
+
+    /// Removes the decoder registered for `payload_type`, returning whether there was one.
+    pub fn unregister(&mut self, payload_type: u32) -> bool {
+        self.decoders.remove(&payload_type).is_some()
+    }
+
+    pub fn is_registered(&self, payload_type: u32) -> bool {
+        self.decoders.contains_key(&payload_type)
+    }
+
+    fn decoder(&self, payload_type: u32) -> Option<&SeiPayloadDecoder> {
+        self.decoders
+            .get(&payload_type)
+            .map(|decoder| decoder.as_ref())
+    }
+}
+
+impl fmt::Debug for SeiPayloadRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut payload_types: Vec<&u32> = self.decoders.keys().collect();
+        payload_types.sort();
+        f.debug_struct("SeiPayloadRegistry")
+            .field("payload_types", &payload_types)
+            .finish()
+    }
+}
+
+impl Sei {
+    /// Reads `sei_rbsp()` up to and including `rbsp_trailing_bits()`.
+    ///
+    /// `nalu_type` is either `PrefixSeiNut` or `SuffixSeiNut`. A `payloadType` reserved for that kind of SEI NAL unit is
+    /// left `SeiPayload::Unknown`.
+    ///
+    /// `sps` is the SPS active for the access unit, which `buffering_period()` and `pic_timing()` depend on. They are
+    /// left `SeiPayload::Unknown` when it is `None`.
+    ///
+    /// `sequence_parameter_sets` are the SPSs received so far, by `sps_seq_parameter_set_id`. As a `buffering_period()`
+    /// may start a CVS with another SPS, its SPS is looked up there, and is used for the following messages as well.
+    pub fn from_rbsp_reader<R: Read>(
+        reader: &mut R,
+        nalu_type: NaluType,
+        sei_payload_registry: &SeiPayloadRegistry,
+        sps: Option<&SequenceParameterSet>,
+        sequence_parameter_sets: &BTreeMap<u8, SequenceParameterSet>,
+    ) -> Result<Self, io::Error> {
+        let mut rbsp: Vec<u8> = Vec::new();
+        reader.read_to_end(&mut rbsp)?;
+
+        let timing_sei_context = sps.map(TimingSeiContext::from_sps);
+        Self::from_rbsp(
+            &rbsp,
+            nalu_type,
+            sei_payload_registry,
+            timing_sei_context,
+            sequence_parameter_sets,
+        )
+    }
+
+    fn from_rbsp(
+        rbsp: &[u8],
+        nalu_type: NaluType,
+        sei_payload_registry: &SeiPayloadRegistry,
+        mut timing_sei_context: Option<TimingSeiContext>,
+        sequence_parameter_sets: &BTreeMap<u8, SequenceParameterSet>,
+    ) -> Result<Self, io::Error> {
+        let mut sei_messages: Vec<SeiMessage> = Vec::new();
+        let mut bytes = rbsp;
+        // `more_rbsp_data()`: Anything other than `rbsp_trailing_bits()` is left.
+        while !matches!(bytes, [0x80, rest @ ..] if rest.iter().all(|&x| x == 0)) {
+            let sei_message = SeiMessage::from_bytes(
+                &mut bytes,
+                nalu_type,
+                sei_payload_registry,
+                timing_sei_context.as_ref(),
+                sequence_parameter_sets,
+            )?;
+            // The SPS of a `buffering_period()` is active for the rest of the access unit.
+            if let Some(sps) = sei_message
+                .payload
+                .as_buffering_period()
+                .and_then(|value| sequence_parameter_sets.get(&value.bp_seq_parameter_set_id))
+            {
+                timing_sei_context = Some(TimingSeiContext::from_sps(sps));
+            }
+            sei_messages.push(sei_message);
+        }
+
+        Ok(Self { sei_messages })
+    }
+}
+
+impl SeiMessage {
+    /// Reads a `sei_message()` from the front of `bytes`, advancing it past the message.
+    fn from_bytes(
+        bytes: &mut &[u8],
+        nalu_type: NaluType,
+        sei_payload_registry: &SeiPayloadRegistry,
+        timing_sei_context: Option<&TimingSeiContext>,
+        sequence_parameter_sets: &BTreeMap<u8, SequenceParameterSet>,
+    ) -> Result<Self, io::Error> {
+        let payload_type = Self::read_ff_coded(bytes)?;
+        let payload_size = Self::read_ff_coded(bytes)?;
+
+        if payload_size as usize > bytes.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "SEI payloadSize {} exceeds the remaining {} bytes",
+                    payload_size,
+                    bytes.len()
+                ),
+            ));
+        }
+        let (payload_bytes, rest) = bytes.split_at(payload_size as usize);
+        *bytes = rest;
+
+        let payload = SeiPayload::decode(
+            payload_type,
+            payload_bytes,
+            nalu_type,
+            sei_payload_registry,
+            timing_sei_context,
+            sequence_parameter_sets,
+        )?;
+
+        Ok(Self {
+            payload_type,
+            payload_size,
+            payload,
+        })
+    }
+
+    /// Reads a value coded as a run of `ff_byte`s followed by a last byte, as for `payloadType` and `payloadSize`.
+    fn read_ff_coded(bytes: &mut &[u8]) -> Result<u32, io::Error> {
+        let mut value: u32 = 0;
+        loop {
+            let (&byte, rest) = bytes.split_first().ok_or_else(|| {
+                io::Error::new(io::ErrorKind::UnexpectedEof, "Truncated sei_message()")
+            })?;
+            *bytes = rest;
+            value += byte as u32;
+            if byte != 0xFF {
+                return Ok(value);
+            }
+        }
+    }
+}
+
+impl SeiPayload {
+    fn decode(
+        payload_type: u32,
+        bytes: &[u8],
+        nalu_type: NaluType,
+        sei_payload_registry: &SeiPayloadRegistry,
+        timing_sei_context: Option<&TimingSeiContext>,
+        sequence_parameter_sets: &BTreeMap<u8, SequenceParameterSet>,
+    ) -> Result<Self, io::Error> {
+        if Self::is_reserved_payload_type(payload_type, nalu_type) {
+            return Ok(Self::Unknown(bytes.to_vec()));
+        }
+        if let Some(decoder) = sei_payload_registry.decoder(payload_type) {
+            return Ok(Self::Custom(decoder(bytes)?));
+        }
+
+        match (payload_type, timing_sei_context) {
+            (0, _) => {
+                // Not necessarily the active SPS, as the SPS of the CVS it starts is activated only by its slices.
+                let bp_seq_parameter_set_id =
+                    BufferingPeriod::seq_parameter_set_id_from_bytes(bytes)?;
+                let context = sequence_parameter_sets
+                    .get(&bp_seq_parameter_set_id)
+                    .map(TimingSeiContext::from_sps)
+                    .or(timing_sei_context.copied());
+                Ok(match context {
+                    Some(context) => match BufferingPeriod::from_bytes(bytes, &context)? {
+                        Some(value) => Self::BufferingPeriod(value),
+                        // Refers to an SPS which has not been received.
+                        None => Self::Unknown(bytes.to_vec()),
+                    },
+                    None => Self::Unknown(bytes.to_vec()),
+                })
+            }
+            (1, Some(context)) => Ok(Self::PicTiming(PicTiming::from_bytes(bytes, context)?)),
+            (4, _) => Ok(Self::UserDataRegisteredItuTT35(
+                UserDataRegisteredItuTT35::from_bytes(bytes)?,
+            )),
+            (5, _) => Ok(Self::UserDataUnregistered(
+                UserDataUnregistered::from_bytes(bytes)?,
+            )),
+            (6, _) => Ok(Self::RecoveryPoint(RecoveryPoint::from_bytes(bytes)?)),
+            (132, _) => Ok(match DecodedPictureHash::from_bytes(bytes)? {
+                Some(value) => Self::DecodedPictureHash(value),
+                // A reserved `hash_type`.
+                None => Self::Unknown(bytes.to_vec()),
+            }),
+            (136, _) => Ok(Self::TimeCode(TimeCode::from_bytes(bytes)?)),
+            (137, _) => Ok(Self::MasteringDisplayColourVolume(
+                MasteringDisplayColourVolume::from_bytes(bytes)?,
+            )),
+            (144, _) => Ok(Self::ContentLightLevelInfo(
+                ContentLightLevelInfo::from_bytes(bytes)?,
+            )),
+            (147, _) => Ok(Self::AlternativeTransferCharacteristics(
+                AlternativeTransferCharacteristics::from_bytes(bytes)?,
+            )),
+            _ => Ok(Self::Unknown(bytes.to_vec())),
+        }
+    }
+
+    /// Whether `payloadType` is reserved in an SEI NAL unit of `nalu_type`, as per _D.2.1 General SEI message syntax_.
+    ///
+    /// Only the types with a built-in decoder are checked; the others are left to the registered decoders.
+    fn is_reserved_payload_type(payload_type: u32, nalu_type: NaluType) -> bool {
+        match nalu_type {
+            // `decoded_picture_hash()` is only allowed in suffix SEI.
+            NaluType::PrefixSeiNut => payload_type == 132,
+            NaluType::SuffixSeiNut => matches!(payload_type, 0 | 1 | 6 | 136 | 137 | 144 | 147),
+            _ => false,
+        }
+    }
+
+    /// Returns the payload as `T` if it is `Custom` and was decoded into a `T`.
+    pub fn downcast_ref<T: 'static>(&self) -> Option<&T> {
+        match self {
+            Self::Custom(value) => value.as_ref().as_any().downcast_ref::<T>(),
+            _ => None,
+        }
+    }
+
+    pub fn as_buffering_period(&self) -> Option<&BufferingPeriod> {
+        match self {
+            Self::BufferingPeriod(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_pic_timing(&self) -> Option<&PicTiming> {
+        match self {
+            Self::PicTiming(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_user_data_registered_itu_t_t35(&self) -> Option<&UserDataRegisteredItuTT35> {
+        match self {
+            Self::UserDataRegisteredItuTT35(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_user_data_unregistered(&self) -> Option<&UserDataUnregistered> {
+        match self {
+            Self::UserDataUnregistered(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_recovery_point(&self) -> Option<&RecoveryPoint> {
+        match self {
+            Self::RecoveryPoint(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_decoded_picture_hash(&self) -> Option<&DecodedPictureHash> {
+        match self {
+            Self::DecodedPictureHash(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_time_code(&self) -> Option<&TimeCode> {
+        match self {
+            Self::TimeCode(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_mastering_display_colour_volume(&self) -> Option<&MasteringDisplayColourVolume> {
+        match self {
+            Self::MasteringDisplayColourVolume(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_content_light_level_info(&self) -> Option<&ContentLightLevelInfo> {
+        match self {
+            Self::ContentLightLevelInfo(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_alternative_transfer_characteristics(
+        &self,
+    ) -> Option<&AlternativeTransferCharacteristics> {
+        match self {
+            Self::AlternativeTransferCharacteristics(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+impl UserDataUnregistered {
+    fn from_bytes(bytes: &[u8]) -> Result<Self, io::Error> {
+        if bytes.len() < 16 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("user_data_unregistered() too short: {} bytes", bytes.len()),
+            ));
+        }
+        let (uuid, user_data_payload_byte) = bytes.split_at(16);
+        Ok(Self {
+            uuid_iso_iec_11578: uuid.try_into().unwrap(),
+            user_data_payload_byte: user_data_payload_byte.to_vec(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct MyPayload(u16);
+
+    #[test]
+    fn sei_works() {
+        let mut rbsp: Vec<u8> = Vec::new();
+        // payloadType = 5 (user_data_unregistered), payloadSize = 18
+        rbsp.extend_from_slice(&[5, 18]);
+        rbsp.extend_from_slice(&[0xAB; 16]);
+        rbsp.extend_from_slice(&[1, 2]);
+        // payloadType = 255 + 45 = 300, payloadSize = 2
+        rbsp.extend_from_slice(&[0xFF, 45, 2, 0x12, 0x34]);
+        // payloadType = 200, payloadSize = 255 + 1 = 256
+        rbsp.extend_from_slice(&[200, 0xFF, 1]);
+        rbsp.extend_from_slice(&[0x5A; 256]);
+        // `rbsp_trailing_bits()`
+        rbsp.push(0x80);
+
+        let mut sei_payload_registry = SeiPayloadRegistry::default();
+        sei_payload_registry.register(300, |bytes| {
+            Ok(MyPayload(u16::from_be_bytes([bytes[0], bytes[1]])))
+        });
+
+        let sei = Sei::from_rbsp_reader(
+            &mut rbsp.as_slice(),
+            NaluType::PrefixSeiNut,
+            &sei_payload_registry,
+            None,
+            &BTreeMap::new(),
+        )
+        .unwrap();
+        assert_eq!(sei.sei_messages.len(), 3);
+
+        let user_data_unregistered = sei.sei_messages[0]
+            .payload
+            .as_user_data_unregistered()
+            .unwrap();
+        assert_eq!(user_data_unregistered.uuid_iso_iec_11578, [0xAB; 16]);
+        assert_eq!(user_data_unregistered.user_data_payload_byte, [1, 2]);
+
+        assert_eq!(sei.sei_messages[1].payload_type, 300);
+        assert_eq!(
+            sei.sei_messages[1].payload.downcast_ref::<MyPayload>(),
+            Some(&MyPayload(0x1234))
+        );
+
+        assert_eq!(sei.sei_messages[2].payload_type, 200);
+        assert_eq!(sei.sei_messages[2].payload_size, 256);
+        assert!(
+            matches!(&sei.sei_messages[2].payload, SeiPayload::Unknown(bytes) if bytes.len() == 256)
+        );
+
+        // recovery_point() is reserved in suffix SEI, and decoded_picture_hash() in prefix SEI.
+        for (rbsp, nalu_type) in [
+            ([6, 1, 0x80, 0x80], NaluType::SuffixSeiNut),
+            ([132, 1, 0x00, 0x80], NaluType::PrefixSeiNut),
+        ] {
+            let sei = Sei::from_rbsp_reader(
+                &mut rbsp.as_slice(),
+                nalu_type,
+                &SeiPayloadRegistry::default(),
+                None,
+                &BTreeMap::new(),
+            )
+            .unwrap();
+            assert!(matches!(
+                sei.sei_messages[0].payload,
+                SeiPayload::Unknown(_)
+            ));
+        }
+
+        // Truncated payload.
+        let rbsp = [5, 18, 0, 0, 0x80];
+        assert!(Sei::from_rbsp_reader(
+            &mut rbsp.as_slice(),
+            NaluType::PrefixSeiNut,
+            &SeiPayloadRegistry::default(),
+            None,
+            &BTreeMap::new()
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,225 @@
+//! CEA-608/708 closed captions, carried as ATSC A/53 `cc_data()` in `user_data_registered_itu_t_t35()`.
+
+use std::io;
+
+use crate::h265::sei::SeiMessage;
+
+/// `user_identifier` of ATSC A/53 user data.
+pub const ATSC_USER_IDENTIFIER: [u8; 4] = *b"GA94";
+/// `user_data_type_code` of `cc_data()`.
+pub const ATSC_USER_DATA_TYPE_CODE_CC_DATA: u8 = 0x03;
+
+/// See `cc_data()` in ATSC A/53 Part 4 and CEA-708.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CcData {
+    pub process_em_data_flag: bool,
+    /// When `false`, the caption data shall be discarded.
+    pub process_cc_data_flag: bool,
+    pub additional_data_flag: bool,
+    pub em_data: u8,
+    /// `cc_count` triplets.
+    pub triplets: Vec<CcDataTriplet>,
+}
+
+/// A `cc_valid`, `cc_type`, `cc_data_1` and `cc_data_2` construct of `cc_data()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CcDataTriplet {
+    pub cc_valid: bool,
+    /// `0`/`1` for CEA-608 data of field 1/2, `2` for DTVCC (CEA-708) packet data and `3` for the start of a DTVCC
+    /// packet.
+    pub cc_type: u8,
+    /// `cc_data_1` and `cc_data_2`.
+    pub cc_data: [u8; 2],
+}
+
+/// The caption data of an access unit, identified by the `PicOrderCntVal` of its picture.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessUnitCaptions {
+    pub pic_order_cnt_val: i32,
+    pub triplets: Vec<CcDataTriplet>,
+}
+
+/// Reorders the caption data of access units from decoding order to output order.
+///
+/// Pictures are output in increasing `PicOrderCntVal` within a CVS, and at most `sps_max_num_reorder_pics` pictures may
+/// precede a picture in decoding order and follow it in output order, which bounds the reordering delay.
+#[derive(Debug, Clone)]
+pub struct CaptionReorderer {
+    max_num_reorder_pics: usize,
+    /// The access units not output yet, including those without caption data.
+    pending: Vec<AccessUnitCaptions>,
+}
+
+impl CcData {
+    /// Reads from the bytes following `user_data_type_code`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, io::Error> {
+        let [flags, em_data, triplet_bytes @ ..] = bytes else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "cc_data() too short",
+            ));
+        };
+        let cc_count = (flags & 0x1F) as usize;
+        if triplet_bytes.len() < cc_count * 3 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "cc_data() with cc_count {} has only {} bytes of triplets",
+                    cc_count,
+                    triplet_bytes.len()
+                ),
+            ));
+        }
+
+        let triplets = triplet_bytes
+            .chunks_exact(3)
+            .take(cc_count)
+            .map(|triplet| CcDataTriplet {
+                // Following `marker_bits` of `'11111'`.
+                cc_valid: triplet[0] & 0x04 != 0,
+                cc_type: triplet[0] & 0x03,
+                cc_data: [triplet[1], triplet[2]],
+            })
+            .collect();
+
+        Ok(Self {
+            process_em_data_flag: flags & 0x80 != 0,
+            process_cc_data_flag: flags & 0x40 != 0,
+            additional_data_flag: flags & 0x20 != 0,
+            em_data: *em_data,
+            triplets,
+        })
+    }
+}
+
+impl CaptionReorderer {
+    /// `max_num_reorder_pics` should be `sps_max_num_reorder_pics[HighestTid]` of the active SPS.
+    pub fn new(max_num_reorder_pics: u8) -> Self {
+        Self {
+            max_num_reorder_pics: max_num_reorder_pics as usize,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Adds an access unit in decoding order, given the SEI messages in it, and returns the caption data of the access
+    /// units which are output by it, in output order.
+    ///
+    /// Every access unit must be pushed, whether it has caption data or not. `starts_coded_video_sequence` must be
+    /// `true` for an IRAP picture with `NoRaslOutputFlag == 1`, which outputs all pending pictures first.
+    pub fn push(
+        &mut self,
+        pic_order_cnt_val: i32,
+        starts_coded_video_sequence: bool,
+        sei_messages: &[SeiMessage],
+    ) -> Vec<AccessUnitCaptions> {
+        let mut output = if starts_coded_video_sequence {
+            self.flush()
+        } else {
+            Vec::new()
+        };
+
+        let triplets = sei_messages
+            .iter()
+            .filter_map(|sei_message| sei_message.payload.as_user_data_registered_itu_t_t35())
+            .filter_map(|user_data| user_data.as_cc_data())
+            .filter(|cc_data| cc_data.process_cc_data_flag)
+            .flat_map(|cc_data| cc_data.triplets.iter().copied())
+            .collect();
+        self.pending.push(AccessUnitCaptions {
+            pic_order_cnt_val,
+            triplets,
+        });
+
+        while self.pending.len() > self.max_num_reorder_pics {
+            output.extend(self.pop_first_in_output_order());
+        }
+        output
+    }
+
+    /// Outputs the caption data of all pending access units, e.g. at the end of the stream.
+    pub fn flush(&mut self) -> Vec<AccessUnitCaptions> {
+        let mut output: Vec<AccessUnitCaptions> = Vec::new();
+        while !self.pending.is_empty() {
+            output.extend(self.pop_first_in_output_order());
+        }
+        output
+    }
+
+    /// Returns `None` if the access unit has no caption data.
+    fn pop_first_in_output_order(&mut self) -> Option<AccessUnitCaptions> {
+        let index = self
+            .pending
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, access_unit)| access_unit.pic_order_cnt_val)
+            .map(|(index, _)| index)?;
+        let access_unit = self.pending.remove(index);
+        (!access_unit.triplets.is_empty()).then_some(access_unit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::BTreeMap;
+
+    use crate::h265::nalu::NaluType;
+    use crate::h265::sei::{Sei, SeiPayloadRegistry};
+
+    #[test]
+    fn captions_work() {
+        // Access units in decoding order with POCs 0, 4, 2, 1, 3, with a CEA-608 pair each but in the one of POC 4.
+        let mut reorderer = CaptionReorderer::new(2);
+        let mut output: Vec<AccessUnitCaptions> = Vec::new();
+        for (i, pic_order_cnt_val) in [0, 4, 2, 1, 3].into_iter().enumerate() {
+            // itu_t_t35_country_code, itu_t_t35_provider_code, user_identifier, user_data_type_code,
+            // process_cc_data_flag = 1, cc_count = 1 or 0, em_data, a triplet and marker_bits
+            let cc_count = if pic_order_cnt_val == 4 { 0 } else { 1 };
+            let mut bytes: Vec<u8> = vec![0xB5, 0x00, 0x31, b'G', b'A', b'9', b'4', 0x03];
+            bytes.extend_from_slice(&[0x40 | cc_count, 0xFF, 0xFC, b'A' + i as u8, 0x80, 0xFF]);
+
+            let mut rbsp: Vec<u8> = vec![4, bytes.len() as u8];
+            rbsp.extend_from_slice(&bytes);
+            rbsp.push(0x80);
+            let sei = Sei::from_rbsp_reader(
+                &mut rbsp.as_slice(),
+                NaluType::PrefixSeiNut,
+                &SeiPayloadRegistry::default(),
+                None,
+                &BTreeMap::new(),
+            )
+            .unwrap();
+
+            if i == 0 {
+                let cc_data = sei.sei_messages[0]
+                    .payload
+                    .as_user_data_registered_itu_t_t35()
+                    .unwrap()
+                    .as_cc_data()
+                    .unwrap();
+                assert!(cc_data.process_cc_data_flag);
+                assert_eq!(
+                    cc_data.triplets,
+                    [CcDataTriplet {
+                        cc_valid: true,
+                        cc_type: 0,
+                        cc_data: [b'A', 0x80],
+                    }]
+                );
+            }
+
+            output.extend(reorderer.push(pic_order_cnt_val, i == 0, &sei.sei_messages));
+        }
+        assert_eq!(output.len(), 3);
+        output.extend(reorderer.flush());
+
+        assert_eq!(
+            output
+                .iter()
+                .map(|x| (x.pic_order_cnt_val, x.triplets[0].cc_data[0]))
+                .collect::<Vec<_>>(),
+            [(0, b'A'), (1, b'D'), (2, b'C'), (3, b'E')]
+        );
+    }
+}
@@ -0,0 +1,242 @@
+//! HDR static metadata SEI messages.
+
+use std::io;
+
+use bitstream_io::BitRead as _;
+use bitstream_io::{BigEndian, BitReader};
+
+use crate::h265::sei::{SeiMessage, SeiPayload};
+use crate::h265::sps::ColourDescription;
+
+/// See `mastering_display_colour_volume()` in _Mastering display colour volume SEI message syntax_ in the spec.
+///
+/// Carries the SMPTE ST 2086 metadata. The primaries are in the order green, blue, red, as is conventional.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MasteringDisplayColourVolume {
+    /// `display_primaries_x[c]`: In increments of 0.00002.
+    pub display_primaries_x: [u16; 3],
+    /// `display_primaries_y[c]`: In increments of 0.00002.
+    pub display_primaries_y: [u16; 3],
+    /// In increments of 0.00002.
+    pub white_point_x: u16,
+    /// In increments of 0.00002.
+    pub white_point_y: u16,
+    /// In units of 0.0001 cd/m².
+    pub max_display_mastering_luminance: u32,
+    /// In units of 0.0001 cd/m².
+    pub min_display_mastering_luminance: u32,
+}
+
+/// See `content_light_level_info()` in _Content light level information SEI message syntax_ in the spec.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContentLightLevelInfo {
+    /// MaxCLL in cd/m². `0` means unknown.
+    pub max_content_light_level: u16,
+    /// MaxFALL in cd/m². `0` means unknown.
+    pub max_pic_average_light_level: u16,
+}
+
+/// See `alternative_transfer_characteristics()` in _Alternative transfer characteristics SEI message syntax_ in the spec.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AlternativeTransferCharacteristics {
+    /// Interpreted as `transfer_characteristics` in _Table E.4_, preferred over the one in the VUI, e.g. `18` (HLG) for a
+    /// stream signaling `14` (BT.2020 10-bit) for backward compatibility.
+    pub preferred_transfer_characteristics: u8,
+}
+
+/// The HDR10 static metadata of a CVS, gathered from the VUI and SEI messages.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HdrStaticMetadata {
+    /// `ColourDescription` from the VUI, with `transfer_characteristics` replaced by `preferred_transfer_characteristics`
+    /// if `alternative_transfer_characteristics` is present.
+    pub colour_description: Option<ColourDescription>,
+    pub mastering_display_colour_volume: Option<MasteringDisplayColourVolume>,
+    pub content_light_level_info: Option<ContentLightLevelInfo>,
+}
+
+impl MasteringDisplayColourVolume {
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Result<Self, io::Error> {
+        let mut bit_reader = BitReader::endian(bytes, BigEndian);
+
+        let mut display_primaries_x = [0; 3];
+        let mut display_primaries_y = [0; 3];
+        for c in 0..3 {
+            display_primaries_x[c] = bit_reader.read(16)?;
+            display_primaries_y[c] = bit_reader.read(16)?;
+        }
+
+        Ok(Self {
+            display_primaries_x,
+            display_primaries_y,
+            white_point_x: bit_reader.read(16)?,
+            white_point_y: bit_reader.read(16)?,
+            max_display_mastering_luminance: bit_reader.read(32)?,
+            min_display_mastering_luminance: bit_reader.read(32)?,
+        })
+    }
+
+    /// The CIE 1931 `(x, y)` chromaticity of each primary, normalized to `[0, 1]`.
+    pub fn display_primaries(&self) -> [(f64, f64); 3] {
+        std::array::from_fn(|c| {
+            (
+                Self::chromaticity(self.display_primaries_x[c]),
+                Self::chromaticity(self.display_primaries_y[c]),
+            )
+        })
+    }
+
+    /// The CIE 1931 `(x, y)` chromaticity of the white point, normalized to `[0, 1]`.
+    pub fn white_point(&self) -> (f64, f64) {
+        (
+            Self::chromaticity(self.white_point_x),
+            Self::chromaticity(self.white_point_y),
+        )
+    }
+
+    /// The nominal maximum display luminance in cd/m².
+    pub fn max_luminance(&self) -> f64 {
+        self.max_display_mastering_luminance as f64 * 0.0001
+    }
+
+    /// The nominal minimum display luminance in cd/m².
+    pub fn min_luminance(&self) -> f64 {
+        self.min_display_mastering_luminance as f64 * 0.0001
+    }
+
+    fn chromaticity(value: u16) -> f64 {
+        value as f64 * 0.00002
+    }
+}
+
+impl ContentLightLevelInfo {
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Result<Self, io::Error> {
+        let mut bit_reader = BitReader::endian(bytes, BigEndian);
+
+        Ok(Self {
+            max_content_light_level: bit_reader.read(16)?,
+            max_pic_average_light_level: bit_reader.read(16)?,
+        })
+    }
+}
+
+impl AlternativeTransferCharacteristics {
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Result<Self, io::Error> {
+        let mut bit_reader = BitReader::endian(bytes, BigEndian);
+
+        Ok(Self {
+            preferred_transfer_characteristics: bit_reader.read(8)?,
+        })
+    }
+
+    /// `colour_description` with `transfer_characteristics` replaced by `preferred_transfer_characteristics`.
+    pub fn apply_to(&self, colour_description: ColourDescription) -> ColourDescription {
+        ColourDescription {
+            transfer_characteristics: self.preferred_transfer_characteristics,
+            ..colour_description
+        }
+    }
+}
+
+impl HdrStaticMetadata {
+    /// Gathers the metadata from `ColourDescription` in the VUI and the SEI messages of an access unit, typically the
+    /// first one of a CVS.
+    pub fn new<'a>(
+        colour_description: Option<ColourDescription>,
+        sei_messages: impl IntoIterator<Item = &'a SeiMessage>,
+    ) -> Self {
+        let mut value = Self {
+            colour_description,
+            ..Default::default()
+        };
+
+        for sei_message in sei_messages {
+            match &sei_message.payload {
+                SeiPayload::MasteringDisplayColourVolume(x) => {
+                    value.mastering_display_colour_volume = Some(*x);
+                }
+                SeiPayload::ContentLightLevelInfo(x) => {
+                    value.content_light_level_info = Some(*x);
+                }
+                SeiPayload::AlternativeTransferCharacteristics(x) => {
+                    value.colour_description =
+                        Some(x.apply_to(colour_description.unwrap_or_default()));
+                }
+                _ => {}
+            }
+        }
+
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::BTreeMap;
+
+    use crate::h265::nalu::NaluType;
+    use crate::h265::sei::{Sei, SeiPayloadRegistry};
+
+    #[test]
+    fn hdr_static_metadata_works() {
+        let mut rbsp: Vec<u8> = Vec::new();
+        // `mastering_display_colour_volume()`: Display P3 primaries, D65, 1000 / 0.0001 cd/m²
+        rbsp.extend_from_slice(&[137, 24]);
+        for value in [13250u16, 34500, 7500, 3000, 34000, 16000, 15635, 16450] {
+            rbsp.extend_from_slice(&value.to_be_bytes());
+        }
+        rbsp.extend_from_slice(&10_000_000u32.to_be_bytes());
+        rbsp.extend_from_slice(&1u32.to_be_bytes());
+        // `content_light_level_info()`: MaxCLL = 1000, MaxFALL = 400
+        rbsp.extend_from_slice(&[144, 4, 0x03, 0xE8, 0x01, 0x90]);
+        // `alternative_transfer_characteristics()`: HLG
+        rbsp.extend_from_slice(&[147, 1, 18]);
+        rbsp.push(0x80);
+
+        let sei = Sei::from_rbsp_reader(
+            &mut rbsp.as_slice(),
+            NaluType::PrefixSeiNut,
+            &SeiPayloadRegistry::default(),
+            None,
+            &BTreeMap::new(),
+        )
+        .unwrap();
+
+        let mastering_display_colour_volume = sei.sei_messages[0]
+            .payload
+            .as_mastering_display_colour_volume()
+            .unwrap();
+        let [green, blue, red] = mastering_display_colour_volume.display_primaries();
+        assert!((green.0 - 0.265).abs() < 1e-9 && (green.1 - 0.69).abs() < 1e-9);
+        assert!((blue.0 - 0.15).abs() < 1e-9 && (blue.1 - 0.06).abs() < 1e-9);
+        assert!((red.0 - 0.68).abs() < 1e-9 && (red.1 - 0.32).abs() < 1e-9);
+        let (white_x, white_y) = mastering_display_colour_volume.white_point();
+        assert!((white_x - 0.3127).abs() < 1e-9 && (white_y - 0.329).abs() < 1e-9);
+        assert!((mastering_display_colour_volume.max_luminance() - 1000.0).abs() < 1e-9);
+        assert!((mastering_display_colour_volume.min_luminance() - 0.0001).abs() < 1e-12);
+
+        let hdr_static_metadata = HdrStaticMetadata::new(
+            Some(ColourDescription {
+                colour_primaries: 9,
+                transfer_characteristics: 14,
+                matrix_coeffs: 9,
+            }),
+            &sei.sei_messages,
+        );
+        let colour_description = hdr_static_metadata.colour_description.unwrap();
+        assert_eq!(colour_description.colour_primaries, 9);
+        assert_eq!(colour_description.transfer_characteristics, 18);
+        assert_eq!(
+            hdr_static_metadata.content_light_level_info,
+            Some(ContentLightLevelInfo {
+                max_content_light_level: 1000,
+                max_pic_average_light_level: 400,
+            })
+        );
+        assert_eq!(
+            hdr_static_metadata.mastering_display_colour_volume,
+            Some(*mastering_display_colour_volume)
+        );
+    }
+}
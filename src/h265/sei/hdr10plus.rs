@@ -0,0 +1,247 @@
+//! HDR10+ dynamic metadata, as specified in SMPTE ST 2094-40 and carried in `user_data_registered_itu_t_t35()`.
+
+use std::io;
+
+use bitstream_io::BitRead as _;
+use bitstream_io::{BigEndian, BitReader};
+
+/// `application_identifier` of ST 2094-40.
+pub const HDR10_PLUS_APPLICATION_IDENTIFIER: u8 = 4;
+
+/// The ST 2094-40 metadata of a picture, following `itu_t_t35_terminal_provider_oriented_code`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hdr10Plus {
+    /// Shall be `4`.
+    pub application_identifier: u8,
+    pub application_version: u8,
+    /// The number of processing windows, in `[1, 3]`.
+    pub num_windows: u8,
+    /// The geometry of the processing windows `w` in `[1, num_windows)`. Window `0` is the whole picture.
+    pub processing_windows: Vec<ProcessingWindow>,
+    /// In cd/m².
+    pub targeted_system_display_maximum_luminance: u32,
+    /// `Some` means `targeted_system_display_actual_peak_luminance_flag == true`.
+    pub targeted_system_display_actual_peak_luminance: Option<ActualPeakLuminance>,
+    /// The per-window metadata for `w` in `[0, num_windows)`.
+    pub windows: Vec<Hdr10PlusWindow>,
+    /// `Some` means `mastering_display_actual_peak_luminance_flag == true`.
+    pub mastering_display_actual_peak_luminance: Option<ActualPeakLuminance>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProcessingWindow {
+    pub window_upper_left_corner_x: u16,
+    pub window_upper_left_corner_y: u16,
+    pub window_lower_right_corner_x: u16,
+    pub window_lower_right_corner_y: u16,
+    pub center_of_ellipse_x: u16,
+    pub center_of_ellipse_y: u16,
+    pub rotation_angle: u8,
+    pub semimajor_axis_internal_ellipse: u16,
+    pub semimajor_axis_external_ellipse: u16,
+    pub semiminor_axis_external_ellipse: u16,
+    pub overlap_process_option: bool,
+}
+
+/// A 2D array of normalized actual peak luminance values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActualPeakLuminance {
+    pub num_rows: u8,
+    pub num_cols: u8,
+    /// Indexed `[i][j]` for row `i` and column `j`. In increments of 1/15.
+    pub values: Vec<Vec<u8>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hdr10PlusWindow {
+    /// `maxscl[w][i]` for the R, G and B components: In increments of 0.00001.
+    pub maxscl: [u32; 3],
+    /// In increments of 0.00001.
+    pub average_maxrgb: u32,
+    /// `distribution_maxrgb_percentages[w][i]` and `distribution_maxrgb_percentiles[w][i]`.
+    pub distribution_maxrgb: Vec<DistributionMaxrgb>,
+    /// In increments of 0.001.
+    pub fraction_bright_pixels: u16,
+    /// `Some` means `tone_mapping_flag[w] == true`.
+    pub tone_mapping: Option<ToneMapping>,
+    /// `Some` means `color_saturation_mapping_flag[w] == true`. In increments of 1/8.
+    pub color_saturation_weight: Option<u8>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DistributionMaxrgb {
+    /// In percent.
+    pub percentage: u8,
+    /// In increments of 0.00001.
+    pub percentile: u32,
+}
+
+/// The parameters of the tone mapping function, i.e. a knee point followed by a Bézier curve.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToneMapping {
+    /// In increments of 1/4095.
+    pub knee_point_x: u16,
+    /// In increments of 1/4095.
+    pub knee_point_y: u16,
+    /// In increments of 1/1023.
+    pub bezier_curve_anchors: Vec<u16>,
+}
+
+impl Hdr10Plus {
+    /// Reads from the bytes following `itu_t_t35_terminal_provider_oriented_code`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, io::Error> {
+        let mut bit_reader = BitReader::endian(bytes, BigEndian);
+
+        let application_identifier: u8 = bit_reader.read(8)?;
+        if application_identifier != HDR10_PLUS_APPLICATION_IDENTIFIER {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Not ST 2094-40: application_identifier = {}",
+                    application_identifier
+                ),
+            ));
+        }
+        let application_version: u8 = bit_reader.read(8)?;
+        let num_windows: u8 = bit_reader.read(2)?;
+        if num_windows == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "ST 2094-40 num_windows == 0",
+            ));
+        }
+
+        let mut processing_windows: Vec<ProcessingWindow> = Vec::new();
+        for _ in 1..num_windows {
+            processing_windows.push(ProcessingWindow {
+                window_upper_left_corner_x: bit_reader.read(16)?,
+                window_upper_left_corner_y: bit_reader.read(16)?,
+                window_lower_right_corner_x: bit_reader.read(16)?,
+                window_lower_right_corner_y: bit_reader.read(16)?,
+                center_of_ellipse_x: bit_reader.read(16)?,
+                center_of_ellipse_y: bit_reader.read(16)?,
+                rotation_angle: bit_reader.read(8)?,
+                semimajor_axis_internal_ellipse: bit_reader.read(16)?,
+                semimajor_axis_external_ellipse: bit_reader.read(16)?,
+                semiminor_axis_external_ellipse: bit_reader.read(16)?,
+                overlap_process_option: bit_reader.read_bit()?,
+            });
+        }
+
+        let targeted_system_display_maximum_luminance: u32 = bit_reader.read(27)?;
+        let targeted_system_display_actual_peak_luminance_flag = bit_reader.read_bit()?;
+        let targeted_system_display_actual_peak_luminance =
+            if targeted_system_display_actual_peak_luminance_flag {
+                Some(ActualPeakLuminance::from_bit_reader(&mut bit_reader)?)
+            } else {
+                None
+            };
+
+        let mut windows: Vec<Hdr10PlusWindow> = Vec::new();
+        for _ in 0..num_windows {
+            let mut maxscl = [0; 3];
+            for value in maxscl.iter_mut() {
+                *value = bit_reader.read(17)?;
+            }
+            let average_maxrgb: u32 = bit_reader.read(17)?;
+            let num_distribution_maxrgb_percentiles: u8 = bit_reader.read(4)?;
+            let mut distribution_maxrgb: Vec<DistributionMaxrgb> = Vec::new();
+            for _ in 0..num_distribution_maxrgb_percentiles {
+                distribution_maxrgb.push(DistributionMaxrgb {
+                    percentage: bit_reader.read(7)?,
+                    percentile: bit_reader.read(17)?,
+                });
+            }
+            let fraction_bright_pixels: u16 = bit_reader.read(10)?;
+
+            windows.push(Hdr10PlusWindow {
+                maxscl,
+                average_maxrgb,
+                distribution_maxrgb,
+                fraction_bright_pixels,
+                tone_mapping: None,
+                color_saturation_weight: None,
+            });
+        }
+
+        let mastering_display_actual_peak_luminance_flag = bit_reader.read_bit()?;
+        let mastering_display_actual_peak_luminance =
+            if mastering_display_actual_peak_luminance_flag {
+                Some(ActualPeakLuminance::from_bit_reader(&mut bit_reader)?)
+            } else {
+                None
+            };
+
+        for window in windows.iter_mut() {
+            let tone_mapping_flag = bit_reader.read_bit()?;
+            if tone_mapping_flag {
+                let knee_point_x: u16 = bit_reader.read(12)?;
+                let knee_point_y: u16 = bit_reader.read(12)?;
+                let num_bezier_curve_anchors: u8 = bit_reader.read(4)?;
+                let mut bezier_curve_anchors: Vec<u16> = Vec::new();
+                for _ in 0..num_bezier_curve_anchors {
+                    bezier_curve_anchors.push(bit_reader.read(10)?);
+                }
+                window.tone_mapping = Some(ToneMapping {
+                    knee_point_x,
+                    knee_point_y,
+                    bezier_curve_anchors,
+                });
+            }
+
+            let color_saturation_mapping_flag = bit_reader.read_bit()?;
+            if color_saturation_mapping_flag {
+                window.color_saturation_weight = Some(bit_reader.read(6)?);
+            }
+        }
+
+        Ok(Self {
+            application_identifier,
+            application_version,
+            num_windows,
+            processing_windows,
+            targeted_system_display_maximum_luminance,
+            targeted_system_display_actual_peak_luminance,
+            windows,
+            mastering_display_actual_peak_luminance,
+        })
+    }
+}
+
+impl ActualPeakLuminance {
+    fn from_bit_reader<R: io::Read>(
+        bit_reader: &mut BitReader<R, BigEndian>,
+    ) -> Result<Self, io::Error> {
+        let num_rows: u8 = bit_reader.read(5)?;
+        let num_cols: u8 = bit_reader.read(5)?;
+        let mut values = vec![vec![0; num_cols as usize]; num_rows as usize];
+        for row in values.iter_mut() {
+            for value in row.iter_mut() {
+                *value = bit_reader.read(4)?;
+            }
+        }
+
+        Ok(Self {
+            num_rows,
+            num_cols,
+            values,
+        })
+    }
+}
+
+impl ToneMapping {
+    /// The knee point `(x, y)`, normalized to `[0, 1]`.
+    pub fn knee_point(&self) -> (f64, f64) {
+        (
+            self.knee_point_x as f64 / 4095.0,
+            self.knee_point_y as f64 / 4095.0,
+        )
+    }
+
+    /// The Bézier curve anchors, normalized to `[0, 1]`.
+    pub fn normalized_bezier_curve_anchors(&self) -> impl Iterator<Item = f64> + '_ {
+        self.bezier_curve_anchors
+            .iter()
+            .map(|&anchor| anchor as f64 / 1023.0)
+    }
+}
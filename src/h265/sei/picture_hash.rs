@@ -0,0 +1,263 @@
+//! Decoded picture hash SEI messages, and the computation of the hashes for verifying decoded pictures.
+
+use std::io;
+
+/// See `decoded_picture_hash()` in _Decoded picture hash SEI message syntax_ in the spec.
+///
+/// Holds a hash for each colour component, i.e. 1 for monochrome and 3 otherwise.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodedPictureHash {
+    /// `hash_type == 0`: `picture_md5[cIdx]`.
+    Md5(Vec<[u8; 16]>),
+    /// `hash_type == 1`: `picture_crc[cIdx]`.
+    Crc(Vec<u16>),
+    /// `hash_type == 2`: `picture_checksum[cIdx]`.
+    Checksum(Vec<u32>),
+}
+
+/// `hash_type`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum HashType {
+    Md5 = 0,
+    Crc = 1,
+    Checksum = 2,
+}
+
+/// A decoded colour component plane.
+#[derive(Debug, Clone, Copy)]
+pub struct Plane<'a, T> {
+    /// At least `stride * (height - 1) + width` samples.
+    pub samples: &'a [T],
+    /// In samples.
+    pub width: usize,
+    /// In samples.
+    pub height: usize,
+    /// The distance between the starts of consecutive rows, in samples.
+    pub stride: usize,
+    pub bit_depth: u8,
+}
+
+impl DecodedPictureHash {
+    /// Returns `None` for a reserved `hash_type`.
+    ///
+    /// The number of colour components is derived from `payloadSize`, which removes the need for `chroma_format_idc`.
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Result<Option<Self>, io::Error> {
+        let Some((&hash_type, bytes)) = bytes.split_first() else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Empty decoded_picture_hash()",
+            ));
+        };
+
+        let hash_size = match hash_type {
+            0 => 16,
+            1 => 2,
+            2 => 4,
+            _ => return Ok(None),
+        };
+        if bytes.len() != hash_size && bytes.len() != hash_size * 3 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "decoded_picture_hash() of hash_type {} with unexpected size: {}",
+                    hash_type,
+                    bytes.len() + 1
+                ),
+            ));
+        }
+
+        let chunks = bytes.chunks_exact(hash_size);
+        Ok(Some(match hash_type {
+            0 => Self::Md5(chunks.map(|x| x.try_into().unwrap()).collect()),
+            1 => Self::Crc(
+                chunks
+                    .map(|x| u16::from_be_bytes(x.try_into().unwrap()))
+                    .collect(),
+            ),
+            _ => Self::Checksum(
+                chunks
+                    .map(|x| u32::from_be_bytes(x.try_into().unwrap()))
+                    .collect(),
+            ),
+        }))
+    }
+
+    pub fn hash_type(&self) -> HashType {
+        match self {
+            Self::Md5(_) => HashType::Md5,
+            Self::Crc(_) => HashType::Crc,
+            Self::Checksum(_) => HashType::Checksum,
+        }
+    }
+
+    /// The number of colour components the hash is for.
+    pub fn num_components(&self) -> usize {
+        match self {
+            Self::Md5(x) => x.len(),
+            Self::Crc(x) => x.len(),
+            Self::Checksum(x) => x.len(),
+        }
+    }
+
+    /// Computes the hash of `planes`, as specified in _D.3.19 Decoded picture hash SEI message semantics_.
+    ///
+    /// # Panics
+    /// If a plane has fewer samples than its dimensions require.
+    pub fn compute<T: Copy + Into<u16>>(hash_type: HashType, planes: &[Plane<T>]) -> Self {
+        match hash_type {
+            HashType::Md5 => Self::Md5(planes.iter().map(Plane::md5).collect()),
+            HashType::Crc => Self::Crc(planes.iter().map(Plane::crc).collect()),
+            HashType::Checksum => Self::Checksum(planes.iter().map(Plane::checksum).collect()),
+        }
+    }
+
+    /// Whether `planes` hash to `self`, i.e. the decoded picture matches the one the encoder hashed.
+    pub fn verify<T: Copy + Into<u16>>(&self, planes: &[Plane<T>]) -> bool {
+        planes.len() == self.num_components() && Self::compute(self.hash_type(), planes) == *self
+    }
+}
+
+impl<'a, T: Copy + Into<u16>> Plane<'a, T> {
+    fn rows(&self) -> impl Iterator<Item = &'a [T]> + '_ {
+        assert!(
+            self.height == 0 || self.samples.len() >= self.stride * (self.height - 1) + self.width,
+            "Plane too small"
+        );
+        (0..self.height).map(|y| &self.samples[y * self.stride..][..self.width])
+    }
+
+    /// `pictureData`, i.e. each sample in 1 byte, or in 2 bytes in little-endian when `bit_depth > 8`.
+    fn picture_data(&self) -> impl Iterator<Item = u8> + '_ {
+        let bytes_per_sample = if self.bit_depth > 8 { 2 } else { 1 };
+        self.rows().flatten().flat_map(move |&sample| {
+            let sample: u16 = sample.into();
+            sample.to_le_bytes().into_iter().take(bytes_per_sample)
+        })
+    }
+
+    fn md5(&self) -> [u8; 16] {
+        let mut context = md5::Context::new();
+        let picture_data: Vec<u8> = self.picture_data().collect();
+        context.consume(picture_data);
+        context.compute().0
+    }
+
+    fn crc(&self) -> u16 {
+        let mut crc: u16 = 0xFFFF;
+        // Followed by 2 zero bytes.
+        for data_byte in self.picture_data().chain([0, 0]) {
+            for bit_idx in 0..8 {
+                let crc_msb = crc >> 15;
+                let bit_val = (data_byte >> (7 - bit_idx)) as u16 & 1;
+                crc = ((crc << 1) + bit_val) ^ (crc_msb * 0x1021);
+            }
+        }
+        crc
+    }
+
+    fn checksum(&self) -> u32 {
+        let mut sum: u32 = 0;
+        for (y, row) in self.rows().enumerate() {
+            for (x, &sample) in row.iter().enumerate() {
+                let sample: u16 = sample.into();
+                let xor_mask = ((x & 0xFF) ^ (y & 0xFF) ^ (x >> 8) ^ (y >> 8)) as u32;
+                sum = sum.wrapping_add((sample as u32 & 0xFF) ^ xor_mask);
+                if self.bit_depth > 8 {
+                    sum = sum.wrapping_add((sample as u32 >> 8) ^ xor_mask);
+                }
+            }
+        }
+        sum
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::BTreeMap;
+
+    use crate::h265::nalu::NaluType;
+    use crate::h265::sei::{Sei, SeiPayloadRegistry};
+
+    #[test]
+    fn decoded_picture_hash_works() {
+        // A 10-bit 4:2:0 picture of 3x2 luma samples, where the luma rows are padded to 4 samples.
+        let luma: [u16; 8] = [0, 1023, 512, 0xFFFF, 7, 300, 1000, 0xFFFF];
+        let cb: [u16; 2] = [100, 200];
+        let cr: [u16; 2] = [513, 2];
+        let planes = [
+            Plane {
+                samples: &luma[..],
+                width: 3,
+                height: 2,
+                stride: 4,
+                bit_depth: 10,
+            },
+            Plane {
+                samples: &cb[..],
+                width: 2,
+                height: 1,
+                stride: 2,
+                bit_depth: 10,
+            },
+            Plane {
+                samples: &cr[..],
+                width: 2,
+                height: 1,
+                stride: 2,
+                bit_depth: 10,
+            },
+        ];
+
+        assert_eq!(
+            DecodedPictureHash::compute(HashType::Crc, &planes),
+            DecodedPictureHash::Crc(vec![0xA7BC, 0x02CE, 0x70A6])
+        );
+        assert_eq!(
+            DecodedPictureHash::compute(HashType::Checksum, &planes),
+            DecodedPictureHash::Checksum(vec![0x221, 0x12E, 0x7])
+        );
+
+        // hash_type = 0, picture_md5
+        let mut rbsp: Vec<u8> = vec![132, 49, 0];
+        for md5 in [
+            0x233a19042663ab238329432b21f6df43u128,
+            0x8428c2914a11a5110f1488dd1b63e550,
+            0x083b269dd71f67b54f1baf070a3426a6,
+        ] {
+            rbsp.extend_from_slice(&md5.to_be_bytes());
+        }
+        rbsp.push(0x80);
+
+        let sei = Sei::from_rbsp_reader(
+            &mut rbsp.as_slice(),
+            NaluType::SuffixSeiNut,
+            &SeiPayloadRegistry::default(),
+            None,
+            &BTreeMap::new(),
+        )
+        .unwrap();
+        let decoded_picture_hash = sei.sei_messages[0]
+            .payload
+            .as_decoded_picture_hash()
+            .unwrap();
+        assert_eq!(decoded_picture_hash.hash_type(), HashType::Md5);
+        assert_eq!(decoded_picture_hash.num_components(), 3);
+        assert!(decoded_picture_hash.verify(&planes));
+
+        let mut corrupted = luma;
+        corrupted[5] += 1;
+        let mut corrupted_planes = planes;
+        corrupted_planes[0].samples = &corrupted[..];
+        assert!(!decoded_picture_hash.verify(&corrupted_planes));
+
+        // Padding is not hashed.
+        let mut padded = luma;
+        padded[3] = 0;
+        let mut padded_planes = planes;
+        padded_planes[0].samples = &padded[..];
+        assert!(decoded_picture_hash.verify(&padded_planes));
+    }
+}
@@ -0,0 +1,242 @@
+//! `user_data_registered_itu_t_t35()` SEI messages, dispatched by the ITU-T T.35 country and provider codes.
+
+use std::io;
+
+use crate::h265::sei::captions::{CcData, ATSC_USER_DATA_TYPE_CODE_CC_DATA, ATSC_USER_IDENTIFIER};
+use crate::h265::sei::hdr10plus::{Hdr10Plus, HDR10_PLUS_APPLICATION_IDENTIFIER};
+
+/// `itu_t_t35_country_code` of the United States.
+pub const ITU_T_T35_COUNTRY_CODE_US: u8 = 0xB5;
+/// `itu_t_t35_terminal_provider_code` of Samsung Electronics America, for HDR10+.
+pub const ITU_T_T35_PROVIDER_CODE_SAMSUNG: u16 = 0x003C;
+/// `itu_t_t35_terminal_provider_code` of ATSC, for A/53 user data.
+pub const ITU_T_T35_PROVIDER_CODE_ATSC: u16 = 0x0031;
+
+/// See `user_data_registered_itu_t_t35()` in _D.2.6 User data registered by Recommendation ITU-T T.35 SEI message syntax_ in the spec.
+#[derive(Debug, Clone)]
+pub struct UserDataRegisteredItuTT35 {
+    pub itu_t_t35_country_code: u8,
+    /// Present when `itu_t_t35_country_code == 0xFF`.
+    pub itu_t_t35_country_code_extension_byte: Option<u8>,
+    /// The first 2 `itu_t_t35_payload_byte`s, which hold the terminal provider code in most registered payloads.
+    pub itu_t_t35_terminal_provider_code: Option<u16>,
+    pub payload: ItuTT35Payload,
+}
+
+/// `itu_t_t35_payload_byte`s, decoded according to the country and provider codes.
+#[derive(Debug, Clone)]
+pub enum ItuTT35Payload {
+    /// SMPTE ST 2094-40 (HDR10+) dynamic metadata.
+    Hdr10Plus(Hdr10Plus),
+    /// ATSC A/53 `cc_data()`, i.e. CEA-608/708 closed captions.
+    CcData(CcData),
+    /// `itu_t_t35_payload_byte`s which are not recognized, including `itu_t_t35_terminal_provider_code`.
+    Unknown(Vec<u8>),
+}
+
+impl UserDataRegisteredItuTT35 {
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Result<Self, io::Error> {
+        let truncated = || {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "user_data_registered_itu_t_t35() too short",
+            )
+        };
+
+        let (&itu_t_t35_country_code, mut payload_bytes) =
+            bytes.split_first().ok_or_else(truncated)?;
+        let itu_t_t35_country_code_extension_byte = if itu_t_t35_country_code == 0xFF {
+            let (&extension_byte, rest) = payload_bytes.split_first().ok_or_else(truncated)?;
+            payload_bytes = rest;
+            Some(extension_byte)
+        } else {
+            None
+        };
+
+        let itu_t_t35_terminal_provider_code = match payload_bytes {
+            [high, low, ..] => Some(u16::from_be_bytes([*high, *low])),
+            _ => None,
+        };
+
+        // A payload which fails to decode is left `Unknown` rather than failing the whole SEI message.
+        let payload = match (itu_t_t35_country_code, payload_bytes) {
+            // `itu_t_t35_terminal_provider_code`, `itu_t_t35_terminal_provider_oriented_code` and `application_identifier`.
+            (
+                ITU_T_T35_COUNTRY_CODE_US,
+                [0x00, 0x3C, 0x00, 0x01, HDR10_PLUS_APPLICATION_IDENTIFIER, ..],
+            ) => Hdr10Plus::from_bytes(&payload_bytes[4..]).map(ItuTT35Payload::Hdr10Plus),
+            // `itu_t_t35_terminal_provider_code`, `user_identifier` and `user_data_type_code`.
+            (
+                ITU_T_T35_COUNTRY_CODE_US,
+                [0x00, 0x31, a, b, c, d, ATSC_USER_DATA_TYPE_CODE_CC_DATA, ..],
+            ) if [*a, *b, *c, *d] == ATSC_USER_IDENTIFIER => {
+                CcData::from_bytes(&payload_bytes[7..]).map(ItuTT35Payload::CcData)
+            }
+            _ => Ok(ItuTT35Payload::Unknown(payload_bytes.to_vec())),
+        }
+        .unwrap_or_else(|_| ItuTT35Payload::Unknown(payload_bytes.to_vec()));
+
+        Ok(Self {
+            itu_t_t35_country_code,
+            itu_t_t35_country_code_extension_byte,
+            itu_t_t35_terminal_provider_code,
+            payload,
+        })
+    }
+
+    pub fn as_hdr10_plus(&self) -> Option<&Hdr10Plus> {
+        match &self.payload {
+            ItuTT35Payload::Hdr10Plus(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_cc_data(&self) -> Option<&CcData> {
+        match &self.payload {
+            ItuTT35Payload::CcData(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::BTreeMap;
+
+    use bitstream_io::{BigEndian, BitWrite, BitWriter};
+
+    use crate::h265::nalu::NaluType;
+    use crate::h265::sei::hdr10plus::DistributionMaxrgb;
+    use crate::h265::sei::{Sei, SeiPayloadRegistry};
+
+    #[test]
+    fn hdr10_plus_works() {
+        let mut bytes: Vec<u8> = Vec::new();
+        {
+            let mut bit_writer = BitWriter::endian(&mut bytes, BigEndian);
+            // itu_t_t35_country_code, itu_t_t35_terminal_provider_code, itu_t_t35_terminal_provider_oriented_code
+            bit_writer.write(8, 0xB5u8).unwrap();
+            bit_writer.write(16, 0x003Cu16).unwrap();
+            bit_writer.write(16, 0x0001u16).unwrap();
+            // application_identifier = 4, application_version = 1, num_windows = 1
+            bit_writer.write(8, 4u8).unwrap();
+            bit_writer.write(8, 1u8).unwrap();
+            bit_writer.write(2, 1u8).unwrap();
+            // targeted_system_display_maximum_luminance = 400, targeted_system_display_actual_peak_luminance_flag = 0
+            bit_writer.write(27, 400u32).unwrap();
+            bit_writer.write_bit(false).unwrap();
+            // maxscl, average_maxrgb
+            for value in [50000u32, 60000, 70000, 12345] {
+                bit_writer.write(17, value).unwrap();
+            }
+            // num_distribution_maxrgb_percentiles = 2
+            bit_writer.write(4, 2u8).unwrap();
+            bit_writer.write(7, 1u8).unwrap();
+            bit_writer.write(17, 100u32).unwrap();
+            bit_writer.write(7, 99u8).unwrap();
+            bit_writer.write(17, 90000u32).unwrap();
+            // fraction_bright_pixels = 0, mastering_display_actual_peak_luminance_flag = 0
+            bit_writer.write(10, 0u16).unwrap();
+            bit_writer.write_bit(false).unwrap();
+            // tone_mapping_flag = 1, knee_point_x = 4095, knee_point_y = 0, num_bezier_curve_anchors = 3
+            bit_writer.write_bit(true).unwrap();
+            bit_writer.write(12, 4095u16).unwrap();
+            bit_writer.write(12, 0u16).unwrap();
+            bit_writer.write(4, 3u8).unwrap();
+            for anchor in [0u16, 512, 1023] {
+                bit_writer.write(10, anchor).unwrap();
+            }
+            // color_saturation_mapping_flag = 0
+            bit_writer.write_bit(false).unwrap();
+            bit_writer.byte_align().unwrap();
+        }
+
+        let mut rbsp: Vec<u8> = vec![4, bytes.len() as u8];
+        rbsp.extend_from_slice(&bytes);
+        rbsp.push(0x80);
+
+        let sei = Sei::from_rbsp_reader(
+            &mut rbsp.as_slice(),
+            NaluType::PrefixSeiNut,
+            &SeiPayloadRegistry::default(),
+            None,
+            &BTreeMap::new(),
+        )
+        .unwrap();
+        let user_data_registered_itu_t_t35 = sei.sei_messages[0]
+            .payload
+            .as_user_data_registered_itu_t_t35()
+            .unwrap();
+        assert_eq!(
+            user_data_registered_itu_t_t35.itu_t_t35_terminal_provider_code,
+            Some(ITU_T_T35_PROVIDER_CODE_SAMSUNG)
+        );
+
+        let hdr10_plus = user_data_registered_itu_t_t35.as_hdr10_plus().unwrap();
+        assert_eq!(hdr10_plus.application_version, 1);
+        assert_eq!(hdr10_plus.num_windows, 1);
+        assert!(hdr10_plus.processing_windows.is_empty());
+        assert_eq!(hdr10_plus.targeted_system_display_maximum_luminance, 400);
+
+        let window = &hdr10_plus.windows[0];
+        assert_eq!(window.maxscl, [50000, 60000, 70000]);
+        assert_eq!(window.average_maxrgb, 12345);
+        assert_eq!(
+            window.distribution_maxrgb,
+            [
+                DistributionMaxrgb {
+                    percentage: 1,
+                    percentile: 100
+                },
+                DistributionMaxrgb {
+                    percentage: 99,
+                    percentile: 90000
+                },
+            ]
+        );
+        let tone_mapping = window.tone_mapping.as_ref().unwrap();
+        assert_eq!(tone_mapping.knee_point(), (1.0, 0.0));
+        assert_eq!(
+            tone_mapping
+                .normalized_bezier_curve_anchors()
+                .collect::<Vec<_>>(),
+            [0.0, 512.0 / 1023.0, 1.0]
+        );
+        assert_eq!(window.color_saturation_weight, None);
+
+        // Another provider.
+        let rbsp = [4, 4, 0xB5, 0x00, 0x31, 0x47, 0x80];
+        let sei = Sei::from_rbsp_reader(
+            &mut rbsp.as_slice(),
+            NaluType::PrefixSeiNut,
+            &SeiPayloadRegistry::default(),
+            None,
+            &BTreeMap::new(),
+        )
+        .unwrap();
+        let user_data_registered_itu_t_t35 = sei.sei_messages[0]
+            .payload
+            .as_user_data_registered_itu_t_t35()
+            .unwrap();
+        assert_eq!(
+            user_data_registered_itu_t_t35.itu_t_t35_terminal_provider_code,
+            Some(0x0031)
+        );
+        assert!(user_data_registered_itu_t_t35.as_hdr10_plus().is_none());
+
+        // Truncated HDR10+ and cc_data() payloads.
+        for payload in [
+            &[0xB5, 0x00, 0x3C, 0x00, 0x01, 4, 1][..],
+            &[0xB5, 0x00, 0x31, 0x47, 0x41, 0x39, 0x34, 0x03][..],
+        ] {
+            let user_data_registered_itu_t_t35 =
+                UserDataRegisteredItuTT35::from_bytes(payload).unwrap();
+            assert!(matches!(
+                &user_data_registered_itu_t_t35.payload,
+                ItuTT35Payload::Unknown(bytes) if bytes[..] == payload[1..]
+            ));
+        }
+    }
+}
@@ -0,0 +1,402 @@
+//! Time code SEI messages, and the derivation of the SMPTE timecode of every picture from them.
+
+use std::fmt;
+use std::io;
+
+use bitstream_io::BitRead as _;
+use bitstream_io::{BigEndian, BitReader};
+
+use crate::h265::sps::VuiTimingInfo;
+
+/// See `time_code()` in _Time code SEI message syntax_ in the spec.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeCode {
+    /// The clock timestamps for `i` in `[0, num_clock_ts)`. `Some` means `clock_timestamp_flag[i] == true`.
+    pub clock_timestamps: Vec<Option<ClockTimestamp>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockTimestamp {
+    /// Whether `n_frames` counts fields rather than frames.
+    pub units_field_based_flag: bool,
+    /// See _Table D.6 – Definition of counting_type[ i ] values_: e.g. `0` for no dropping and `4` for NTSC drop-frame
+    /// counting.
+    pub counting_type: u8,
+    pub full_timestamp_flag: bool,
+    pub discontinuity_flag: bool,
+    pub cnt_dropped_flag: bool,
+    pub n_frames: u16,
+    /// Present when `full_timestamp_flag == true` or `seconds_flag == true`.
+    pub seconds_value: Option<u8>,
+    /// Present when `full_timestamp_flag == true` or `minutes_flag == true`.
+    pub minutes_value: Option<u8>,
+    /// Present when `full_timestamp_flag == true` or `hours_flag == true`.
+    pub hours_value: Option<u8>,
+    /// `0` means `time_offset_value` is not present, and inferred to be `0`.
+    pub time_offset_length: u8,
+    /// In clock ticks of `1 / vui_time_scale` seconds.
+    pub time_offset_value: i32,
+}
+
+/// A SMPTE timecode, i.e. `HH:MM:SS:FF`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SmpteTimecode {
+    pub hours: u8,
+    pub minutes: u8,
+    pub seconds: u8,
+    pub frames: u16,
+    /// Whether frame numbers `0` and `1` are dropped at the start of each minute but every 10th, as for `counting_type`
+    /// `4`.
+    pub drop_frame: bool,
+}
+
+/// Derives the SMPTE timecode of every picture from time code SEI messages.
+///
+/// A picture with a time code SEI message gets the timecode of its first clock timestamp, and becomes the anchor from
+/// which the timecodes of following pictures are extrapolated by their `PicOrderCntVal` difference. Each POC difference
+/// of one is `vui_num_ticks_poc_diff_one_minus1 + 1` clock ticks when given, and assumed to be one frame otherwise.
+#[derive(Debug, Clone)]
+pub struct TimecodeComputer {
+    vui_num_units_in_tick: u32,
+    vui_time_scale: u32,
+    vui_num_ticks_poc_diff_one_minus1: Option<u32>,
+    /// `hours_value`, `minutes_value` and `seconds_value` of the previous clock timestamp, from which omitted ones are
+    /// inferred.
+    prev_hours_minutes_seconds: [u8; 3],
+    /// `PicOrderCntVal`, timecode and `units_field_based_flag` of the picture extrapolated from.
+    anchor: Option<(i32, SmpteTimecode, bool)>,
+}
+
+impl TimeCode {
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Result<Self, io::Error> {
+        let mut bit_reader = BitReader::endian(bytes, BigEndian);
+
+        let num_clock_ts: u8 = bit_reader.read(2)?;
+        let mut clock_timestamps: Vec<Option<ClockTimestamp>> = Vec::new();
+        for _ in 0..num_clock_ts {
+            let clock_timestamp_flag = bit_reader.read_bit()?;
+            if !clock_timestamp_flag {
+                clock_timestamps.push(None);
+                continue;
+            }
+
+            let units_field_based_flag = bit_reader.read_bit()?;
+            let counting_type: u8 = bit_reader.read(5)?;
+            let full_timestamp_flag = bit_reader.read_bit()?;
+            let discontinuity_flag = bit_reader.read_bit()?;
+            let cnt_dropped_flag = bit_reader.read_bit()?;
+            let n_frames: u16 = bit_reader.read(9)?;
+
+            let mut seconds_value = None;
+            let mut minutes_value = None;
+            let mut hours_value = None;
+            if full_timestamp_flag {
+                seconds_value = Some(bit_reader.read(6)?);
+                minutes_value = Some(bit_reader.read(6)?);
+                hours_value = Some(bit_reader.read(5)?);
+            } else {
+                let seconds_flag = bit_reader.read_bit()?;
+                if seconds_flag {
+                    seconds_value = Some(bit_reader.read(6)?);
+                    let minutes_flag = bit_reader.read_bit()?;
+                    if minutes_flag {
+                        minutes_value = Some(bit_reader.read(6)?);
+                        let hours_flag = bit_reader.read_bit()?;
+                        if hours_flag {
+                            hours_value = Some(bit_reader.read(5)?);
+                        }
+                    }
+                }
+            }
+
+            let time_offset_length: u8 = bit_reader.read(5)?;
+            let time_offset_value = if time_offset_length > 0 {
+                bit_reader.read_signed::<i32>(time_offset_length as u32)?
+            } else {
+                0
+            };
+
+            clock_timestamps.push(Some(ClockTimestamp {
+                units_field_based_flag,
+                counting_type,
+                full_timestamp_flag,
+                discontinuity_flag,
+                cnt_dropped_flag,
+                n_frames,
+                seconds_value,
+                minutes_value,
+                hours_value,
+                time_offset_length,
+                time_offset_value,
+            }));
+        }
+
+        Ok(Self { clock_timestamps })
+    }
+}
+
+impl ClockTimestamp {
+    /// `clockTimestamp`, in clock ticks of `1 / vui_time_scale` seconds.
+    ///
+    /// Omitted `hours_value`, `minutes_value` and `seconds_value` are taken to be `0`.
+    pub fn clock_timestamp(&self, vui_timing_info: &VuiTimingInfo) -> i64 {
+        let hours = self.hours_value.unwrap_or(0) as i64;
+        let minutes = self.minutes_value.unwrap_or(0) as i64;
+        let seconds = self.seconds_value.unwrap_or(0) as i64;
+        ((hours * 60 + minutes) * 60 + seconds) * vui_timing_info.vui_time_scale as i64
+            + self.n_frames as i64
+                * (vui_timing_info.vui_num_units_in_tick as i64
+                    * (1 + self.units_field_based_flag as i64))
+            + self.time_offset_value as i64
+    }
+}
+
+impl SmpteTimecode {
+    /// The number of frames since `00:00:00:00`, given the nominal frame rate, e.g. `30` for 29.97 Hz.
+    pub fn to_frame_count(&self, timebase: u32) -> i64 {
+        let timebase = timebase as i64;
+        let total_minutes = self.hours as i64 * 60 + self.minutes as i64;
+        let frames = (total_minutes * 60 + self.seconds as i64) * timebase + self.frames as i64;
+        if self.drop_frame {
+            frames - DROP_FRAMES_PER_MINUTE * (total_minutes - total_minutes / 10)
+        } else {
+            frames
+        }
+    }
+
+    /// The inverse of `to_frame_count()`, wrapping around at 24 hours.
+    pub fn from_frame_count(frame_count: i64, timebase: u32, drop_frame: bool) -> Self {
+        let timebase = timebase as i64;
+        let mut frame_count = frame_count;
+        if drop_frame {
+            let drop_frames = DROP_FRAMES_PER_MINUTE;
+            let frames_per_minute = timebase * 60 - drop_frames;
+            let frames_per_10_minutes = frames_per_minute * 10 + drop_frames;
+            frame_count = frame_count.rem_euclid(frames_per_10_minutes * 6 * 24);
+
+            let tens_of_minutes = frame_count / frames_per_10_minutes;
+            let remainder = frame_count % frames_per_10_minutes;
+            frame_count += drop_frames * 9 * tens_of_minutes;
+            if remainder > drop_frames {
+                frame_count += drop_frames * ((remainder - drop_frames) / frames_per_minute);
+            }
+        } else {
+            frame_count = frame_count.rem_euclid(timebase * 60 * 60 * 24);
+        }
+
+        let total_seconds = frame_count / timebase;
+        Self {
+            hours: (total_seconds / 3600) as u8,
+            minutes: (total_seconds / 60 % 60) as u8,
+            seconds: (total_seconds % 60) as u8,
+            frames: (frame_count % timebase) as u16,
+            drop_frame,
+        }
+    }
+}
+
+impl fmt::Display for SmpteTimecode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let separator = if self.drop_frame { ';' } else { ':' };
+        write!(
+            f,
+            "{:02}:{:02}:{:02}{}{:02}",
+            self.hours, self.minutes, self.seconds, separator, self.frames
+        )
+    }
+}
+
+impl TimecodeComputer {
+    pub fn new(vui_timing_info: &VuiTimingInfo) -> Self {
+        Self {
+            vui_num_units_in_tick: vui_timing_info.vui_num_units_in_tick,
+            vui_time_scale: vui_timing_info.vui_time_scale,
+            vui_num_ticks_poc_diff_one_minus1: vui_timing_info.vui_num_ticks_poc_diff_one_minus1,
+            prev_hours_minutes_seconds: [0; 3],
+            anchor: None,
+        }
+    }
+
+    /// Reset when `PicOrderCntVal` is reset without a time code SEI message, e.g. at an IDR picture.
+    pub fn reset(&mut self) {
+        self.anchor = None;
+    }
+
+    /// Computes the timecode of a picture, given in decoding order with the time code SEI message associated with it.
+    ///
+    /// Returns `None` until a picture has a time code SEI message.
+    pub fn compute_timecode(
+        &mut self,
+        pic_order_cnt_val: i32,
+        time_code: Option<&TimeCode>,
+    ) -> Option<SmpteTimecode> {
+        let clock_timestamp =
+            time_code.and_then(|time_code| time_code.clock_timestamps.iter().flatten().next());
+        if let Some(clock_timestamp) = clock_timestamp {
+            // Omitted values are inferred from the previous clock timestamp.
+            let [hours, minutes, seconds] = &mut self.prev_hours_minutes_seconds;
+            *hours = clock_timestamp.hours_value.unwrap_or(*hours);
+            *minutes = clock_timestamp.minutes_value.unwrap_or(*minutes);
+            *seconds = clock_timestamp.seconds_value.unwrap_or(*seconds);
+
+            let mut timecode = SmpteTimecode {
+                hours: *hours,
+                minutes: *minutes,
+                seconds: *seconds,
+                frames: clock_timestamp.n_frames,
+                drop_frame: clock_timestamp.counting_type == 4,
+            };
+            if clock_timestamp.units_field_based_flag {
+                timecode.frames /= 2;
+            }
+
+            self.anchor = Some((
+                pic_order_cnt_val,
+                timecode,
+                clock_timestamp.units_field_based_flag,
+            ));
+            return Some(timecode);
+        }
+
+        let (anchor_pic_order_cnt_val, anchor_timecode, units_field_based_flag) = self.anchor?;
+        let poc_diff = pic_order_cnt_val as i64 - anchor_pic_order_cnt_val as i64;
+        let frame_diff = match self.vui_num_ticks_poc_diff_one_minus1 {
+            // A frame lasts `1 + units_field_based_flag` clock ticks.
+            // Only wraps for a POC difference far beyond the `DiffPicOrderCnt()` range allowed within a CVS.
+            Some(x) => poc_diff.wrapping_mul(x as i64 + 1) / (1 + units_field_based_flag as i64),
+            None => poc_diff,
+        };
+
+        let timebase = self.timebase(units_field_based_flag);
+        Some(SmpteTimecode::from_frame_count(
+            anchor_timecode.to_frame_count(timebase) + frame_diff,
+            timebase,
+            anchor_timecode.drop_frame,
+        ))
+    }
+
+    /// The nominal frame rate, i.e. the frame rate rounded up to an integer.
+    fn timebase(&self, units_field_based_flag: bool) -> u32 {
+        let ticks_per_frame =
+            self.vui_num_units_in_tick as u64 * (1 + units_field_based_flag as u64);
+        (self.vui_time_scale as u64)
+            .div_ceil(ticks_per_frame)
+            .max(1) as u32
+    }
+}
+
+/// `counting_type` `4` drops the two lowest `n_frames` counts whatever the frame rate, unlike SMPTE ST 12-1 which
+/// drops four at 59.94 Hz.
+const DROP_FRAMES_PER_MINUTE: i64 = 2;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::BTreeMap;
+
+    use bitstream_io::{BitWrite, BitWriter};
+
+    use crate::h265::nalu::NaluType;
+    use crate::h265::sei::{Sei, SeiPayloadRegistry};
+
+    #[test]
+    fn time_code_works() {
+        let mut bytes: Vec<u8> = Vec::new();
+        {
+            let mut bit_writer = BitWriter::endian(&mut bytes, BigEndian);
+            // num_clock_ts = 1, clock_timestamp_flag = 1, units_field_based_flag = 0, counting_type = 4,
+            // full_timestamp_flag = 1, discontinuity_flag = 0, cnt_dropped_flag = 0, n_frames = 28
+            bit_writer.write(2, 1u8).unwrap();
+            bit_writer.write_bit(true).unwrap();
+            bit_writer.write_bit(false).unwrap();
+            bit_writer.write(5, 4u8).unwrap();
+            bit_writer.write(3, 0b100u8).unwrap();
+            bit_writer.write(9, 28u16).unwrap();
+            // seconds_value = 59, minutes_value = 9, hours_value = 1
+            bit_writer.write(6, 59u8).unwrap();
+            bit_writer.write(6, 9u8).unwrap();
+            bit_writer.write(5, 1u8).unwrap();
+            // time_offset_length = 4, time_offset_value = -3
+            bit_writer.write(5, 4u8).unwrap();
+            bit_writer.write_signed(4, -3i8).unwrap();
+            bit_writer.write_bit(true).unwrap();
+            bit_writer.byte_align().unwrap();
+        }
+
+        let mut rbsp: Vec<u8> = vec![136, bytes.len() as u8];
+        rbsp.extend_from_slice(&bytes);
+        rbsp.push(0x80);
+
+        let sei = Sei::from_rbsp_reader(
+            &mut rbsp.as_slice(),
+            NaluType::PrefixSeiNut,
+            &SeiPayloadRegistry::default(),
+            None,
+            &BTreeMap::new(),
+        )
+        .unwrap();
+        let time_code = sei.sei_messages[0].payload.as_time_code().unwrap();
+        let clock_timestamp = time_code.clock_timestamps[0].unwrap();
+        assert_eq!(clock_timestamp.counting_type, 4);
+        assert_eq!(clock_timestamp.n_frames, 28);
+        assert_eq!(clock_timestamp.hours_value, Some(1));
+        assert_eq!(clock_timestamp.time_offset_value, -3);
+
+        // 29.97 Hz, with POC incremented by 1 per frame.
+        let vui_timing_info = VuiTimingInfo {
+            vui_num_units_in_tick: 1001,
+            vui_time_scale: 30000,
+            vui_num_ticks_poc_diff_one_minus1: Some(0),
+            vui_hrd_parameters: None,
+        };
+        assert_eq!(
+            clock_timestamp.clock_timestamp(&vui_timing_info),
+            (3600 + 9 * 60 + 59) * 30000 + 28 * 1001 - 3
+        );
+
+        let mut timecode_computer = TimecodeComputer::new(&vui_timing_info);
+        assert_eq!(timecode_computer.compute_timecode(0, None), None);
+        let timecode = timecode_computer
+            .compute_timecode(0, Some(time_code))
+            .unwrap();
+        assert_eq!(timecode.to_string(), "01:09:59;28");
+        // Frames 00 and 01 of minute 10 are not dropped, but those of minute 11 are.
+        let timecodes: Vec<String> = [1, 2, 3, 1801, 1802]
+            .into_iter()
+            .map(|poc| {
+                timecode_computer
+                    .compute_timecode(poc, None)
+                    .unwrap()
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(
+            timecodes,
+            [
+                "01:09:59;29",
+                "01:10:00;00",
+                "01:10:00;01",
+                "01:10:59;29",
+                "01:11:00;02",
+            ]
+        );
+
+        // The POC difference does not overflow.
+        let timecode = timecode_computer
+            .compute_timecode(i32::MAX, Some(time_code))
+            .unwrap();
+        assert!(timecode_computer.compute_timecode(i32::MIN, None).is_some());
+
+        // Two frame numbers are dropped at 59.94 Hz as well.
+        let timecode = SmpteTimecode {
+            minutes: 10,
+            seconds: 59,
+            frames: 59,
+            ..timecode
+        };
+        let next_timecode =
+            SmpteTimecode::from_frame_count(timecode.to_frame_count(60) + 1, 60, true);
+        assert_eq!(next_timecode.to_string(), "01:11:00;02");
+    }
+}
@@ -0,0 +1,582 @@
+//! Buffering period, picture timing and recovery point SEI messages, and the HRD timing model built on them.
+
+use std::io;
+
+use bitstream_io::BitRead as _;
+use bitstream_io::{BigEndian, BitReader};
+
+use crate::base::{read_exp_golomb_se, read_exp_golomb_ue};
+use crate::h265::hrd::HrdCommonInfo;
+use crate::h265::sps::{SequenceParameterSet, VuiTimingInfo};
+
+/// See `buffering_period()` in _D.2.2 Buffering period SEI message syntax_ in the spec.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BufferingPeriod {
+    pub bp_seq_parameter_set_id: u8,
+    /// Inferred to be `false` when `sub_pic_hrd_params_present_flag == true`.
+    pub irap_cpb_params_present_flag: bool,
+    /// Present when `irap_cpb_params_present_flag == true`.
+    pub cpb_delay_offset: Option<u32>,
+    /// Present when `irap_cpb_params_present_flag == true`.
+    pub dpb_delay_offset: Option<u32>,
+    pub concatenation_flag: bool,
+    pub au_cpb_removal_delay_delta_minus1: u32,
+    /// `Some` means `NalHrdBpPresentFlag == true`.
+    pub nal_initial_cpb_removal: Option<Vec<InitialCpbRemoval>>,
+    /// `Some` means `VclHrdBpPresentFlag == true`.
+    pub vcl_initial_cpb_removal: Option<Vec<InitialCpbRemoval>>,
+    /// Present when `payload_extension_present()`.
+    pub use_alt_cpb_params_flag: Option<bool>,
+}
+
+/// The initial CPB removal delay and offset of a CPB specification `SchedSelIdx`, in units of a 90 kHz clock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InitialCpbRemoval {
+    pub initial_cpb_removal_delay: u32,
+    pub initial_cpb_removal_offset: u32,
+    /// Present when `sub_pic_hrd_params_present_flag == true` or `irap_cpb_params_present_flag == true`.
+    pub initial_alt_cpb_removal_delay: Option<u32>,
+    /// Present when `sub_pic_hrd_params_present_flag == true` or `irap_cpb_params_present_flag == true`.
+    pub initial_alt_cpb_removal_offset: Option<u32>,
+}
+
+/// See `pic_timing()` in _D.2.3 Picture timing SEI message syntax_ in the spec.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PicTiming {
+    /// `Some` means `frame_field_info_present_flag == true`.
+    pub frame_field_info: Option<FrameFieldInfo>,
+    /// `Some` means `CpbDpbDelaysPresentFlag == true`.
+    pub cpb_dpb_delays: Option<CpbDpbDelays>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameFieldInfo {
+    /// See _Table D.2 – Interpretation of pic_struct_: e.g. `0` for a frame, `1`/`2` for a top/bottom field and `7`/`8`
+    /// for frame doubling/tripling.
+    pub pic_struct: u8,
+    /// `0` for interlaced, `1` for progressive and `2` for unknown.
+    pub source_scan_type: u8,
+    pub duplicate_flag: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CpbDpbDelays {
+    pub au_cpb_removal_delay_minus1: u32,
+    pub pic_dpb_output_delay: u32,
+    /// Present when `sub_pic_hrd_params_present_flag == true`.
+    pub pic_dpb_output_du_delay: Option<u32>,
+    /// `Some` means `sub_pic_hrd_params_present_flag == true` and `sub_pic_cpb_params_in_pic_timing_sei_flag == true`.
+    pub decoding_unit_info: Option<DecodingUnitInfo>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodingUnitInfo {
+    pub num_decoding_units_minus1: u32,
+    /// `Some` means `du_common_cpb_removal_delay_flag == true`.
+    pub du_common_cpb_removal_delay_increment_minus1: Option<u32>,
+    /// `num_nalus_in_du_minus1[i]` for `i` in `[0, num_decoding_units_minus1]`.
+    pub num_nalus_in_du_minus1: Vec<u32>,
+    /// `du_cpb_removal_delay_increment_minus1[i]` for `i` in `[0, num_decoding_units_minus1)`. Empty when
+    /// `du_common_cpb_removal_delay_flag == true`.
+    pub du_cpb_removal_delay_increment_minus1: Vec<u32>,
+}
+
+/// See `recovery_point()` in _D.2.8 Recovery point SEI message syntax_ in the spec.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecoveryPoint {
+    /// The recovery point in output order, as a POC difference from the picture the SEI message is associated with.
+    pub recovery_poc_cnt: i32,
+    /// Whether decoding from the picture reproduces the recovery point picture exactly.
+    pub exact_match_flag: bool,
+    pub broken_link_flag: bool,
+}
+
+/// The parameters of the active SPS which `buffering_period()` and `pic_timing()` depend on.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TimingSeiContext {
+    pub sps_seq_parameter_set_id: u8,
+    pub frame_field_info_present_flag: bool,
+    /// The defaults when there are no HRD parameters.
+    pub hrd_common_info: HrdCommonInfo,
+    /// `cpb_cnt_minus1[0]`, following the reference decoder in using the one of the lowest sub-layer.
+    pub cpb_cnt_minus1: u8,
+}
+
+/// The nominal times of an access unit derived by `HrdTimingModel`, in seconds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AccessUnitTiming {
+    /// `AuNominalRemovalTime`: When the access unit is removed from the CPB and decoded.
+    pub au_nominal_removal_time: f64,
+    /// `DpbOutputTime`: When the picture is output from the DPB.
+    pub dpb_output_time: f64,
+}
+
+/// Derives the nominal CPB removal and DPB output times of access units from their timing SEI messages, as specified in
+/// _C.2.3 Timing of decoding unit removal and decoding of decoding units_ and _C.3.3 Picture output_.
+///
+/// Operates at the access unit level and ignores the final arrival times, so the removal time of an access unit
+/// starting a buffering period with `concatenation_flag == true` is based on `au_cpb_removal_delay_delta_minus1` only.
+#[derive(Debug, Clone)]
+pub struct HrdTimingModel {
+    /// `ClockTick`, in seconds.
+    clock_tick: f64,
+    sched_sel_idx: usize,
+    /// `AuNominalRemovalTime` of the first access unit of the current buffering period.
+    first_au_in_buffering_period_removal_time: Option<f64>,
+    /// `AuNominalRemovalTime` of the previous access unit.
+    prev_au_removal_time: Option<f64>,
+}
+
+impl BufferingPeriod {
+    /// Returns `None` if `bp_seq_parameter_set_id` does not refer to the SPS of `context`, whose HRD parameters are then
+    /// unknown.
+    pub(crate) fn from_bytes(
+        bytes: &[u8],
+        context: &TimingSeiContext,
+    ) -> Result<Option<Self>, io::Error> {
+        let hrd_common_info = &context.hrd_common_info;
+        let sub_pic_hrd_params_present_flag = hrd_common_info.sub_pic_hrd_params.is_some();
+
+        let mut bit_reader = BitReader::endian(io::Cursor::new(bytes), BigEndian);
+
+        let bp_seq_parameter_set_id = Self::read_bp_seq_parameter_set_id(&mut bit_reader)?;
+        if bp_seq_parameter_set_id != context.sps_seq_parameter_set_id {
+            return Ok(None);
+        }
+
+        let irap_cpb_params_present_flag = if !sub_pic_hrd_params_present_flag {
+            bit_reader.read_bit()?
+        } else {
+            false
+        };
+        let au_cpb_removal_delay_length =
+            hrd_common_info.au_cpb_removal_delay_length_minus1 as u32 + 1;
+        let (cpb_delay_offset, dpb_delay_offset) = if irap_cpb_params_present_flag {
+            let dpb_output_delay_length = hrd_common_info.dpb_output_delay_length_minus1 as u32 + 1;
+            (
+                Some(bit_reader.read(au_cpb_removal_delay_length)?),
+                Some(bit_reader.read(dpb_output_delay_length)?),
+            )
+        } else {
+            (None, None)
+        };
+        let concatenation_flag = bit_reader.read_bit()?;
+        let au_cpb_removal_delay_delta_minus1 = bit_reader.read(au_cpb_removal_delay_length)?;
+
+        let alt_present = sub_pic_hrd_params_present_flag || irap_cpb_params_present_flag;
+        let initial_cpb_removal_delay_length =
+            hrd_common_info.initial_cpb_removal_delay_length_minus1 as u32 + 1;
+        let mut read_initial_cpb_removals = |present: bool| -> Result<_, io::Error> {
+            if !present {
+                return Ok(None);
+            }
+            let mut initial_cpb_removals: Vec<InitialCpbRemoval> = Vec::new();
+            for _ in 0..=context.cpb_cnt_minus1 {
+                let initial_cpb_removal_delay =
+                    bit_reader.read(initial_cpb_removal_delay_length)?;
+                let initial_cpb_removal_offset =
+                    bit_reader.read(initial_cpb_removal_delay_length)?;
+                let (initial_alt_cpb_removal_delay, initial_alt_cpb_removal_offset) = if alt_present
+                {
+                    (
+                        Some(bit_reader.read(initial_cpb_removal_delay_length)?),
+                        Some(bit_reader.read(initial_cpb_removal_delay_length)?),
+                    )
+                } else {
+                    (None, None)
+                };
+                initial_cpb_removals.push(InitialCpbRemoval {
+                    initial_cpb_removal_delay,
+                    initial_cpb_removal_offset,
+                    initial_alt_cpb_removal_delay,
+                    initial_alt_cpb_removal_offset,
+                });
+            }
+            Ok(Some(initial_cpb_removals))
+        };
+        let nal_initial_cpb_removal =
+            read_initial_cpb_removals(hrd_common_info.nal_hrd_parameters_present_flag)?;
+        let vcl_initial_cpb_removal =
+            read_initial_cpb_removals(hrd_common_info.vcl_hrd_parameters_present_flag)?;
+
+        let bit_position = bit_reader.position_in_bits()?;
+        let use_alt_cpb_params_flag = if payload_extension_present(bytes, bit_position) {
+            Some(bit_reader.read_bit()?)
+        } else {
+            None
+        };
+
+        Ok(Some(Self {
+            bp_seq_parameter_set_id,
+            irap_cpb_params_present_flag,
+            cpb_delay_offset,
+            dpb_delay_offset,
+            concatenation_flag,
+            au_cpb_removal_delay_delta_minus1,
+            nal_initial_cpb_removal,
+            vcl_initial_cpb_removal,
+            use_alt_cpb_params_flag,
+        }))
+    }
+
+    /// Reads `bp_seq_parameter_set_id` only, which is needed to find the SPS the rest of the payload depends on.
+    pub(crate) fn seq_parameter_set_id_from_bytes(bytes: &[u8]) -> Result<u8, io::Error> {
+        let mut bit_reader = BitReader::endian(io::Cursor::new(bytes), BigEndian);
+        Self::read_bp_seq_parameter_set_id(&mut bit_reader)
+    }
+
+    fn read_bp_seq_parameter_set_id<R: io::Read>(
+        bit_reader: &mut BitReader<R, BigEndian>,
+    ) -> Result<u8, io::Error> {
+        let bp_seq_parameter_set_id = read_exp_golomb_ue(bit_reader)?;
+        if bp_seq_parameter_set_id > 15 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "bp_seq_parameter_set_id out of range: {}",
+                    bp_seq_parameter_set_id
+                ),
+            ));
+        }
+        Ok(bp_seq_parameter_set_id as u8)
+    }
+
+    /// `InitCpbRemovalDelay[SchedSelIdx]`, preferring the NAL HRD parameters over the VCL ones.
+    pub fn initial_cpb_removal_delay(&self, sched_sel_idx: usize) -> Option<u32> {
+        self.nal_initial_cpb_removal
+            .as_ref()
+            .or(self.vcl_initial_cpb_removal.as_ref())
+            .and_then(|x| x.get(sched_sel_idx))
+            .map(|x| x.initial_cpb_removal_delay)
+    }
+}
+
+impl PicTiming {
+    pub(crate) fn from_bytes(bytes: &[u8], context: &TimingSeiContext) -> Result<Self, io::Error> {
+        let hrd_common_info = &context.hrd_common_info;
+        let cpb_dpb_delays_present_flag = hrd_common_info.nal_hrd_parameters_present_flag
+            || hrd_common_info.vcl_hrd_parameters_present_flag;
+
+        let mut bit_reader = BitReader::endian(bytes, BigEndian);
+
+        let frame_field_info = if context.frame_field_info_present_flag {
+            Some(FrameFieldInfo {
+                pic_struct: bit_reader.read(4)?,
+                source_scan_type: bit_reader.read(2)?,
+                duplicate_flag: bit_reader.read_bit()?,
+            })
+        } else {
+            None
+        };
+
+        let cpb_dpb_delays = if cpb_dpb_delays_present_flag {
+            let au_cpb_removal_delay_minus1 =
+                bit_reader.read(hrd_common_info.au_cpb_removal_delay_length_minus1 as u32 + 1)?;
+            let pic_dpb_output_delay =
+                bit_reader.read(hrd_common_info.dpb_output_delay_length_minus1 as u32 + 1)?;
+
+            let mut pic_dpb_output_du_delay = None;
+            let mut decoding_unit_info = None;
+            if let Some(sub_pic_hrd_params) = &hrd_common_info.sub_pic_hrd_params {
+                pic_dpb_output_du_delay = Some(
+                    bit_reader
+                        .read(sub_pic_hrd_params.dpb_output_delay_du_length_minus1 as u32 + 1)?,
+                );
+
+                if sub_pic_hrd_params.sub_pic_cpb_params_in_pic_timing_sei_flag {
+                    let du_cpb_removal_delay_increment_length =
+                        sub_pic_hrd_params.du_cpb_removal_delay_increment_length_minus1 as u32 + 1;
+
+                    let num_decoding_units_minus1 = read_exp_golomb_ue(&mut bit_reader)?;
+                    let du_common_cpb_removal_delay_flag = bit_reader.read_bit()?;
+                    let du_common_cpb_removal_delay_increment_minus1 =
+                        if du_common_cpb_removal_delay_flag {
+                            Some(bit_reader.read(du_cpb_removal_delay_increment_length)?)
+                        } else {
+                            None
+                        };
+
+                    let mut num_nalus_in_du_minus1: Vec<u32> = Vec::new();
+                    let mut du_cpb_removal_delay_increment_minus1: Vec<u32> = Vec::new();
+                    for i in 0..=num_decoding_units_minus1 {
+                        num_nalus_in_du_minus1.push(read_exp_golomb_ue(&mut bit_reader)?);
+                        if !du_common_cpb_removal_delay_flag && i < num_decoding_units_minus1 {
+                            du_cpb_removal_delay_increment_minus1
+                                .push(bit_reader.read(du_cpb_removal_delay_increment_length)?);
+                        }
+                    }
+
+                    decoding_unit_info = Some(DecodingUnitInfo {
+                        num_decoding_units_minus1,
+                        du_common_cpb_removal_delay_increment_minus1,
+                        num_nalus_in_du_minus1,
+                        du_cpb_removal_delay_increment_minus1,
+                    });
+                }
+            }
+
+            Some(CpbDpbDelays {
+                au_cpb_removal_delay_minus1,
+                pic_dpb_output_delay,
+                pic_dpb_output_du_delay,
+                decoding_unit_info,
+            })
+        } else {
+            None
+        };
+
+        Ok(Self {
+            frame_field_info,
+            cpb_dpb_delays,
+        })
+    }
+}
+
+impl RecoveryPoint {
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Result<Self, io::Error> {
+        let mut bit_reader = BitReader::endian(bytes, BigEndian);
+
+        Ok(Self {
+            recovery_poc_cnt: read_exp_golomb_se(&mut bit_reader)?,
+            exact_match_flag: bit_reader.read_bit()?,
+            broken_link_flag: bit_reader.read_bit()?,
+        })
+    }
+
+    /// The POC of the recovery point picture, given the POC of the picture the SEI message is associated with.
+    ///
+    /// Pictures at or after it in output order are correct (or approximately so when `exact_match_flag == false`) when
+    /// decoding starts at the associated picture.
+    pub fn recovery_poc(&self, pic_order_cnt_val: i32) -> i32 {
+        pic_order_cnt_val + self.recovery_poc_cnt
+    }
+}
+
+impl HrdTimingModel {
+    /// `sched_sel_idx` selects the CPB specification, usually `0`.
+    pub fn new(vui_timing_info: &VuiTimingInfo, sched_sel_idx: usize) -> Self {
+        Self {
+            clock_tick: vui_timing_info.vui_num_units_in_tick as f64
+                / vui_timing_info.vui_time_scale as f64,
+            sched_sel_idx,
+            first_au_in_buffering_period_removal_time: None,
+            prev_au_removal_time: None,
+        }
+    }
+
+    /// Advances to the next access unit in decoding order, given its timing SEI messages.
+    ///
+    /// Returns `None` if `pic_timing` has no CPB/DPB delays, or the first access unit has no usable `buffering_period`.
+    pub fn next_access_unit(
+        &mut self,
+        buffering_period: Option<&BufferingPeriod>,
+        pic_timing: &PicTiming,
+    ) -> Option<AccessUnitTiming> {
+        let cpb_dpb_delays = pic_timing.cpb_dpb_delays.as_ref()?;
+        let au_cpb_removal_delay_val = cpb_dpb_delays.au_cpb_removal_delay_minus1 as f64 + 1.0;
+
+        let au_nominal_removal_time = match (buffering_period, self.prev_au_removal_time) {
+            (Some(buffering_period), None) => {
+                buffering_period.initial_cpb_removal_delay(self.sched_sel_idx)? as f64 / 90000.0
+            }
+            (Some(buffering_period), Some(prev_au_removal_time)) => {
+                if buffering_period.concatenation_flag {
+                    prev_au_removal_time
+                        + self.clock_tick
+                            * (buffering_period.au_cpb_removal_delay_delta_minus1 as f64 + 1.0)
+                } else {
+                    self.first_au_in_buffering_period_removal_time?
+                        + self.clock_tick * au_cpb_removal_delay_val
+                }
+            }
+            (None, _) => {
+                self.first_au_in_buffering_period_removal_time?
+                    + self.clock_tick * au_cpb_removal_delay_val
+            }
+        };
+
+        if buffering_period.is_some() {
+            self.first_au_in_buffering_period_removal_time = Some(au_nominal_removal_time);
+        }
+        self.prev_au_removal_time = Some(au_nominal_removal_time);
+
+        Some(AccessUnitTiming {
+            au_nominal_removal_time,
+            dpb_output_time: au_nominal_removal_time
+                + self.clock_tick * cpb_dpb_delays.pic_dpb_output_delay as f64,
+        })
+    }
+}
+
+impl TimingSeiContext {
+    pub fn from_sps(sps: &SequenceParameterSet) -> Self {
+        let hrd_parameters = sps
+            .vui
+            .as_ref()
+            .and_then(|vui| vui.vui_timing_info.as_ref())
+            .and_then(|vui_timing_info| vui_timing_info.vui_hrd_parameters.as_ref());
+
+        Self {
+            sps_seq_parameter_set_id: sps.sps_seq_parameter_set_id,
+            frame_field_info_present_flag: sps
+                .vui
+                .as_ref()
+                .is_some_and(|vui| vui.frame_field_info_present_flag),
+            hrd_common_info: hrd_parameters
+                .map(|hrd_parameters| hrd_parameters.common_info)
+                .unwrap_or_default(),
+            cpb_cnt_minus1: hrd_parameters
+                .and_then(|hrd_parameters| hrd_parameters.sub_layers.first())
+                .map_or(0, |sub_layer| sub_layer.cpb_cnt_minus1),
+        }
+    }
+}
+
+/// `payload_extension_present()`: Whether there is data between `bit_position` and the `payload_bit_equal_to_one` of
+/// the payload.
+fn payload_extension_present(bytes: &[u8], bit_position: u64) -> bool {
+    let last_one_bit_position = bytes
+        .iter()
+        .rposition(|&x| x != 0)
+        .map(|i| i as u64 * 8 + 7 - bytes[i].trailing_zeros() as u64);
+    last_one_bit_position.is_some_and(|x| x > bit_position)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::BTreeMap;
+
+    use bitstream_io::{BitWrite, BitWriter};
+
+    use crate::base::write_exp_golomb_ue;
+    use crate::h265::nalu::NaluType;
+    use crate::h265::sei::{Sei, SeiPayload, SeiPayloadRegistry};
+
+    #[test]
+    fn timing_sei_works() {
+        // An SPS with `frame_field_info_present_flag`, and NAL HRD parameters with 1 CPB specification at 25 Hz.
+        let context = TimingSeiContext {
+            sps_seq_parameter_set_id: 0,
+            frame_field_info_present_flag: true,
+            hrd_common_info: HrdCommonInfo {
+                nal_hrd_parameters_present_flag: true,
+                initial_cpb_removal_delay_length_minus1: 15,
+                au_cpb_removal_delay_length_minus1: 7,
+                dpb_output_delay_length_minus1: 4,
+                ..Default::default()
+            },
+            cpb_cnt_minus1: 0,
+        };
+        let vui_timing_info = VuiTimingInfo {
+            vui_num_units_in_tick: 1,
+            vui_time_scale: 25,
+            vui_num_ticks_poc_diff_one_minus1: None,
+            vui_hrd_parameters: None,
+        };
+
+        let mut payloads: Vec<(u8, Vec<u8>)> = Vec::new();
+        {
+            // `buffering_period()`: bp_seq_parameter_set_id = 0, irap_cpb_params_present_flag = 0,
+            // concatenation_flag = 0, au_cpb_removal_delay_delta_minus1 = 0,
+            // nal_initial_cpb_removal_delay[0] = 9000, nal_initial_cpb_removal_offset[0] = 0
+            let mut bytes: Vec<u8> = Vec::new();
+            let mut bit_writer = BitWriter::endian(&mut bytes, BigEndian);
+            write_exp_golomb_ue(&mut bit_writer, 0);
+            bit_writer.write(2, 0u8).unwrap();
+            bit_writer.write(8, 0u8).unwrap();
+            bit_writer.write(16, 9000u16).unwrap();
+            bit_writer.write(16, 0u16).unwrap();
+            // `payload_bit_equal_to_one`
+            bit_writer.write_bit(true).unwrap();
+            bit_writer.byte_align().unwrap();
+            payloads.push((0, bytes));
+        }
+        {
+            // `pic_timing()`: pic_struct = 1, source_scan_type = 0, duplicate_flag = 0,
+            // au_cpb_removal_delay_minus1 = 1, pic_dpb_output_delay = 3
+            let mut bytes: Vec<u8> = Vec::new();
+            let mut bit_writer = BitWriter::endian(&mut bytes, BigEndian);
+            bit_writer.write(4, 1u8).unwrap();
+            bit_writer.write(3, 0u8).unwrap();
+            bit_writer.write(8, 1u8).unwrap();
+            bit_writer.write(5, 3u8).unwrap();
+            bit_writer.write_bit(true).unwrap();
+            bit_writer.byte_align().unwrap();
+            payloads.push((1, bytes));
+        }
+        // `recovery_point()`: recovery_poc_cnt = -2, exact_match_flag = 1, broken_link_flag = 0
+        payloads.push((6, vec![0b0010_1101]));
+
+        let mut rbsp: Vec<u8> = Vec::new();
+        for (payload_type, bytes) in &payloads {
+            rbsp.push(*payload_type);
+            rbsp.push(bytes.len() as u8);
+            rbsp.extend_from_slice(bytes);
+        }
+        rbsp.push(0x80);
+
+        let sei = Sei::from_rbsp(
+            &rbsp,
+            NaluType::PrefixSeiNut,
+            &SeiPayloadRegistry::default(),
+            Some(context),
+            &BTreeMap::new(),
+        )
+        .unwrap();
+
+        let buffering_period = sei.sei_messages[0].payload.as_buffering_period().unwrap();
+        assert!(!buffering_period.irap_cpb_params_present_flag);
+        assert_eq!(buffering_period.initial_cpb_removal_delay(0), Some(9000));
+        assert!(buffering_period.vcl_initial_cpb_removal.is_none());
+        assert_eq!(buffering_period.use_alt_cpb_params_flag, None);
+
+        let pic_timing = sei.sei_messages[1].payload.as_pic_timing().unwrap();
+        assert_eq!(
+            pic_timing.frame_field_info,
+            Some(FrameFieldInfo {
+                pic_struct: 1,
+                source_scan_type: 0,
+                duplicate_flag: false,
+            })
+        );
+        let cpb_dpb_delays = pic_timing.cpb_dpb_delays.as_ref().unwrap();
+        assert_eq!(cpb_dpb_delays.au_cpb_removal_delay_minus1, 1);
+        assert_eq!(cpb_dpb_delays.pic_dpb_output_delay, 3);
+
+        let recovery_point = sei.sei_messages[2].payload.as_recovery_point().unwrap();
+        assert_eq!(recovery_point.recovery_poc_cnt, -2);
+        assert!(recovery_point.exact_match_flag);
+        assert!(!recovery_point.broken_link_flag);
+        assert_eq!(recovery_point.recovery_poc(10), 8);
+
+        // Without the SPS, the messages depending on it are kept as-is.
+        let sei = Sei::from_rbsp_reader(
+            &mut rbsp.as_slice(),
+            NaluType::PrefixSeiNut,
+            &SeiPayloadRegistry::default(),
+            None,
+            &BTreeMap::new(),
+        )
+        .unwrap();
+        assert!(matches!(
+            sei.sei_messages[0].payload,
+            SeiPayload::Unknown(_)
+        ));
+        assert!(matches!(
+            sei.sei_messages[1].payload,
+            SeiPayload::Unknown(_)
+        ));
+        assert!(sei.sei_messages[2].payload.as_recovery_point().is_some());
+
+        let mut hrd_timing_model = HrdTimingModel::new(&vui_timing_info, 0);
+        let timing = hrd_timing_model
+            .next_access_unit(Some(buffering_period), pic_timing)
+            .unwrap();
+        assert!((timing.au_nominal_removal_time - 0.1).abs() < 1e-9);
+        assert!((timing.dpb_output_time - 0.22).abs() < 1e-9);
+        let timing = hrd_timing_model.next_access_unit(None, pic_timing).unwrap();
+        assert!((timing.au_nominal_removal_time - 0.18).abs() < 1e-9);
+    }
+}
@@ -3,11 +3,13 @@ use std::io::{self, Read};
 use bitstream_io::{BigEndian, BitReader};
 use bitstream_io::BitRead as _;
 
-use crate::base::read_exp_golomb_ue;
+use crate::base::{read_exp_golomb_se, read_exp_golomb_ue, CountingReader};
 use crate::h265::nalu::NaluType;
 use crate::h265::rps::ShortTermReferencePictureSet;
+use crate::h265::sps::SequenceParameterSet;
+use crate::h265::pps::PictureParameterSet;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct SliceSegmentContext {
     pub dependent_slice_segments_enabled_flag: bool,
     pub pic_width_in_luma_samples: u32,
@@ -19,6 +21,38 @@ pub struct SliceSegmentContext {
     pub separate_colour_plane_flag: bool,
     pub log2_max_pic_order_cnt_lsb_minus4: u8,
     pub num_short_term_ref_pic_sets: u8,
+    /// The candidate short-term RPSs in the SPS, i.e. `SequenceParameterSet::short_term_ref_pic_sets`.
+    ///
+    /// Required for a short-term RPS in a slice segment header predicted from one of them.
+    pub short_term_ref_pic_sets: Vec<ShortTermReferencePictureSet>,
+    pub long_term_ref_pics_present_flag: bool,
+    pub num_long_term_ref_pics_sps: u8,
+    pub lt_ref_pic_poc_lsb_sps: [u16; 32],
+    pub used_by_curr_pic_lt_sps_flag: [bool; 32],
+    pub sps_temporal_mvp_enabled_flag: bool,
+    pub sample_adaptive_offset_enabled_flag: bool,
+    /// `ChromaArrayType`.
+    pub chroma_array_type: u8,
+    pub bit_depth_chroma_minus8: u8,
+    pub high_precision_offsets_enabled_flag: bool,
+    pub lists_modification_present_flag: bool,
+    pub cabac_init_present_flag: bool,
+    pub num_ref_idx_l0_default_active_minus1: u8,
+    pub num_ref_idx_l1_default_active_minus1: u8,
+    pub weighted_pred_flag: bool,
+    pub weighted_bipred_flag: bool,
+    pub pps_slice_chroma_qp_offsets_present_flag: bool,
+    pub chroma_qp_offset_list_enabled_flag: bool,
+    pub deblocking_filter_override_enabled_flag: bool,
+    pub pps_deblocking_filter_disabled_flag: bool,
+    /// `0` when not present in the PPS.
+    pub pps_beta_offset_div2: i8,
+    /// `0` when not present in the PPS.
+    pub pps_tc_offset_div2: i8,
+    pub pps_loop_filter_across_slices_enabled_flag: bool,
+    pub tiles_enabled_flag: bool,
+    pub entropy_coding_sync_enabled_flag: bool,
+    pub slice_segment_header_extension_present_flag: bool,
 }
 
 /// See `slice_segment_layer_rbsp()` in _7.3.2.9 Slice segment layer RBSP syntax_ in the spec.
@@ -28,7 +62,11 @@ pub struct SliceSegmentLayer {
 }
 
 /// See `slice_segment_header()` in _7.3.6 Slice segment header syntax_ in the spec.
-#[derive(Debug, Clone, Copy)]
+///
+/// For a dependent slice segment, i.e. `dependent_slice_segment_flag == Some(true)`, the syntax elements up to
+/// `slice_loop_filter_across_slices_enabled_flag` are not present and are to be taken from the preceding independent slice segment header.
+/// The corresponding fields are left at their inferred or default values.
+#[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SliceSegmentHeader {
     pub nal_unit_type: NaluType,
@@ -58,9 +96,181 @@ pub struct SliceSegmentHeader {
     pub short_term_ref_pic_set_idx: Option<u8>,
     /// `CurrRpsIdx`.
     pub curr_rps_idx: u8,
+    /// `Some` when `SliceSegmentContext::long_term_ref_pics_present_flag == true` for non-IDR slices.
+    pub long_term_ref_pics: Option<LongTermRefPics>,
+    /// `None` for dependent slice segments.
+    pub slice_type: Option<SliceType>,
+    /// Inferred to be `true` when not present.
+    pub pic_output_flag: bool,
+    /// `Some` when `separate_colour_plane_flag == true`.
+    pub colour_plane_id: Option<u8>,
+    /// Specifies whether temporal motion vector predictors can be used for inter prediction. Inferred to be `false` when not present.
+    pub slice_temporal_mvp_enabled_flag: bool,
+    pub slice_sao_luma_flag: bool,
+    pub slice_sao_chroma_flag: bool,
+    /// `Some` for P and B slices.
+    pub num_ref_idx_active_override_flag: Option<bool>,
+    /// Inferred to be `num_ref_idx_l0_default_active_minus1` when not present. Only meaningful for P and B slices.
+    pub num_ref_idx_l0_active_minus1: u8,
+    /// Inferred to be `num_ref_idx_l1_default_active_minus1` when not present. Only meaningful for B slices.
+    pub num_ref_idx_l1_active_minus1: u8,
+    /// `Some` when `lists_modification_present_flag == true` and `NumPicTotalCurr > 1` for P and B slices.
+    pub ref_pic_lists_modification: Option<RefPicListsModification>,
+    pub mvd_l1_zero_flag: bool,
+    /// Inferred to be `false` when not present.
+    pub cabac_init_flag: bool,
+    /// Inferred to be `true` when not present.
+    pub collocated_from_l0_flag: bool,
+    /// Inferred to be `0` when not present.
+    pub collocated_ref_idx: u8,
+    /// `Some` when `weighted_pred_flag == true` for P slices or `weighted_bipred_flag == true` for B slices.
+    pub pred_weight_table: Option<PredWeightTable>,
+    /// `Some` for P and B slices.
+    pub five_minus_max_num_merge_cand: Option<u8>,
+    /// Specifies the initial value of `QpY` to be used for the coding blocks in the slice until modified by the value of `CuQpDeltaVal` in the coding unit layer.
+    pub slice_qp_delta: i8,
+    /// Inferred to be `0` when not present.
+    pub slice_cb_qp_offset: i8,
+    /// Inferred to be `0` when not present.
+    pub slice_cr_qp_offset: i8,
+    /// Specifies whether `cu_chroma_qp_offset_flag` may be present in the transform unit syntax. Inferred to be `false` when not present.
+    pub cu_chroma_qp_offset_enabled_flag: bool,
+    /// `Some` when `deblocking_filter_override_enabled_flag == true`.
+    pub deblocking_filter_override_flag: Option<bool>,
+    /// Inferred to be `pps_deblocking_filter_disabled_flag` when not present.
+    pub slice_deblocking_filter_disabled_flag: bool,
+    /// The deblocking parameter offset for β, divided by 2. Inferred to be `pps_beta_offset_div2` when not present.
+    pub slice_beta_offset_div2: i8,
+    /// The deblocking parameter offset for tC, divided by 2. Inferred to be `pps_tc_offset_div2` when not present.
+    pub slice_tc_offset_div2: i8,
+    /// Inferred to be `pps_loop_filter_across_slices_enabled_flag` when not present.
+    pub slice_loop_filter_across_slices_enabled_flag: bool,
+    /// `NumPicTotalCurr`; the number of pictures in the RPS usable for inter prediction of the current picture.
+    pub num_pic_total_curr: u8,
+    /// `0` when not present.
+    pub num_entry_point_offsets: u32,
+    /// `0` when not present.
+    pub offset_len_minus1: u8,
+    /// `entry_point_offset_minus1[i]` for `i` in `[0, num_entry_point_offsets)`.
+    ///
+    /// The offsets are in bytes of the slice segment data, which includes emulation prevention bytes.
+    pub entry_point_offset_minus1: Vec<u32>,
+    /// `slice_segment_header_extension_data_byte[i]`. Empty when not present.
+    pub slice_segment_header_extension_data_byte: Vec<u8>,
+    /// The offset in bytes of `slice_segment_data()` from the start of the slice segment layer RBSP, i.e. the size of the
+    /// slice segment header including `byte_alignment()`, excluding the NAL unit header.
+    ///
+    /// This is an RBSP offset, i.e. not counting emulation prevention bytes. See `base::rbsp_offset_to_ebsp_offset()` for the
+    /// offset in the NAL unit payload.
+    pub slice_segment_data_offset: u32,
+}
+
+/// See `ref_pic_lists_modification()` in _7.3.6.2 Reference picture list modification syntax_ in the spec.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RefPicListsModification {
+    pub ref_pic_list_modification_flag_l0: bool,
+    /// `list_entry_l0[i]` for `i` in `[0, num_ref_idx_l0_active_minus1]`.
+    pub list_entry_l0: [u8; 16],
+    pub ref_pic_list_modification_flag_l1: bool,
+    /// `list_entry_l1[i]` for `i` in `[0, num_ref_idx_l1_active_minus1]`.
+    pub list_entry_l1: [u8; 16],
+}
+
+/// See `pred_weight_table()` in _7.3.6.3 Weighted prediction parameters syntax_ in the spec.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PredWeightTable {
+    /// The base 2 logarithm of the denominator for all luma weighting factors. `[0, 7]`.
+    pub luma_log2_weight_denom: u8,
+    /// `Some` when `ChromaArrayType != 0`.
+    pub delta_chroma_log2_weight_denom: Option<i8>,
+    /// `ChromaLog2WeightDenom = luma_log2_weight_denom + delta_chroma_log2_weight_denom`. `[0, 7]`.
+    pub chroma_log2_weight_denom: u8,
+    /// The weights for reference picture list 0.
+    pub l0: PredWeights,
+    /// The weights for reference picture list 1. `Some` for B slices.
+    pub l1: Option<PredWeights>,
+}
+
+/// The part of `pred_weight_table()` for a single reference picture list `X`.
+///
+/// Each array is indexed by the reference index `i` in `[0, num_ref_idx_lX_active_minus1]`, and the chroma arrays additionally by
+/// the chroma component `j`: `0` for Cb and `1` for Cr.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PredWeights {
+    /// `luma_weight_lX_flag[i]`.
+    pub luma_weight_flag: [bool; 16],
+    /// `chroma_weight_lX_flag[i]`. `false` when `ChromaArrayType == 0`.
+    pub chroma_weight_flag: [bool; 16],
+    /// `delta_luma_weight_lX[i]`. `0` when not present.
+    pub delta_luma_weight: [i8; 16],
+    /// `luma_offset_lX[i]`. `0` when not present.
+    ///
+    /// The value is to be left-shifted by `BitDepthY - 8` in the weighted sample prediction process
+    /// unless `high_precision_offsets_enabled_flag == true`.
+    pub luma_offset: [i32; 16],
+    /// `delta_chroma_weight_lX[i][j]`. `0` when not present.
+    pub delta_chroma_weight: [[i8; 2]; 16],
+    /// `delta_chroma_offset_lX[i][j]`. `0` when not present.
+    pub delta_chroma_offset: [[i32; 2]; 16],
+    /// `LumaWeightLX[i] = (1 << luma_log2_weight_denom) + delta_luma_weight_lX[i]`.
+    pub luma_weight: [i32; 16],
+    /// `ChromaWeightLX[i][j] = (1 << ChromaLog2WeightDenom) + delta_chroma_weight_lX[i][j]`.
+    pub chroma_weight: [[i32; 2]; 16],
+    /// `ChromaOffsetLX[i][j]`, derived as follows:
+    /// ```text
+    /// ChromaOffsetLX[ i ][ j ] = Clip3( −wpOffsetHalfRangeC, wpOffsetHalfRangeC − 1,
+    ///     ( wpOffsetHalfRangeC − ( ( wpOffsetHalfRangeC * ChromaWeightLX[ i ][ j ] ) >> ChromaLog2WeightDenom ) + delta_chroma_offset_lX[ i ][ j ] ) )
+    /// ```
+    pub chroma_offset: [[i32; 2]; 16],
+}
+
+/// The long-term reference picture part of `slice_segment_header()` in _7.3.6.1 General slice segment header syntax_ in the spec.
+///
+/// Entries `[0, num_long_term_sps)` refer to candidate long-term reference pictures specified in the SPS,
+/// and entries `[num_long_term_sps, num_long_term_sps + num_long_term_pics)` are signalled directly in the slice segment header.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LongTermRefPics {
+    /// Specifies the number of entries in the long-term RPS of the current picture that are derived based on the candidate long-term reference pictures specified in the SPS.
+    pub num_long_term_sps: u8,
+    /// Specifies the number of entries in the long-term RPS of the current picture that are directly signalled in the slice header.
+    pub num_long_term_pics: u8,
+    /// `lt_idx_sps[i]`. Only meaningful for `i < num_long_term_sps`.
+    pub lt_idx_sps: [u8; 32],
+    /// `PocLsbLt[i]`, derived as follows:
+    /// ```text
+    /// if( i < num_long_term_sps )
+    ///     PocLsbLt[ i ] = lt_ref_pic_poc_lsb_sps[ lt_idx_sps[ i ] ]
+    /// else
+    ///     PocLsbLt[ i ] = poc_lsb_lt[ i ]
+    /// ```
+    pub poc_lsb_lt: [u16; 32],
+    /// `UsedByCurrPicLt[i]`, derived as follows:
+    /// ```text
+    /// if( i < num_long_term_sps )
+    ///     UsedByCurrPicLt[ i ] = used_by_curr_pic_lt_sps_flag[ lt_idx_sps[ i ] ]
+    /// else
+    ///     UsedByCurrPicLt[ i ] = used_by_curr_pic_lt_flag[ i ]
+    /// ```
+    pub used_by_curr_pic_lt: [bool; 32],
+    pub delta_poc_msb_present_flag: [bool; 32],
+    /// `delta_poc_msb_cycle_lt[i]`. `0` when not present.
+    pub delta_poc_msb_cycle_lt: [u32; 32],
+    /// `DeltaPocMsbCycleLt[i]`, derived as follows:
+    /// ```text
+    /// if( i = = 0 | | i = = num_long_term_sps )
+    ///     DeltaPocMsbCycleLt[ i ] = delta_poc_msb_cycle_lt[ i ]
+    /// else
+    ///     DeltaPocMsbCycleLt[ i ] = delta_poc_msb_cycle_lt[ i ] + DeltaPocMsbCycleLt[ i − 1 ]
+    /// ```
+    pub delta_poc_msb_cycle_lt_accumulated: [u32; 32],
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 pub enum SliceType {
     /// B slice.
@@ -84,28 +294,127 @@ impl TryFrom<u8> for SliceType {
     }
 }
 
+impl SliceSegmentContext {
+    /// Gathers the values needed to parse slice segment headers referring to `pps`, which in turn refers to `sps`.
+    ///
+    /// Returns `Unsupported` for SCC(Screen Content Coding) streams, i.e. with `sps_scc_extension_flag` or
+    /// `pps_scc_extension_flag`, as the SCC syntax elements of slice segment headers are not parsed.
+    pub fn new(sps: &SequenceParameterSet, pps: &PictureParameterSet) -> Result<Self, io::Error> {
+        if sps.sps_scc_extension_flag || pps.pps_scc_extension_flag {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "sps_scc_extension_flag or pps_scc_extension_flag == true not supported",
+            ));
+        }
+
+        Ok(Self {
+            dependent_slice_segments_enabled_flag: pps.dependent_slice_segments_enabled_flag,
+            pic_width_in_luma_samples: sps.pic_width_in_luma_samples,
+            pic_height_in_luma_samples: sps.pic_height_in_luma_samples,
+            log2_min_luma_coding_block_size_minus3: sps.log2_min_luma_coding_block_size_minus3,
+            log2_diff_max_min_luma_coding_block_size: sps.log2_diff_max_min_luma_coding_block_size,
+            num_extra_slice_header_bits: pps.num_extra_slice_header_bits,
+            output_flag_present_flag: pps.output_flag_present_flag,
+            separate_colour_plane_flag: sps.separate_colour_plane_flag.unwrap_or(false),
+            log2_max_pic_order_cnt_lsb_minus4: sps.log2_max_pic_order_cnt_lsb_minus4,
+            num_short_term_ref_pic_sets: sps.short_term_ref_pic_sets.len() as u8,
+            short_term_ref_pic_sets: sps.short_term_ref_pic_sets.clone(),
+            long_term_ref_pics_present_flag: sps.long_term_ref_pics_present_flag,
+            num_long_term_ref_pics_sps: sps.num_long_term_ref_pics_sps,
+            lt_ref_pic_poc_lsb_sps: sps.lt_ref_pic_poc_lsb_sps,
+            used_by_curr_pic_lt_sps_flag: sps.used_by_curr_pic_lt_sps_flag,
+            sps_temporal_mvp_enabled_flag: sps.sps_temporal_mvp_enabled_flag,
+            sample_adaptive_offset_enabled_flag: sps.sample_adaptive_offset_enabled_flag,
+            chroma_array_type: sps.chroma_array_type(),
+            bit_depth_chroma_minus8: sps.bit_depth_chroma_minus8,
+            high_precision_offsets_enabled_flag: sps
+                .sps_range_extension
+                .map(|x| x.high_precision_offsets_enabled_flag)
+                .unwrap_or(false),
+            lists_modification_present_flag: pps.lists_modification_present_flag,
+            cabac_init_present_flag: pps.cabac_init_present_flag,
+            num_ref_idx_l0_default_active_minus1: pps.num_ref_idx_l0_default_active_minus1,
+            num_ref_idx_l1_default_active_minus1: pps.num_ref_idx_l1_default_active_minus1,
+            weighted_pred_flag: pps.weighted_pred_flag,
+            weighted_bipred_flag: pps.weighted_bipred_flag,
+            pps_slice_chroma_qp_offsets_present_flag: pps.pps_slice_chroma_qp_offsets_present_flag,
+            chroma_qp_offset_list_enabled_flag: pps
+                .pps_range_extension
+                .map(|x| x.chroma_qp_offset_list_enabled_flag)
+                .unwrap_or(false),
+            deblocking_filter_override_enabled_flag: pps
+                .deblocking_filter_control
+                .map(|x| x.deblocking_filter_override_enabled_flag)
+                .unwrap_or(false),
+            pps_deblocking_filter_disabled_flag: pps
+                .deblocking_filter_control
+                .map(|x| x.pps_deblocking_filter_disabled_flag)
+                .unwrap_or(false),
+            pps_beta_offset_div2: pps
+                .deblocking_filter_control
+                .and_then(|x| x.pps_beta_offset_div2)
+                .unwrap_or(0),
+            pps_tc_offset_div2: pps
+                .deblocking_filter_control
+                .and_then(|x| x.pps_tc_offset_div2)
+                .unwrap_or(0),
+            pps_loop_filter_across_slices_enabled_flag: pps
+                .pps_loop_filter_across_slices_enabled_flag,
+            tiles_enabled_flag: pps.tiles.is_some(),
+            entropy_coding_sync_enabled_flag: pps.entropy_coding_sync_enabled_flag,
+            slice_segment_header_extension_present_flag: pps
+                .slice_segment_header_extension_present_flag,
+        })
+    }
+}
+
 impl SliceSegmentHeader {
-    /// `NumDeltaPocs[RefRpsIdx]`.
+    /// `MaxNumMergeCand = 5 - five_minus_max_num_merge_cand`.
     ///
-    /// Returns `Some` if this slice segment header has a `ShortTermReferencePictureSet`, otherwise `None`.
-    pub fn rps_idx_num_delta_pocs(&self) -> Option<u8> {
-        if let Some(rps) = self.short_term_ref_pic_set.as_ref() {
-            Some(rps.num_positive_pics() + rps.num_negative_pics())
-        } else {
+    /// Returns `Some` for P and B slices.
+    pub fn max_num_merge_cand(&self) -> Option<u8> {
+        self.five_minus_max_num_merge_cand.map(|x| 5 - x)
+    }
+
+    /// `SliceQpY = 26 + init_qp_minus26 + slice_qp_delta`.
+    pub fn slice_qp_y(&self, pps: &PictureParameterSet) -> i32 {
+        26 + pps.init_qp_minus26 as i32 + self.slice_qp_delta as i32
+    }
+
+    /// The deblocking parameter offsets `(slice_beta_offset_div2 << 1, slice_tc_offset_div2 << 1)` in effect for the slice,
+    /// with the PPS values applied unless overridden.
+    ///
+    /// `None` when `slice_deblocking_filter_disabled_flag == true`.
+    pub fn deblocking_filter_offsets(&self) -> Option<(i32, i32)> {
+        if self.slice_deblocking_filter_disabled_flag {
             None
+        } else {
+            Some((
+                (self.slice_beta_offset_div2 as i32) << 1,
+                (self.slice_tc_offset_div2 as i32) << 1,
+            ))
         }
     }
 
+    /// `NumDeltaPocs[RefRpsIdx]`.
+    ///
+    /// Returns `Some` if this slice segment header has a `ShortTermReferencePictureSet` predicted from one in the SPS, otherwise `None`.
+    pub fn rps_idx_num_delta_pocs(&self) -> Option<u8> {
+        self.short_term_ref_pic_set
+            .as_ref()
+            .and_then(|rps| rps.rps_idx_num_delta_pocs())
+    }
+
     /// Reads from _RBSP(Raw Byte Sequence Payload)_.
     ///
-    /// ## Remarks
-    /// Currently does *NOT* consume the whole bytes for the slice segment header.
+    /// Consumes exactly the bytes for the slice segment header, up to and including `byte_alignment()`.
     pub fn from_rbsp_reader<R: Read>(
         reader: &mut R,
         nal_unit_type: NaluType,
-        slice_segment_context: SliceSegmentContext,
+        slice_segment_context: &SliceSegmentContext,
     ) -> Result<Self, io::Error> {
-        let mut bit_reader = BitReader::endian(reader, BigEndian);
+        let mut counting_reader = CountingReader::new(reader);
+        let mut bit_reader = BitReader::endian(&mut counting_reader, BigEndian);
 
         let first_slice_segment_in_pic_flag = bit_reader.read_bit()?;
 
@@ -136,12 +445,12 @@ impl SliceSegmentHeader {
                     let ctb_log2_size_y = min_cb_log2_size_y
                         + slice_segment_context.log2_diff_max_min_luma_coding_block_size;
                     let ctb_size_y = 1 << ctb_log2_size_y;
-                    let pic_width_in_ctbs_y =
-                        (slice_segment_context.pic_width_in_luma_samples + ctb_size_y - 1)
-                            / ctb_size_y;
-                    let pic_height_in_ctbs_y =
-                        (slice_segment_context.pic_height_in_luma_samples + ctb_size_y - 1)
-                            / ctb_size_y;
+                    let pic_width_in_ctbs_y = slice_segment_context
+                        .pic_width_in_luma_samples
+                        .div_ceil(ctb_size_y);
+                    let pic_height_in_ctbs_y = slice_segment_context
+                        .pic_height_in_luma_samples
+                        .div_ceil(ctb_size_y);
                     let pic_size_in_ctbs_y = pic_width_in_ctbs_y * pic_height_in_ctbs_y;
 
                     let length_in_bits = (pic_size_in_ctbs_y as f64).log2().ceil() as u32;
@@ -159,6 +468,37 @@ impl SliceSegmentHeader {
         let mut short_term_ref_pic_set_size: Option<u16> = None;
         let mut short_term_ref_pic_set_idx: Option<u8> = None;
         let mut curr_rps_idx: u8 = 0;
+        let mut long_term_ref_pics: Option<LongTermRefPics> = None;
+        let mut slice_type: Option<SliceType> = None;
+        let mut pic_output_flag = true;
+        let mut colour_plane_id: Option<u8> = None;
+        let mut slice_temporal_mvp_enabled_flag = false;
+        let mut slice_sao_luma_flag = false;
+        let mut slice_sao_chroma_flag = false;
+        let mut num_ref_idx_active_override_flag: Option<bool> = None;
+        let mut num_ref_idx_l0_active_minus1 =
+            slice_segment_context.num_ref_idx_l0_default_active_minus1;
+        let mut num_ref_idx_l1_active_minus1 =
+            slice_segment_context.num_ref_idx_l1_default_active_minus1;
+        let mut ref_pic_lists_modification: Option<RefPicListsModification> = None;
+        let mut mvd_l1_zero_flag = false;
+        let mut cabac_init_flag = false;
+        let mut collocated_from_l0_flag = true;
+        let mut collocated_ref_idx: u8 = 0;
+        let mut pred_weight_table: Option<PredWeightTable> = None;
+        let mut five_minus_max_num_merge_cand: Option<u8> = None;
+        let mut slice_qp_delta: i8 = 0;
+        let mut slice_cb_qp_offset: i8 = 0;
+        let mut slice_cr_qp_offset: i8 = 0;
+        let mut cu_chroma_qp_offset_enabled_flag = false;
+        let mut deblocking_filter_override_flag: Option<bool> = None;
+        let mut slice_deblocking_filter_disabled_flag =
+            slice_segment_context.pps_deblocking_filter_disabled_flag;
+        let mut slice_beta_offset_div2 = slice_segment_context.pps_beta_offset_div2;
+        let mut slice_tc_offset_div2 = slice_segment_context.pps_tc_offset_div2;
+        let mut slice_loop_filter_across_slices_enabled_flag =
+            slice_segment_context.pps_loop_filter_across_slices_enabled_flag;
+        let mut num_pic_total_curr: u8 = 0;
 
         if !dependent_slice_segment_flag.unwrap_or(false) {
             for _ in 0..slice_segment_context.num_extra_slice_header_bits {
@@ -166,20 +506,18 @@ impl SliceSegmentHeader {
                 bit_reader.read_bit()?;
             }
 
-            let slice_type: SliceType = (read_exp_golomb_ue(&mut bit_reader)? as u8)
-                .try_into()
-                .unwrap();
+            let slice_type = *slice_type.insert(
+                (read_exp_golomb_ue(&mut bit_reader)? as u8)
+                    .try_into()
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?,
+            );
 
-            let pic_output_flag = if slice_segment_context.output_flag_present_flag {
-                Some(bit_reader.read_bit()?)
-            } else {
-                None
-            };
-            let colour_plane_id = if slice_segment_context.separate_colour_plane_flag {
-                Some(bit_reader.read::<u8>(2)?)
-            } else {
-                None
-            };
+            if slice_segment_context.output_flag_present_flag {
+                pic_output_flag = bit_reader.read_bit()?;
+            }
+            if slice_segment_context.separate_colour_plane_flag {
+                colour_plane_id = Some(bit_reader.read::<u8>(2)?);
+            }
 
             // 7.4.7.1  General slice segment header semantics:
             // > The variable CurrRpsIdx is derived as follows:
@@ -204,6 +542,7 @@ impl SliceSegmentHeader {
                         &mut bit_reader,
                         slice_segment_context.num_short_term_ref_pic_sets as usize,
                         slice_segment_context.num_short_term_ref_pic_sets as usize,
+                        &slice_segment_context.short_term_ref_pic_sets,
                         &mut bit_count,
                     )?);
 
@@ -217,9 +556,219 @@ impl SliceSegmentHeader {
                     short_term_ref_pic_set_idx = Some(value);
                     curr_rps_idx = value;
                 }
+
+                if slice_segment_context.long_term_ref_pics_present_flag {
+                    long_term_ref_pics = Some(LongTermRefPics::from_bit_reader(
+                        &mut bit_reader,
+                        slice_segment_context,
+                    )?);
+                }
+
+                if slice_segment_context.sps_temporal_mvp_enabled_flag {
+                    slice_temporal_mvp_enabled_flag = bit_reader.read_bit()?;
+                }
+
+                // (7-55)
+                let curr_rps = short_term_ref_pic_set
+                    .as_ref()
+                    .or_else(|| {
+                        slice_segment_context
+                            .short_term_ref_pic_sets
+                            .get(curr_rps_idx as usize)
+                    })
+                    .ok_or_else(|| {
+                        io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("No short-term RPS for CurrRpsIdx {}", curr_rps_idx),
+                        )
+                    })?;
+                let used_by_curr_pic_s0_flag = curr_rps.used_by_curr_pic_s0_flag();
+                let used_by_curr_pic_s1_flag = curr_rps.used_by_curr_pic_s1_flag();
+                num_pic_total_curr = used_by_curr_pic_s0_flag
+                    [..curr_rps.num_negative_pics() as usize]
+                    .iter()
+                    .chain(&used_by_curr_pic_s1_flag[..curr_rps.num_positive_pics() as usize])
+                    .filter(|&&used| used)
+                    .count() as u8;
+                if let Some(long_term_ref_pics) = long_term_ref_pics.as_ref() {
+                    num_pic_total_curr += long_term_ref_pics.used_by_curr_pic_lt
+                        [..long_term_ref_pics.num_long_term()]
+                        .iter()
+                        .filter(|&&used| used)
+                        .count() as u8;
+                }
+            }
+
+            if slice_segment_context.sample_adaptive_offset_enabled_flag {
+                slice_sao_luma_flag = bit_reader.read_bit()?;
+                if slice_segment_context.chroma_array_type != 0 {
+                    slice_sao_chroma_flag = bit_reader.read_bit()?;
+                }
+            }
+
+            if matches!(slice_type, SliceType::P | SliceType::B) {
+                let num_ref_idx_active_override_flag =
+                    *num_ref_idx_active_override_flag.insert(bit_reader.read_bit()?);
+                if num_ref_idx_active_override_flag {
+                    let read_num_ref_idx_active_minus1 =
+                        |bit_reader: &mut BitReader<_, BigEndian>, name: &str| {
+                            let value = read_exp_golomb_ue(bit_reader)?;
+                            u8::try_from(value)
+                                .ok()
+                                .filter(|&value| value <= 14)
+                                .ok_or_else(|| {
+                                    io::Error::new(
+                                        io::ErrorKind::InvalidData,
+                                        format!("{} out of range: {}", name, value),
+                                    )
+                                })
+                        };
+                    num_ref_idx_l0_active_minus1 = read_num_ref_idx_active_minus1(
+                        &mut bit_reader,
+                        "num_ref_idx_l0_active_minus1",
+                    )?;
+                    if slice_type == SliceType::B {
+                        num_ref_idx_l1_active_minus1 = read_num_ref_idx_active_minus1(
+                            &mut bit_reader,
+                            "num_ref_idx_l1_active_minus1",
+                        )?;
+                    }
+                }
+
+                // The values inferred from the PPS defaults need checking as well.
+                if num_ref_idx_l0_active_minus1 > 14 || num_ref_idx_l1_active_minus1 > 14 {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "num_ref_idx_l0_active_minus1 ({}) or num_ref_idx_l1_active_minus1 ({}) out of range",
+                            num_ref_idx_l0_active_minus1, num_ref_idx_l1_active_minus1
+                        ),
+                    ));
+                }
+
+                if slice_segment_context.lists_modification_present_flag && num_pic_total_curr > 1 {
+                    ref_pic_lists_modification = Some(RefPicListsModification::from_bit_reader(
+                        &mut bit_reader,
+                        slice_type,
+                        num_ref_idx_l0_active_minus1,
+                        num_ref_idx_l1_active_minus1,
+                        num_pic_total_curr,
+                    )?);
+                }
+
+                if slice_type == SliceType::B {
+                    mvd_l1_zero_flag = bit_reader.read_bit()?;
+                }
+                if slice_segment_context.cabac_init_present_flag {
+                    cabac_init_flag = bit_reader.read_bit()?;
+                }
+                if slice_temporal_mvp_enabled_flag {
+                    if slice_type == SliceType::B {
+                        collocated_from_l0_flag = bit_reader.read_bit()?;
+                    }
+                    if (collocated_from_l0_flag && num_ref_idx_l0_active_minus1 > 0)
+                        || (!collocated_from_l0_flag && num_ref_idx_l1_active_minus1 > 0)
+                    {
+                        collocated_ref_idx = read_exp_golomb_ue(&mut bit_reader)? as _;
+                    }
+                }
+
+                if (slice_segment_context.weighted_pred_flag && slice_type == SliceType::P)
+                    || (slice_segment_context.weighted_bipred_flag && slice_type == SliceType::B)
+                {
+                    pred_weight_table = Some(PredWeightTable::from_bit_reader(
+                        &mut bit_reader,
+                        slice_segment_context,
+                        slice_type,
+                        num_ref_idx_l0_active_minus1,
+                        num_ref_idx_l1_active_minus1,
+                    )?);
+                }
+
+                // MaxNumMergeCand shall be in the range of 1 to 5.
+                let value = read_exp_golomb_ue(&mut bit_reader)?;
+                if value > 4 {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("five_minus_max_num_merge_cand out of range: {}", value),
+                    ));
+                }
+                five_minus_max_num_merge_cand = Some(value as u8);
+            }
+
+            slice_qp_delta = read_exp_golomb_se(&mut bit_reader)? as _;
+            if slice_segment_context.pps_slice_chroma_qp_offsets_present_flag {
+                slice_cb_qp_offset = read_exp_golomb_se(&mut bit_reader)? as _;
+                slice_cr_qp_offset = read_exp_golomb_se(&mut bit_reader)? as _;
+            }
+            if slice_segment_context.chroma_qp_offset_list_enabled_flag {
+                cu_chroma_qp_offset_enabled_flag = bit_reader.read_bit()?;
+            }
+
+            if slice_segment_context.deblocking_filter_override_enabled_flag {
+                let deblocking_filter_override_flag =
+                    *deblocking_filter_override_flag.insert(bit_reader.read_bit()?);
+                if deblocking_filter_override_flag {
+                    slice_deblocking_filter_disabled_flag = bit_reader.read_bit()?;
+                    if !slice_deblocking_filter_disabled_flag {
+                        slice_beta_offset_div2 = read_exp_golomb_se(&mut bit_reader)? as _;
+                        slice_tc_offset_div2 = read_exp_golomb_se(&mut bit_reader)? as _;
+                    }
+                }
+            }
+
+            if slice_segment_context.pps_loop_filter_across_slices_enabled_flag
+                && (slice_sao_luma_flag
+                    || slice_sao_chroma_flag
+                    || !slice_deblocking_filter_disabled_flag)
+            {
+                slice_loop_filter_across_slices_enabled_flag = bit_reader.read_bit()?;
             }
         }
 
+        let mut num_entry_point_offsets: u32 = 0;
+        let mut offset_len_minus1: u8 = 0;
+        let mut entry_point_offset_minus1: Vec<u32> = Vec::new();
+        if slice_segment_context.tiles_enabled_flag
+            || slice_segment_context.entropy_coding_sync_enabled_flag
+        {
+            num_entry_point_offsets = read_exp_golomb_ue(&mut bit_reader)?;
+            if num_entry_point_offsets > 0 {
+                offset_len_minus1 = read_exp_golomb_ue(&mut bit_reader)? as _;
+                if offset_len_minus1 > 31 {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("offset_len_minus1 out of range: {}", offset_len_minus1),
+                    ));
+                }
+                for _ in 0..num_entry_point_offsets {
+                    // offset_len_minus1 + 1  bits.
+                    entry_point_offset_minus1
+                        .push(bit_reader.read::<u32>(offset_len_minus1 as u32 + 1)?);
+                }
+            }
+        }
+
+        let mut slice_segment_header_extension_data_byte: Vec<u8> = Vec::new();
+        if slice_segment_context.slice_segment_header_extension_present_flag {
+            let slice_segment_header_extension_length = read_exp_golomb_ue(&mut bit_reader)?;
+            for _ in 0..slice_segment_header_extension_length {
+                slice_segment_header_extension_data_byte.push(bit_reader.read::<u8>(8)?);
+            }
+        }
+
+        // `byte_alignment()`
+        let alignment_bit_equal_to_one = bit_reader.read_bit()?;
+        if !alignment_bit_equal_to_one {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "alignment_bit_equal_to_one expected in byte_alignment()",
+            ));
+        }
+        bit_reader.byte_align();
+
+        let slice_segment_data_offset = counting_reader.count() as u32;
+
         Ok(Self {
             nal_unit_type,
             first_slice_segment_in_pic_flag,
@@ -233,10 +782,349 @@ impl SliceSegmentHeader {
             slice_pic_order_cnt_lsb,
             short_term_ref_pic_set_idx,
             curr_rps_idx,
+            long_term_ref_pics,
+            slice_type,
+            pic_output_flag,
+            colour_plane_id,
+            slice_temporal_mvp_enabled_flag,
+            slice_sao_luma_flag,
+            slice_sao_chroma_flag,
+            num_ref_idx_active_override_flag,
+            num_ref_idx_l0_active_minus1,
+            num_ref_idx_l1_active_minus1,
+            ref_pic_lists_modification,
+            mvd_l1_zero_flag,
+            cabac_init_flag,
+            collocated_from_l0_flag,
+            collocated_ref_idx,
+            pred_weight_table,
+            five_minus_max_num_merge_cand,
+            slice_qp_delta,
+            slice_cb_qp_offset,
+            slice_cr_qp_offset,
+            cu_chroma_qp_offset_enabled_flag,
+            deblocking_filter_override_flag,
+            slice_deblocking_filter_disabled_flag,
+            slice_beta_offset_div2,
+            slice_tc_offset_div2,
+            slice_loop_filter_across_slices_enabled_flag,
+            num_pic_total_curr,
+            num_entry_point_offsets,
+            offset_len_minus1,
+            entry_point_offset_minus1,
+            slice_segment_header_extension_data_byte,
+            slice_segment_data_offset,
         })
     }
 }
 
+impl RefPicListsModification {
+    fn from_bit_reader<R: Read>(
+        bit_reader: &mut BitReader<R, BigEndian>,
+        slice_type: SliceType,
+        num_ref_idx_l0_active_minus1: u8,
+        num_ref_idx_l1_active_minus1: u8,
+        num_pic_total_curr: u8,
+    ) -> Result<Self, io::Error> {
+        // Ceil(Log2(NumPicTotalCurr)) bits.
+        let length_in_bits = (num_pic_total_curr as f64).log2().ceil() as u32;
+
+        let mut value = Self {
+            ref_pic_list_modification_flag_l0: bit_reader.read_bit()?,
+            list_entry_l0: [0; 16],
+            ref_pic_list_modification_flag_l1: false,
+            list_entry_l1: [0; 16],
+        };
+        if value.ref_pic_list_modification_flag_l0 {
+            for list_entry in &mut value.list_entry_l0[..=num_ref_idx_l0_active_minus1 as usize] {
+                *list_entry = bit_reader.read::<u8>(length_in_bits)?;
+            }
+        }
+
+        if slice_type == SliceType::B {
+            value.ref_pic_list_modification_flag_l1 = bit_reader.read_bit()?;
+            if value.ref_pic_list_modification_flag_l1 {
+                for list_entry in &mut value.list_entry_l1[..=num_ref_idx_l1_active_minus1 as usize]
+                {
+                    *list_entry = bit_reader.read::<u8>(length_in_bits)?;
+                }
+            }
+        }
+
+        Ok(value)
+    }
+}
+
+impl PredWeightTable {
+    fn from_bit_reader<R: Read>(
+        bit_reader: &mut BitReader<R, BigEndian>,
+        slice_segment_context: &SliceSegmentContext,
+        slice_type: SliceType,
+        num_ref_idx_l0_active_minus1: u8,
+        num_ref_idx_l1_active_minus1: u8,
+    ) -> Result<Self, io::Error> {
+        let luma_log2_weight_denom = read_exp_golomb_ue(bit_reader)?;
+        if luma_log2_weight_denom > 7 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "luma_log2_weight_denom out of range: {}",
+                    luma_log2_weight_denom
+                ),
+            ));
+        }
+        let luma_log2_weight_denom = luma_log2_weight_denom as u8;
+
+        let delta_chroma_log2_weight_denom = if slice_segment_context.chroma_array_type != 0 {
+            Some(read_exp_golomb_se(bit_reader)?)
+        } else {
+            None
+        };
+        let chroma_log2_weight_denom =
+            luma_log2_weight_denom as i32 + delta_chroma_log2_weight_denom.unwrap_or(0);
+        if !(0..=7).contains(&chroma_log2_weight_denom) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "ChromaLog2WeightDenom out of range: {}",
+                    chroma_log2_weight_denom
+                ),
+            ));
+        }
+        let chroma_log2_weight_denom = chroma_log2_weight_denom as u8;
+
+        let l0 = PredWeights::from_bit_reader(
+            bit_reader,
+            slice_segment_context,
+            num_ref_idx_l0_active_minus1,
+            luma_log2_weight_denom,
+            chroma_log2_weight_denom,
+        )?;
+        let l1 = if slice_type == SliceType::B {
+            Some(PredWeights::from_bit_reader(
+                bit_reader,
+                slice_segment_context,
+                num_ref_idx_l1_active_minus1,
+                luma_log2_weight_denom,
+                chroma_log2_weight_denom,
+            )?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            luma_log2_weight_denom,
+            delta_chroma_log2_weight_denom: delta_chroma_log2_weight_denom.map(|x| x as i8),
+            chroma_log2_weight_denom,
+            l0,
+            l1,
+        })
+    }
+}
+
+impl PredWeights {
+    fn from_bit_reader<R: Read>(
+        bit_reader: &mut BitReader<R, BigEndian>,
+        slice_segment_context: &SliceSegmentContext,
+        num_ref_idx_active_minus1: u8,
+        luma_log2_weight_denom: u8,
+        chroma_log2_weight_denom: u8,
+    ) -> Result<Self, io::Error> {
+        let num_ref_idx_active = num_ref_idx_active_minus1 as usize + 1;
+
+        // WpOffsetHalfRangeC
+        let wp_offset_half_range_c: i32 =
+            if slice_segment_context.high_precision_offsets_enabled_flag {
+                1 << (slice_segment_context.bit_depth_chroma_minus8 + 8 - 1)
+            } else {
+                1 << 7
+            };
+
+        let mut value = Self {
+            luma_weight_flag: [false; 16],
+            chroma_weight_flag: [false; 16],
+            delta_luma_weight: [0; 16],
+            luma_offset: [0; 16],
+            delta_chroma_weight: [[0; 2]; 16],
+            delta_chroma_offset: [[0; 2]; 16],
+            luma_weight: [1 << luma_log2_weight_denom; 16],
+            chroma_weight: [[1 << chroma_log2_weight_denom; 2]; 16],
+            chroma_offset: [[0; 2]; 16],
+        };
+
+        // The weight flags are present for every reference picture, as a reference picture cannot have the same POC
+        // and `nuh_layer_id` as the current picture in a single-layer non-SCC bitstream. With
+        // `pps_curr_pic_ref_enabled_flag` of SCC, the current picture itself may be in the reference picture lists
+        // without them, but `SliceSegmentContext::new()` rejects SCC streams.
+        for flag in &mut value.luma_weight_flag[..num_ref_idx_active] {
+            *flag = bit_reader.read_bit()?;
+        }
+        if slice_segment_context.chroma_array_type != 0 {
+            for flag in &mut value.chroma_weight_flag[..num_ref_idx_active] {
+                *flag = bit_reader.read_bit()?;
+            }
+        }
+
+        for i in 0..num_ref_idx_active {
+            if value.luma_weight_flag[i] {
+                value.delta_luma_weight[i] = read_exp_golomb_se(bit_reader)? as _;
+                value.luma_offset[i] = read_exp_golomb_se(bit_reader)?;
+
+                value.luma_weight[i] += value.delta_luma_weight[i] as i32;
+            }
+            if value.chroma_weight_flag[i] {
+                for j in 0..2 {
+                    value.delta_chroma_weight[i][j] = read_exp_golomb_se(bit_reader)? as _;
+                    value.delta_chroma_offset[i][j] = read_exp_golomb_se(bit_reader)?;
+
+                    value.chroma_weight[i][j] += value.delta_chroma_weight[i][j] as i32;
+                    value.chroma_offset[i][j] = (wp_offset_half_range_c
+                        - ((wp_offset_half_range_c * value.chroma_weight[i][j])
+                            >> chroma_log2_weight_denom)
+                        + value.delta_chroma_offset[i][j])
+                        .clamp(-wp_offset_half_range_c, wp_offset_half_range_c - 1);
+                }
+            }
+        }
+
+        Ok(value)
+    }
+}
+
+impl LongTermRefPics {
+    /// `num_long_term_sps + num_long_term_pics`.
+    pub fn num_long_term(&self) -> usize {
+        self.num_long_term_sps as usize + self.num_long_term_pics as usize
+    }
+
+    /// Derives `pocLt` of the i-th entry as specified in _8.3.2 Decoding process for reference picture set_ in the spec.
+    ///
+    /// When `delta_poc_msb_present_flag[i] == false`, the returned value is `PocLsbLt[i]`, which is to be matched against
+    /// `slice_pic_order_cnt_lsb` of the reference pictures rather than `PicOrderCntVal`.
+    ///
+    /// The arithmetic wraps around as `i32`, which a conforming bitstream never relies on.
+    ///
+    /// * `pic_order_cnt_val`: `PicOrderCntVal` of the current picture.
+    /// * `log2_max_pic_order_cnt_lsb_minus4`: From the SPS.
+    pub fn poc_lt(
+        &self,
+        i: usize,
+        pic_order_cnt_val: i32,
+        log2_max_pic_order_cnt_lsb_minus4: u8,
+    ) -> i32 {
+        let mut poc_lt = self.poc_lsb_lt[i] as i32;
+        if self.delta_poc_msb_present_flag[i] {
+            let max_pic_order_cnt_lsb: i32 = 1 << (log2_max_pic_order_cnt_lsb_minus4 + 4);
+            poc_lt = poc_lt
+                .wrapping_add(pic_order_cnt_val)
+                .wrapping_sub(
+                    (self.delta_poc_msb_cycle_lt_accumulated[i] as i32)
+                        .wrapping_mul(max_pic_order_cnt_lsb),
+                )
+                .wrapping_sub(pic_order_cnt_val & (max_pic_order_cnt_lsb - 1));
+        }
+        poc_lt
+    }
+
+    fn from_bit_reader<R: Read>(
+        bit_reader: &mut BitReader<R, BigEndian>,
+        slice_segment_context: &SliceSegmentContext,
+    ) -> Result<Self, io::Error> {
+        let num_long_term_ref_pics_sps = slice_segment_context.num_long_term_ref_pics_sps;
+
+        let num_long_term_sps: u32 = if num_long_term_ref_pics_sps > 0 {
+            read_exp_golomb_ue(bit_reader)?
+        } else {
+            0
+        };
+        let num_long_term_pics: u32 = read_exp_golomb_ue(bit_reader)?;
+        if num_long_term_sps > num_long_term_ref_pics_sps as u32
+            || num_long_term_sps as u64 + num_long_term_pics as u64 > 32
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "num_long_term_sps ({}) or num_long_term_pics ({}) out of range",
+                    num_long_term_sps, num_long_term_pics
+                ),
+            ));
+        }
+        // Both are at most 32 now.
+        let num_long_term_sps: u8 = num_long_term_sps.try_into().unwrap();
+        let num_long_term_pics: u8 = num_long_term_pics.try_into().unwrap();
+
+        let mut value = Self {
+            num_long_term_sps,
+            num_long_term_pics,
+            lt_idx_sps: [0; 32],
+            poc_lsb_lt: [0; 32],
+            used_by_curr_pic_lt: [false; 32],
+            delta_poc_msb_present_flag: [false; 32],
+            delta_poc_msb_cycle_lt: [0; 32],
+            delta_poc_msb_cycle_lt_accumulated: [0; 32],
+        };
+
+        for i in 0..value.num_long_term() {
+            if i < num_long_term_sps as usize {
+                if num_long_term_ref_pics_sps > 1 {
+                    // Ceil(Log2(num_long_term_ref_pics_sps)) bits.
+                    let length_in_bits = (num_long_term_ref_pics_sps as f64).log2().ceil() as u32;
+                    value.lt_idx_sps[i] = bit_reader.read::<u8>(length_in_bits)?;
+                    if value.lt_idx_sps[i] >= num_long_term_ref_pics_sps {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("lt_idx_sps[{}] out of range: {}", i, value.lt_idx_sps[i]),
+                        ));
+                    }
+                }
+                let lt_idx_sps = value.lt_idx_sps[i] as usize;
+                value.poc_lsb_lt[i] = slice_segment_context.lt_ref_pic_poc_lsb_sps[lt_idx_sps];
+                value.used_by_curr_pic_lt[i] =
+                    slice_segment_context.used_by_curr_pic_lt_sps_flag[lt_idx_sps];
+            } else {
+                // log2_max_pic_order_cnt_lsb_minus4 + 4  bits.
+                value.poc_lsb_lt[i] = bit_reader
+                    .read((slice_segment_context.log2_max_pic_order_cnt_lsb_minus4 + 4) as u32)?;
+                value.used_by_curr_pic_lt[i] = bit_reader.read_bit()?;
+            }
+
+            value.delta_poc_msb_present_flag[i] = bit_reader.read_bit()?;
+            if value.delta_poc_msb_present_flag[i] {
+                value.delta_poc_msb_cycle_lt[i] = read_exp_golomb_ue(bit_reader)?;
+            }
+
+            value.delta_poc_msb_cycle_lt_accumulated[i] =
+                if i == 0 || i == num_long_term_sps as usize {
+                    value.delta_poc_msb_cycle_lt[i]
+                } else {
+                    value.delta_poc_msb_cycle_lt[i]
+                        .checked_add(value.delta_poc_msb_cycle_lt_accumulated[i - 1])
+                        .ok_or_else(|| {
+                            io::Error::new(
+                                io::ErrorKind::InvalidData,
+                                format!("DeltaPocMsbCycleLt[{}] overflows", i),
+                            )
+                        })?
+                };
+            // DeltaPocMsbCycleLt[i] shall be in the range of 0 to 2^(32 − log2_max_pic_order_cnt_lsb_minus4 − 4).
+            let max_delta_poc_msb_cycle_lt: u64 = 1
+                << 28u32
+                    .saturating_sub(slice_segment_context.log2_max_pic_order_cnt_lsb_minus4 as u32);
+            if value.delta_poc_msb_cycle_lt_accumulated[i] as u64 > max_delta_poc_msb_cycle_lt {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "DeltaPocMsbCycleLt[{}] out of range: {}",
+                        i, value.delta_poc_msb_cycle_lt_accumulated[i]
+                    ),
+                ));
+            }
+        }
+
+        Ok(value)
+    }
+}
+
 impl SliceSegmentLayer {
     /// Reads from _RBSP(Raw Byte Sequence Payload)_.
     ///
@@ -245,22 +1133,371 @@ impl SliceSegmentLayer {
         reader: &mut R,
         value_length: usize,
         nal_unit_type: NaluType,
-        slice_segment_context: SliceSegmentContext,
+        slice_segment_context: &SliceSegmentContext,
     ) -> Result<Self, io::Error> {
-        // Consume `value_length` bytes here, as `SliceSegmentHeader::from_reader` currently does not consume the whole bytes for the slice segment header.
-        let bytes = {
-            let mut bytes = vec![0; value_length];
-            reader.read_exact(&mut bytes)?;
-            bytes
-        };
-
-        let mut reader = io::Cursor::new(bytes);
+        // Bound the header to `value_length` bytes, and skip the rest as `slice_segment_data()` is not parsed.
+        let mut reader = CountingReader::new(reader.take(value_length as u64));
 
         let header = SliceSegmentHeader::from_rbsp_reader(
             &mut reader,
             nal_unit_type,
             slice_segment_context,
         )?;
+
+        let remaining = (value_length - reader.count()) as u64;
+        if io::copy(&mut reader.into_inner(), &mut io::sink())? < remaining {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "Slice segment layer shorter than value_length",
+            ));
+        }
         Ok(Self { header })
     }
 }
+
+/// A `SliceSegmentContext` of a 1080p Main profile stream, for building slice segment headers in tests.
+#[cfg(test)]
+pub(crate) fn test_slice_segment_context() -> SliceSegmentContext {
+    SliceSegmentContext {
+        dependent_slice_segments_enabled_flag: false,
+        pic_width_in_luma_samples: 1920,
+        pic_height_in_luma_samples: 1080,
+        log2_min_luma_coding_block_size_minus3: 0,
+        log2_diff_max_min_luma_coding_block_size: 3,
+        num_extra_slice_header_bits: 0,
+        output_flag_present_flag: false,
+        separate_colour_plane_flag: false,
+        log2_max_pic_order_cnt_lsb_minus4: 4,
+        num_short_term_ref_pic_sets: 0,
+        short_term_ref_pic_sets: Vec::new(),
+        long_term_ref_pics_present_flag: false,
+        num_long_term_ref_pics_sps: 0,
+        lt_ref_pic_poc_lsb_sps: [0; 32],
+        used_by_curr_pic_lt_sps_flag: [false; 32],
+        sps_temporal_mvp_enabled_flag: true,
+        sample_adaptive_offset_enabled_flag: true,
+        chroma_array_type: 1,
+        bit_depth_chroma_minus8: 0,
+        high_precision_offsets_enabled_flag: false,
+        lists_modification_present_flag: false,
+        cabac_init_present_flag: false,
+        num_ref_idx_l0_default_active_minus1: 0,
+        num_ref_idx_l1_default_active_minus1: 0,
+        weighted_pred_flag: false,
+        weighted_bipred_flag: false,
+        pps_slice_chroma_qp_offsets_present_flag: false,
+        chroma_qp_offset_list_enabled_flag: false,
+        deblocking_filter_override_enabled_flag: false,
+        pps_deblocking_filter_disabled_flag: false,
+        pps_beta_offset_div2: 0,
+        pps_tc_offset_div2: 0,
+        pps_loop_filter_across_slices_enabled_flag: true,
+        tiles_enabled_flag: false,
+        entropy_coding_sync_enabled_flag: true,
+        slice_segment_header_extension_present_flag: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use bitstream_io::{BitWrite, BitWriter};
+
+    use crate::base::{test_sps_rbsp, write_exp_golomb_ue};
+
+    #[test]
+    fn slice_segment_context_works() {
+        let mut sps =
+            SequenceParameterSet::from_rbsp_reader(&mut test_sps_rbsp(0, false, None).as_slice())
+                .unwrap();
+
+        // A PPS with nothing enabled but `pps_scc_extension_flag`.
+        let mut bytes: Vec<u8> = Vec::new();
+        {
+            let mut bit_writer = BitWriter::endian(&mut bytes, BigEndian);
+            for bit in [
+                1, 1, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 0, 0, 0, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1,
+                0, 1, 0, 0, 0, 1, 0, 0, 0, 0, 1,
+            ] {
+                bit_writer.write_bit(bit == 1).unwrap();
+            }
+            bit_writer.byte_align().unwrap();
+        }
+        let mut pps = PictureParameterSet::from_rbsp_reader(&mut bytes.as_slice(), 1).unwrap();
+        assert!(pps.pps_scc_extension_flag);
+
+        let error = SliceSegmentContext::new(&sps, &pps).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::Unsupported);
+
+        pps.pps_scc_extension_flag = false;
+        let slice_segment_context = SliceSegmentContext::new(&sps, &pps).unwrap();
+        assert_eq!(slice_segment_context.pic_width_in_luma_samples, 1920);
+        assert!(slice_segment_context.sample_adaptive_offset_enabled_flag);
+
+        sps.sps_scc_extension_flag = true;
+        assert!(SliceSegmentContext::new(&sps, &pps).is_err());
+    }
+
+    #[test]
+    fn slice_segment_header_through_byte_alignment_works() {
+        let slice_segment_context = test_slice_segment_context();
+
+        let mut bytes: Vec<u8> = Vec::new();
+        {
+            let mut bit_writer = BitWriter::endian(&mut bytes, BigEndian);
+            // first_slice_segment_in_pic_flag = 1, no_output_of_prior_pics_flag = 0, slice_pic_parameter_set_id = 0,
+            // slice_type = I, slice_sao_luma_flag = 1, slice_sao_chroma_flag = 0, slice_qp_delta = -3,
+            // slice_loop_filter_across_slices_enabled_flag = 0, num_entry_point_offsets = 2, offset_len_minus1 = 7.
+            for bit in [
+                1, 0, 1, 0, 1, 1, 1, 0, 0, 0, 1, 1, 1, 0, 0, 1, 1, 0, 0, 0, 1, 0, 0, 0,
+            ] {
+                bit_writer.write_bit(bit == 1).unwrap();
+            }
+            // entry_point_offset_minus1[0..2]
+            bit_writer.write::<u8>(8, 100).unwrap();
+            bit_writer.write::<u8>(8, 200).unwrap();
+            // byte_alignment()
+            bit_writer.write_bit(true).unwrap();
+            bit_writer.byte_align().unwrap();
+            // slice_segment_data()
+            bit_writer.write::<u8>(8, 0xAB).unwrap();
+        }
+
+        let mut reader = io::Cursor::new(bytes);
+        let header = SliceSegmentHeader::from_rbsp_reader(
+            &mut reader,
+            NaluType::IdrWRadl,
+            &slice_segment_context,
+        )
+        .unwrap();
+
+        assert_eq!(header.slice_type, Some(SliceType::I));
+        assert!(header.pic_output_flag);
+        assert!(header.slice_sao_luma_flag);
+        assert!(!header.slice_sao_chroma_flag);
+        assert_eq!(header.slice_qp_delta, -3);
+        assert!(!header.slice_loop_filter_across_slices_enabled_flag);
+        assert_eq!(header.num_entry_point_offsets, 2);
+        assert_eq!(header.offset_len_minus1, 7);
+        assert_eq!(header.entry_point_offset_minus1, [100, 200]);
+        assert_eq!(header.slice_segment_data_offset, 6);
+        assert_eq!(reader.position(), 6);
+    }
+
+    #[test]
+    fn pred_weight_table_works() {
+        let slice_segment_context = test_slice_segment_context();
+
+        let mut bytes: Vec<u8> = Vec::new();
+        {
+            let mut bit_writer = BitWriter::endian(&mut bytes, BigEndian);
+            // luma_log2_weight_denom = 6, delta_chroma_log2_weight_denom = 0,
+            // luma_weight_l0_flag[0..2] = [1, 0], chroma_weight_l0_flag[0..2] = [1, 0],
+            // delta_luma_weight_l0[0] = -1, luma_offset_l0[0] = 2,
+            // delta_chroma_weight_l0[0][0] = 1, delta_chroma_offset_l0[0][0] = -1,
+            // delta_chroma_weight_l0[0][1] = 0, delta_chroma_offset_l0[0][1] = 0.
+            for bit in [
+                0, 0, 1, 1, 1, 1, 1, 0, 1, 0, 0, 1, 1, 0, 0, 1, 0, 0, 0, 1, 0, 0, 1, 1, 1, 1,
+            ] {
+                bit_writer.write_bit(bit == 1).unwrap();
+            }
+            bit_writer.byte_align().unwrap();
+        }
+
+        let mut reader = io::Cursor::new(bytes);
+        let mut bit_reader = BitReader::endian(&mut reader, BigEndian);
+        let pred_weight_table = PredWeightTable::from_bit_reader(
+            &mut bit_reader,
+            &slice_segment_context,
+            SliceType::P,
+            1,
+            0,
+        )
+        .unwrap();
+
+        assert_eq!(pred_weight_table.luma_log2_weight_denom, 6);
+        assert_eq!(pred_weight_table.chroma_log2_weight_denom, 6);
+        assert!(pred_weight_table.l1.is_none());
+
+        let l0 = &pred_weight_table.l0;
+        assert_eq!(l0.luma_weight[..2], [63, 64]);
+        assert_eq!(l0.luma_offset[..2], [2, 0]);
+        assert_eq!(l0.chroma_weight[..2], [[65, 64], [64, 64]]);
+        // 128 - ((128 * 65) >> 6) - 1 = -3
+        assert_eq!(l0.chroma_offset[..2], [[-3, 0], [0, 0]]);
+    }
+
+    #[test]
+    fn max_num_merge_cand_works() {
+        let slice_segment_context = test_slice_segment_context();
+
+        for five_minus_max_num_merge_cand in [3, 5] {
+            let mut bytes: Vec<u8> = Vec::new();
+            {
+                let mut bit_writer = BitWriter::endian(&mut bytes, BigEndian);
+                // first_slice_segment_in_pic_flag = 1, slice_pic_parameter_set_id = 0, slice_type = P,
+                // slice_pic_order_cnt_lsb = 1, short_term_ref_pic_set_sps_flag = 0
+                bit_writer.write_bit(true).unwrap();
+                write_exp_golomb_ue(&mut bit_writer, 0);
+                write_exp_golomb_ue(&mut bit_writer, 1);
+                bit_writer.write(8, 1u8).unwrap();
+                bit_writer.write_bit(false).unwrap();
+                // st_ref_pic_set(0): num_negative_pics = 1, num_positive_pics = 0, delta_poc_s0_minus1[0] = 0,
+                // used_by_curr_pic_s0_flag[0] = 1
+                for value in [1, 0, 0] {
+                    write_exp_golomb_ue(&mut bit_writer, value);
+                }
+                bit_writer.write_bit(true).unwrap();
+                // slice_temporal_mvp_enabled_flag = 1, slice_sao_luma_flag = 0, slice_sao_chroma_flag = 0,
+                // num_ref_idx_active_override_flag = 0
+                bit_writer.write(4, 0b1000u8).unwrap();
+                write_exp_golomb_ue(&mut bit_writer, five_minus_max_num_merge_cand);
+                // slice_qp_delta = 0, slice_loop_filter_across_slices_enabled_flag = 1, num_entry_point_offsets = 0
+                bit_writer.write(3, 0b111u8).unwrap();
+                // byte_alignment()
+                bit_writer.write_bit(true).unwrap();
+                bit_writer.byte_align().unwrap();
+            }
+
+            let result = SliceSegmentHeader::from_rbsp_reader(
+                &mut io::Cursor::new(bytes),
+                NaluType::TrailR,
+                &slice_segment_context,
+            );
+            if five_minus_max_num_merge_cand == 3 {
+                let header = result.unwrap();
+                assert_eq!(header.slice_type, Some(SliceType::P));
+                assert_eq!(header.max_num_merge_cand(), Some(2));
+                assert!(header.slice_loop_filter_across_slices_enabled_flag);
+            } else {
+                assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidData);
+            }
+        }
+    }
+
+    #[test]
+    fn deblocking_filter_override_works() {
+        let slice_segment_context = SliceSegmentContext {
+            deblocking_filter_override_enabled_flag: true,
+            pps_deblocking_filter_disabled_flag: true,
+            ..test_slice_segment_context()
+        };
+
+        let mut bytes: Vec<u8> = Vec::new();
+        {
+            let mut bit_writer = BitWriter::endian(&mut bytes, BigEndian);
+            // first_slice_segment_in_pic_flag = 1, no_output_of_prior_pics_flag = 0, slice_pic_parameter_set_id = 0,
+            // slice_type = I, slice_sao_luma_flag = 0, slice_sao_chroma_flag = 0, slice_qp_delta = 0,
+            // deblocking_filter_override_flag = 1, slice_deblocking_filter_disabled_flag = 0,
+            // slice_beta_offset_div2 = -2, slice_tc_offset_div2 = 1,
+            // slice_loop_filter_across_slices_enabled_flag = 1, num_entry_point_offsets = 0.
+            for bit in [
+                1, 0, 1, 0, 1, 1, 0, 0, 1, 1, 0, 0, 0, 1, 0, 1, 0, 1, 0, 1, 1,
+            ] {
+                bit_writer.write_bit(bit == 1).unwrap();
+            }
+            // byte_alignment()
+            bit_writer.write_bit(true).unwrap();
+            bit_writer.byte_align().unwrap();
+        }
+
+        let mut reader = io::Cursor::new(bytes);
+        let header = SliceSegmentHeader::from_rbsp_reader(
+            &mut reader,
+            NaluType::IdrWRadl,
+            &slice_segment_context,
+        )
+        .unwrap();
+
+        assert_eq!(header.deblocking_filter_override_flag, Some(true));
+        assert!(!header.slice_deblocking_filter_disabled_flag);
+        assert_eq!(header.slice_beta_offset_div2, -2);
+        assert_eq!(header.slice_tc_offset_div2, 1);
+        assert_eq!(header.deblocking_filter_offsets(), Some((-4, 2)));
+        assert!(header.slice_loop_filter_across_slices_enabled_flag);
+        assert_eq!(header.slice_segment_data_offset, 3);
+    }
+
+    #[test]
+    fn long_term_ref_pics_works() {
+        let mut slice_segment_context = test_slice_segment_context();
+        slice_segment_context.long_term_ref_pics_present_flag = true;
+        slice_segment_context.num_long_term_ref_pics_sps = 2;
+        slice_segment_context.lt_ref_pic_poc_lsb_sps[..2].copy_from_slice(&[10, 20]);
+        slice_segment_context.used_by_curr_pic_lt_sps_flag[..2].copy_from_slice(&[true, false]);
+
+        let mut bytes: Vec<u8> = Vec::new();
+        {
+            let mut bit_writer = BitWriter::endian(&mut bytes, BigEndian);
+            // num_long_term_sps = 1, num_long_term_pics = 2
+            write_exp_golomb_ue(&mut bit_writer, 1);
+            write_exp_golomb_ue(&mut bit_writer, 2);
+            // lt_idx_sps[0] = 1, delta_poc_msb_present_flag[0] = 1, delta_poc_msb_cycle_lt[0] = 2
+            bit_writer.write(2, 0b11u8).unwrap();
+            write_exp_golomb_ue(&mut bit_writer, 2);
+            // poc_lsb_lt[1] = 55, used_by_curr_pic_lt_flag[1] = 1, delta_poc_msb_present_flag[1] = 1,
+            // delta_poc_msb_cycle_lt[1] = 3
+            bit_writer.write(8, 55u8).unwrap();
+            bit_writer.write(2, 0b11u8).unwrap();
+            write_exp_golomb_ue(&mut bit_writer, 3);
+            // poc_lsb_lt[2] = 66, used_by_curr_pic_lt_flag[2] = 0, delta_poc_msb_present_flag[2] = 1,
+            // delta_poc_msb_cycle_lt[2] = 1
+            bit_writer.write(8, 66u8).unwrap();
+            bit_writer.write(2, 0b01u8).unwrap();
+            write_exp_golomb_ue(&mut bit_writer, 1);
+            bit_writer.byte_align().unwrap();
+        }
+
+        let mut bit_reader = BitReader::endian(io::Cursor::new(bytes), BigEndian);
+        let long_term_ref_pics =
+            LongTermRefPics::from_bit_reader(&mut bit_reader, &slice_segment_context).unwrap();
+
+        assert_eq!(long_term_ref_pics.num_long_term(), 3);
+        // PocLsbLt and UsedByCurrPicLt
+        assert_eq!(long_term_ref_pics.poc_lsb_lt[..3], [20, 55, 66]);
+        assert_eq!(
+            long_term_ref_pics.used_by_curr_pic_lt[..3],
+            [false, true, false]
+        );
+        // DeltaPocMsbCycleLt restarts at `num_long_term_sps`.
+        assert_eq!(
+            long_term_ref_pics.delta_poc_msb_cycle_lt_accumulated[..3],
+            [2, 3, 4]
+        );
+
+        // 288 would wrap around to 32 as a `u8`.
+        let mut bytes: Vec<u8> = Vec::new();
+        {
+            let mut bit_writer = BitWriter::endian(&mut bytes, BigEndian);
+            write_exp_golomb_ue(&mut bit_writer, 0);
+            write_exp_golomb_ue(&mut bit_writer, 288);
+            bit_writer.byte_align().unwrap();
+        }
+        let mut bit_reader = BitReader::endian(io::Cursor::new(bytes), BigEndian);
+        assert!(LongTermRefPics::from_bit_reader(&mut bit_reader, &slice_segment_context).is_err());
+
+        // lt_idx_sps[0] = 2 with 3 candidates in the SPS, and delta_poc_msb_cycle_lt[0] exceeding 2^24 with
+        // log2_max_pic_order_cnt_lsb_minus4 = 4.
+        slice_segment_context.num_long_term_ref_pics_sps = 3;
+        for (lt_idx_sps, delta_poc_msb_cycle_lt, is_ok) in
+            [(2, 1 << 24, true), (3, 0, false), (0, (1 << 24) + 1, false)]
+        {
+            let mut bytes: Vec<u8> = Vec::new();
+            {
+                let mut bit_writer = BitWriter::endian(&mut bytes, BigEndian);
+                // num_long_term_sps = 1, num_long_term_pics = 0
+                write_exp_golomb_ue(&mut bit_writer, 1);
+                write_exp_golomb_ue(&mut bit_writer, 0);
+                // lt_idx_sps[0], delta_poc_msb_present_flag[0] = 1, delta_poc_msb_cycle_lt[0]
+                bit_writer.write(2, lt_idx_sps as u8).unwrap();
+                bit_writer.write_bit(true).unwrap();
+                write_exp_golomb_ue(&mut bit_writer, delta_poc_msb_cycle_lt);
+                bit_writer.byte_align().unwrap();
+            }
+            let mut bit_reader = BitReader::endian(io::Cursor::new(bytes), BigEndian);
+            let result = LongTermRefPics::from_bit_reader(&mut bit_reader, &slice_segment_context);
+            assert_eq!(result.is_ok(), is_ok);
+        }
+    }
+}
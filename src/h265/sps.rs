@@ -4,8 +4,10 @@ use bitstream_io::BitRead as _;
 use bitstream_io::{BigEndian, BitReader};
 
 use crate::base::read_exp_golomb_ue;
+use crate::h265::hrd::HrdParameters;
 use crate::h265::ptl::{ProfileTierLevel, SubLayerOrderingInfo};
 use crate::h265::rps::ShortTermReferencePictureSet;
+use crate::h265::scaling_list::ScalingListData;
 
 /// See _7.3.2.2 Sequence parameter set RBSP syntax_ in the spec.
 #[derive(Debug, Clone)]
@@ -34,18 +36,79 @@ pub struct SequenceParameterSet {
     pub max_transform_hierarchy_depth_inter: u8,
     pub max_transform_hierarchy_depth_intra: u8,
     pub scaling_list_enabled_flag: bool,
+    /// `Some` means `sps_scaling_list_data_present_flag == true`.
+    ///
+    /// When `scaling_list_enabled_flag == true` and this is `None`, the default scaling list data are used.
+    /// See `ScalingListData::select()`.
+    pub sps_scaling_list_data: Option<ScalingListData>,
     pub amp_enabled_flag: bool,
     pub sample_adaptive_offset_enabled_flag: bool,
     pub pcm_enabled_flag: bool,
-    pub pcm_loop_filter_disabled_flag: bool,
+    /// `Some` means `pcm_enabled_flag == true`.
+    pub pcm: Option<PcmParameters>,
     /// Specifies short-term reference picture sets.
     pub short_term_ref_pic_sets: Vec<ShortTermReferencePictureSet>,
     /// `true` specifies that long-term reference pictures may be used for inter prediction of one or more coded pictures in the CVS.
     pub long_term_ref_pics_present_flag: bool,
+    /// Specifies the number of candidate long-term reference pictures that are specified in the SPS. `[0, 32]`.
     pub num_long_term_ref_pics_sps: u8,
+    /// Specifies the picture order count modulo `MaxPicOrderCntLsb` of the i-th candidate long-term reference picture specified in the SPS.
+    pub lt_ref_pic_poc_lsb_sps: [u16; 32],
+    /// `false` specifies that the i-th candidate long-term reference picture specified in the SPS is not used for reference by a picture that includes it in its long-term RPS.
+    pub used_by_curr_pic_lt_sps_flag: [bool; 32],
     pub sps_temporal_mvp_enabled_flag: bool,
     pub strong_intra_smoothing_enabled_flag: bool,
     pub vui: Option<Vui>,
+    pub sps_extension_present_flag: bool,
+    /// `Some` means `sps_range_extension_flag == true`.
+    pub sps_range_extension: Option<SpsRangeExtension>,
+    /// `sps_multilayer_extension()` is not parsed, as it only applies to layers other than the base layer.
+    pub sps_multilayer_extension_flag: bool,
+    /// `sps_3d_extension()` is not parsed, as it only applies to layers other than the base layer.
+    pub sps_3d_extension_flag: bool,
+    /// `sps_scc_extension()` is not parsed. `SliceSegmentContext::new()` rejects SCC streams, whose slice segment headers
+    /// are not supported.
+    pub sps_scc_extension_flag: bool,
+    /// `sps_extension_data_flag`s following are ignored.
+    pub sps_extension_4bits: u8,
+}
+
+/// See `sps_range_extension()` in _7.3.2.2.2 Sequence parameter set range extension syntax_ in the spec.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SpsRangeExtension {
+    /// Specifies that a rotation is applied to the residual data block for intra 4x4 blocks coded using a transform skip operation.
+    pub transform_skip_rotation_enabled_flag: bool,
+    /// Specifies that a particular context is used for the parsing of the `sig_coeff_flag` for transform blocks with a skipped transform.
+    pub transform_skip_context_enabled_flag: bool,
+    /// Specifies that the residual modification process for blocks using a transform bypass may be used for intra blocks in the CVS.
+    pub implicit_rdpcm_enabled_flag: bool,
+    /// Specifies that the residual modification process for blocks using a transform bypass may be used for inter blocks in the CVS.
+    pub explicit_rdpcm_enabled_flag: bool,
+    /// Specifies that an extended dynamic range is used for coefficient parsing and inverse transform processing.
+    pub extended_precision_processing_flag: bool,
+    /// Specifies that the filtering process of neighbouring samples is unconditionally disabled for intra prediction.
+    pub intra_smoothing_disabled_flag: bool,
+    /// Specifies that weighted prediction offset values are signalled using a bit-depth-dependent precision.
+    pub high_precision_offsets_enabled_flag: bool,
+    /// Specifies that the Rice parameter derivation for the binarization of `coeff_abs_level_remaining` is initialized at the start of each sub-block.
+    pub persistent_rice_adaptation_enabled_flag: bool,
+    /// Specifies that an alignment process is used prior to bypass decoding of `coeff_sign_flag` and `coeff_abs_level_remaining`.
+    pub cabac_bypass_alignment_enabled_flag: bool,
+}
+
+/// The PCM sample parameters, present when `pcm_enabled_flag == true`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PcmParameters {
+    /// `PcmBitDepthY = pcm_sample_bit_depth_luma_minus1 + 1`. Shall be less than or equal to `BitDepthY`.
+    pub pcm_sample_bit_depth_luma_minus1: u8,
+    /// `PcmBitDepthC = pcm_sample_bit_depth_chroma_minus1 + 1`. Shall be less than or equal to `BitDepthC`.
+    pub pcm_sample_bit_depth_chroma_minus1: u8,
+    /// `Log2MinIpcmCbSizeY = log2_min_pcm_luma_coding_block_size_minus3 + 3`.
+    pub log2_min_pcm_luma_coding_block_size_minus3: u8,
+    /// `Log2MaxIpcmCbSizeY = Log2MinIpcmCbSizeY + log2_diff_max_min_pcm_luma_coding_block_size`.
+    pub log2_diff_max_min_pcm_luma_coding_block_size: u8,
+    /// Specifies whether the in-loop filter processes are disabled on reconstructed samples in a coding unit with `pcm_flag == true`.
+    pub pcm_loop_filter_disabled_flag: bool,
 }
 
 #[derive(Debug, Clone, Copy, Default)]
@@ -57,12 +120,16 @@ pub struct ConformanceWindow {
 }
 
 /// See _Annex E.2.1 VUI parameters syntax_ in the spec.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct Vui {
     pub aspect_ratio_info_present_flag: bool,
     pub aspect_ratio_idc: u8,
     pub sar_width: u16,
     pub sar_height: u16,
+    /// Indicates whether the cropped decoded pictures output are suitable for display using overscan.
+    ///
+    /// `Some` means `overscan_info_present_flag == true`.
+    pub overscan_appropriate_flag: Option<bool>,
     /// `Some` means `video_signal_type_present_flag == true`.
     pub video_signal_type: Option<VideoSignalType>,
     /// `Some` means `chroma_loc_info_present_flag == true`.
@@ -129,7 +196,7 @@ pub struct DefaultDisplayWindow {
     pub def_disp_win_bottom_offset: u16,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct VuiTimingInfo {
     /// When not present, inferred to be equal to `vps_num_units_in_tick` of the VPS referred to by the SPS.
     pub vui_num_units_in_tick: u32,
@@ -137,6 +204,8 @@ pub struct VuiTimingInfo {
     pub vui_time_scale: u32,
     /// `Some` means `vui_poc_proportional_to_timing_flag == true`.
     pub vui_num_ticks_poc_diff_one_minus1: Option<u32>,
+    /// `Some` means `vui_hrd_parameters_present_flag == true`.
+    pub vui_hrd_parameters: Option<HrdParameters>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -167,6 +236,45 @@ impl Default for BitstreamRestriction {
 }
 
 impl SequenceParameterSet {
+    /// `ChromaArrayType`: `0` when `separate_colour_plane_flag == true`, otherwise `chroma_format_idc`.
+    pub fn chroma_array_type(&self) -> u8 {
+        if self.separate_colour_plane_flag.unwrap_or(false) {
+            0
+        } else {
+            self.chroma_format_idc
+        }
+    }
+
+    /// `pcm_loop_filter_disabled_flag` of `pcm`, or `false` when `pcm_enabled_flag == false`.
+    pub fn pcm_loop_filter_disabled_flag(&self) -> bool {
+        self.pcm
+            .is_some_and(|pcm| pcm.pcm_loop_filter_disabled_flag)
+    }
+
+    /// `CtbLog2SizeY = MinCbLog2SizeY + log2_diff_max_min_luma_coding_block_size`.
+    pub fn ctb_log2_size_y(&self) -> u8 {
+        self.log2_min_luma_coding_block_size_minus3
+            + 3
+            + self.log2_diff_max_min_luma_coding_block_size
+    }
+
+    /// `PicWidthInCtbsY = Ceil(pic_width_in_luma_samples ÷ CtbSizeY)`.
+    pub fn pic_width_in_ctbs_y(&self) -> u32 {
+        self.pic_width_in_luma_samples
+            .div_ceil(1 << self.ctb_log2_size_y())
+    }
+
+    /// `PicHeightInCtbsY = Ceil(pic_height_in_luma_samples ÷ CtbSizeY)`.
+    pub fn pic_height_in_ctbs_y(&self) -> u32 {
+        self.pic_height_in_luma_samples
+            .div_ceil(1 << self.ctb_log2_size_y())
+    }
+
+    /// `PicSizeInCtbsY = PicWidthInCtbsY * PicHeightInCtbsY`.
+    pub fn pic_size_in_ctbs_y(&self) -> u32 {
+        self.pic_width_in_ctbs_y() * self.pic_height_in_ctbs_y()
+    }
+
     pub fn from_rbsp_reader<R: Read>(reader: &mut R) -> Result<Self, io::Error> {
         // See `seq_parameter_set_rbsp` in _7.3.2.2 General sequence parameter set RBSP syntax_.
         let mut bit_reader = BitReader::endian(reader, BigEndian);
@@ -208,7 +316,17 @@ impl SequenceParameterSet {
         };
         let bit_depth_luma_minus8: u8 = read_exp_golomb_ue(&mut bit_reader)? as _;
         let bit_depth_chroma_minus8: u8 = read_exp_golomb_ue(&mut bit_reader)? as _;
-        let log2_max_pic_order_cnt_lsb_minus4: u8 = read_exp_golomb_ue(&mut bit_reader)? as _;
+        let log2_max_pic_order_cnt_lsb_minus4 = read_exp_golomb_ue(&mut bit_reader)?;
+        if log2_max_pic_order_cnt_lsb_minus4 > 12 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "log2_max_pic_order_cnt_lsb_minus4 out of range: {}",
+                    log2_max_pic_order_cnt_lsb_minus4
+                ),
+            ));
+        }
+        let log2_max_pic_order_cnt_lsb_minus4 = log2_max_pic_order_cnt_lsb_minus4 as u8;
 
         let sps_sub_layer_ordering_info_present_flag = bit_reader.read_bit()?;
         let sub_layer_ordering_info = if sps_sub_layer_ordering_info_present_flag {
@@ -241,17 +359,33 @@ impl SequenceParameterSet {
         let max_transform_hierarchy_depth_intra: u8 = read_exp_golomb_ue(&mut bit_reader)? as _;
 
         let scaling_list_enabled_flag = bit_reader.read_bit()?;
-        if scaling_list_enabled_flag {
-            todo!("scaling_list_enabled_flag == true not supported");
-        }
+        let sps_scaling_list_data = if scaling_list_enabled_flag {
+            let sps_scaling_list_data_present_flag = bit_reader.read_bit()?;
+            if sps_scaling_list_data_present_flag {
+                Some(ScalingListData::from_bit_reader(&mut bit_reader)?)
+            } else {
+                None
+            }
+        } else {
+            None
+        };
 
         let amp_enabled_flag = bit_reader.read_bit()?;
         let sample_adaptive_offset_enabled_flag = bit_reader.read_bit()?;
         let pcm_enabled_flag = bit_reader.read_bit()?;
-        if pcm_enabled_flag {
-            todo!("pcm_enabled_flag == true not supported");
-        }
-        let pcm_loop_filter_disabled_flag = false;
+        let pcm = if pcm_enabled_flag {
+            Some(PcmParameters {
+                pcm_sample_bit_depth_luma_minus1: bit_reader.read(4)?,
+                pcm_sample_bit_depth_chroma_minus1: bit_reader.read(4)?,
+                log2_min_pcm_luma_coding_block_size_minus3: read_exp_golomb_ue(&mut bit_reader)?
+                    as _,
+                log2_diff_max_min_pcm_luma_coding_block_size: read_exp_golomb_ue(&mut bit_reader)?
+                    as _,
+                pcm_loop_filter_disabled_flag: bit_reader.read_bit()?,
+            })
+        } else {
+            None
+        };
 
         let short_term_ref_pic_sets = {
             let num_short_term_ref_pic_sets = read_exp_golomb_ue(&mut bit_reader)?;
@@ -264,6 +398,7 @@ impl SequenceParameterSet {
                     &mut bit_reader,
                     st_rps_index as usize,
                     num_short_term_ref_pic_sets as usize,
+                    &short_term_ref_pic_sets,
                     &mut bit_count,
                 )?;
                 short_term_ref_pic_sets.push(st_rps);
@@ -272,26 +407,79 @@ impl SequenceParameterSet {
         };
 
         let long_term_ref_pics_present_flag = bit_reader.read_bit()?;
-        let num_long_term_ref_pics_sps = if long_term_ref_pics_present_flag {
-            let num_long_term_ref_pics_sps: u8 = read_exp_golomb_ue(&mut bit_reader)? as _;
-
-            todo!("long_term_ref_pics_present_flag == true not supported");
-            num_long_term_ref_pics_sps
-        } else {
-            0
-        };
+        let mut num_long_term_ref_pics_sps: u8 = 0;
+        let mut lt_ref_pic_poc_lsb_sps = [0u16; 32];
+        let mut used_by_curr_pic_lt_sps_flag = [false; 32];
+        if long_term_ref_pics_present_flag {
+            let value = read_exp_golomb_ue(&mut bit_reader)?;
+            num_long_term_ref_pics_sps = value
+                .try_into()
+                .ok()
+                .filter(|&value| value <= 32)
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("num_long_term_ref_pics_sps out of range: {}", value),
+                    )
+                })?;
+
+            for i in 0..num_long_term_ref_pics_sps as usize {
+                // log2_max_pic_order_cnt_lsb_minus4 + 4  bits.
+                lt_ref_pic_poc_lsb_sps[i] =
+                    bit_reader.read((log2_max_pic_order_cnt_lsb_minus4 + 4) as u32)?;
+                used_by_curr_pic_lt_sps_flag[i] = bit_reader.read_bit()?;
+            }
+        }
 
         let sps_temporal_mvp_enabled_flag = bit_reader.read_bit()?;
         let strong_intra_smoothing_enabled_flag = bit_reader.read_bit()?;
 
         let vui_parameters_present_flag = bit_reader.read_bit()?;
         let vui = if vui_parameters_present_flag {
-            let vui = Vui::from_bit_reader(&mut bit_reader)?;
+            let vui = Vui::from_bit_reader(&mut bit_reader, sps_max_sub_layers_minus1)?;
             Some(vui)
         } else {
             None
         };
 
+        let sps_extension_present_flag = bit_reader.read_bit()?;
+        let (
+            sps_range_extension_flag,
+            sps_multilayer_extension_flag,
+            sps_3d_extension_flag,
+            sps_scc_extension_flag,
+            sps_extension_4bits,
+        ) = if sps_extension_present_flag {
+            (
+                bit_reader.read_bit()?,
+                bit_reader.read_bit()?,
+                bit_reader.read_bit()?,
+                bit_reader.read_bit()?,
+                bit_reader.read::<u8>(4)?,
+            )
+        } else {
+            (false, false, false, false, 0)
+        };
+
+        let sps_range_extension = if sps_range_extension_flag {
+            Some(SpsRangeExtension {
+                transform_skip_rotation_enabled_flag: bit_reader.read_bit()?,
+                transform_skip_context_enabled_flag: bit_reader.read_bit()?,
+                implicit_rdpcm_enabled_flag: bit_reader.read_bit()?,
+                explicit_rdpcm_enabled_flag: bit_reader.read_bit()?,
+                extended_precision_processing_flag: bit_reader.read_bit()?,
+                intra_smoothing_disabled_flag: bit_reader.read_bit()?,
+                high_precision_offsets_enabled_flag: bit_reader.read_bit()?,
+                persistent_rice_adaptation_enabled_flag: bit_reader.read_bit()?,
+                cabac_bypass_alignment_enabled_flag: bit_reader.read_bit()?,
+            })
+        } else {
+            None
+        };
+
+        // The remaining extensions, i.e. from `sps_scc_extension()` on, as well as `sps_extension_data_flag`s for
+        // `sps_extension_4bits != 0`, are ignored since nothing follows them but `rbsp_trailing_bits()`.
+
         Ok(Self {
             sps_video_parameter_set_id,
             sps_max_sub_layers_minus1,
@@ -316,14 +504,23 @@ impl SequenceParameterSet {
             amp_enabled_flag,
             sample_adaptive_offset_enabled_flag,
             scaling_list_enabled_flag,
+            sps_scaling_list_data,
             pcm_enabled_flag,
-            pcm_loop_filter_disabled_flag,
+            pcm,
             short_term_ref_pic_sets,
             long_term_ref_pics_present_flag,
             num_long_term_ref_pics_sps,
+            lt_ref_pic_poc_lsb_sps,
+            used_by_curr_pic_lt_sps_flag,
             sps_temporal_mvp_enabled_flag,
             strong_intra_smoothing_enabled_flag,
             vui,
+            sps_extension_present_flag,
+            sps_range_extension,
+            sps_multilayer_extension_flag,
+            sps_3d_extension_flag,
+            sps_scc_extension_flag,
+            sps_extension_4bits,
         })
     }
 }
@@ -331,6 +528,7 @@ impl SequenceParameterSet {
 impl Vui {
     pub fn from_bit_reader<R: Read>(
         bit_reader: &mut BitReader<R, BigEndian>,
+        sps_max_sub_layers_minus1: u8,
     ) -> Result<Self, io::Error> {
         let aspect_ratio_info_present_flag = bit_reader.read_bit()?;
         let (aspect_ratio_idc, sar_width, sar_height) = if aspect_ratio_info_present_flag {
@@ -376,9 +574,11 @@ impl Vui {
         };
 
         let overscan_info_present_flag = bit_reader.read_bit()?;
-        if overscan_info_present_flag {
-            todo!("overscan_info_present_flag == true not supported");
-        }
+        let overscan_appropriate_flag = if overscan_info_present_flag {
+            Some(bit_reader.read_bit()?)
+        } else {
+            None
+        };
 
         let video_signal_type_present_flag = bit_reader.read_bit()?;
         let video_signal_type = if video_signal_type_present_flag {
@@ -428,7 +628,17 @@ impl Vui {
 
         let default_display_window_flag = bit_reader.read_bit()?;
         let def_disp_win: Option<DefaultDisplayWindow> = if default_display_window_flag {
-            todo!("default_display_window_flag == true not supported");
+            let def_disp_win_left_offset: u16 = read_exp_golomb_ue(bit_reader)? as _;
+            let def_disp_win_right_offset: u16 = read_exp_golomb_ue(bit_reader)? as _;
+            let def_disp_win_top_offset: u16 = read_exp_golomb_ue(bit_reader)? as _;
+            let def_disp_win_bottom_offset: u16 = read_exp_golomb_ue(bit_reader)? as _;
+
+            Some(DefaultDisplayWindow {
+                def_disp_win_left_offset,
+                def_disp_win_right_offset,
+                def_disp_win_top_offset,
+                def_disp_win_bottom_offset,
+            })
         } else {
             None
         };
@@ -448,14 +658,22 @@ impl Vui {
             };
 
             let vui_hrd_parameters_present_flag = bit_reader.read_bit()?;
-            if vui_hrd_parameters_present_flag {
-                todo!("vui_hrd_parameters_present_flag == true not supported");
-            }
+            let vui_hrd_parameters = if vui_hrd_parameters_present_flag {
+                Some(HrdParameters::from_bit_reader(
+                    bit_reader,
+                    true,
+                    sps_max_sub_layers_minus1,
+                    None,
+                )?)
+            } else {
+                None
+            };
 
             Some(VuiTimingInfo {
                 vui_num_units_in_tick,
                 vui_time_scale,
                 vui_num_ticks_poc_diff_one_minus1,
+                vui_hrd_parameters,
             })
         } else {
             None
@@ -463,7 +681,25 @@ impl Vui {
 
         let bitstream_restriction_flag = bit_reader.read_bit()?;
         let bitstream_restriction: Option<BitstreamRestriction> = if bitstream_restriction_flag {
-            todo!("bitstream_restriction_flag == true not supported");
+            let tiles_fixed_structure_flag = bit_reader.read_bit()?;
+            let motion_vectors_over_pic_boundaries_flag = bit_reader.read_bit()?;
+            let restricted_ref_pic_lists_flag = bit_reader.read_bit()?;
+            let min_spatial_segmentation_idc: u16 = read_exp_golomb_ue(bit_reader)? as _;
+            let max_bytes_per_pic_denom: u8 = read_exp_golomb_ue(bit_reader)? as _;
+            let max_bits_per_min_cu_denom: u8 = read_exp_golomb_ue(bit_reader)? as _;
+            let log2_max_mv_length_horizontal: u8 = read_exp_golomb_ue(bit_reader)? as _;
+            let log2_max_mv_length_vertical: u8 = read_exp_golomb_ue(bit_reader)? as _;
+
+            Some(BitstreamRestriction {
+                tiles_fixed_structure_flag,
+                motion_vectors_over_pic_boundaries_flag,
+                restricted_ref_pic_lists_flag,
+                min_spatial_segmentation_idc,
+                max_bytes_per_pic_denom,
+                max_bits_per_min_cu_denom,
+                log2_max_mv_length_horizontal,
+                log2_max_mv_length_vertical,
+            })
         } else {
             None
         };
@@ -473,6 +709,7 @@ impl Vui {
             aspect_ratio_idc,
             sar_width,
             sar_height,
+            overscan_appropriate_flag,
             video_signal_type,
             chroma_loc_info,
             neutral_chroma_indication_flag,
@@ -484,3 +721,118 @@ impl Vui {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use bitstream_io::{BitWrite, BitWriter};
+
+    use crate::base::{test_sps_rbsp, write_exp_golomb_ue};
+
+    #[test]
+    fn vui_works() {
+        let mut bytes: Vec<u8> = Vec::new();
+        {
+            let mut bit_writer = BitWriter::endian(&mut bytes, BigEndian);
+            // aspect_ratio_info_present_flag = 1, aspect_ratio_idc = 1
+            bit_writer.write_bit(true).unwrap();
+            bit_writer.write(8, 1u8).unwrap();
+            // overscan_info_present_flag = 1, overscan_appropriate_flag = 1
+            bit_writer.write(2, 0b11u8).unwrap();
+            // video_signal_type_present_flag, chroma_loc_info_present_flag, neutral_chroma_indication_flag,
+            // field_seq_flag, frame_field_info_present_flag
+            bit_writer.write(5, 0u8).unwrap();
+            // default_display_window_flag = 1
+            bit_writer.write_bit(true).unwrap();
+            for offset in [0, 0, 4, 8] {
+                write_exp_golomb_ue(&mut bit_writer, offset);
+            }
+            // vui_timing_info_present_flag = 1
+            bit_writer.write_bit(true).unwrap();
+            bit_writer.write(32, 1001u32).unwrap();
+            bit_writer.write(32, 60000u32).unwrap();
+            // vui_poc_proportional_to_timing_flag = 0, vui_hrd_parameters_present_flag = 1
+            bit_writer.write(2, 0b01u8).unwrap();
+            // nal_hrd_parameters_present_flag = 1, vcl_hrd_parameters_present_flag = 0, sub_pic_hrd_params_present_flag = 0
+            bit_writer.write(3, 0b100u8).unwrap();
+            // bit_rate_scale = 4, cpb_size_scale = 6
+            bit_writer.write(4, 4u8).unwrap();
+            bit_writer.write(4, 6u8).unwrap();
+            for _ in 0..3 {
+                bit_writer.write(5, 23u8).unwrap();
+            }
+            // fixed_pic_rate_general_flag = 1, elemental_duration_in_tc_minus1 = 0, cpb_cnt_minus1 = 0
+            bit_writer.write_bit(true).unwrap();
+            write_exp_golomb_ue(&mut bit_writer, 0);
+            write_exp_golomb_ue(&mut bit_writer, 0);
+            // bit_rate_value_minus1 = 1000, cpb_size_value_minus1 = 2000, cbr_flag = 0
+            write_exp_golomb_ue(&mut bit_writer, 1000);
+            write_exp_golomb_ue(&mut bit_writer, 2000);
+            bit_writer.write_bit(false).unwrap();
+            // bitstream_restriction_flag = 1
+            bit_writer.write(4, 0b1010u8).unwrap();
+            for value in [0, 2, 1, 15, 15] {
+                write_exp_golomb_ue(&mut bit_writer, value);
+            }
+            bit_writer.byte_align().unwrap();
+        }
+
+        let mut reader = io::Cursor::new(bytes);
+        let mut bit_reader = BitReader::endian(&mut reader, BigEndian);
+        let vui = Vui::from_bit_reader(&mut bit_reader, 0).unwrap();
+
+        assert_eq!((vui.sar_width, vui.sar_height), (1, 1));
+        assert_eq!(vui.overscan_appropriate_flag, Some(true));
+
+        let def_disp_win = vui.def_disp_win.unwrap();
+        assert_eq!(def_disp_win.def_disp_win_top_offset, 4);
+        assert_eq!(def_disp_win.def_disp_win_bottom_offset, 8);
+
+        let vui_timing_info = vui.vui_timing_info.unwrap();
+        assert_eq!(vui_timing_info.vui_num_units_in_tick, 1001);
+        assert_eq!(vui_timing_info.vui_time_scale, 60000);
+
+        let hrd_parameters = vui_timing_info.vui_hrd_parameters.unwrap();
+        assert!(hrd_parameters.common_info.nal_hrd_parameters_present_flag);
+        assert_eq!(hrd_parameters.sub_layers.len(), 1);
+        assert_eq!(
+            hrd_parameters.sub_layers[0].elemental_duration_in_tc_minus1,
+            Some(0)
+        );
+        let nal_hrd_parameters = hrd_parameters.sub_layers[0]
+            .nal_hrd_parameters
+            .as_ref()
+            .unwrap();
+        assert_eq!(nal_hrd_parameters.bit_rate_value_minus1, [1000]);
+        assert_eq!(nal_hrd_parameters.cpb_size_value_minus1, [2000]);
+        assert!(hrd_parameters.sub_layers[0].vcl_hrd_parameters.is_none());
+
+        let bitstream_restriction = vui.bitstream_restriction.unwrap();
+        assert!(bitstream_restriction.motion_vectors_over_pic_boundaries_flag);
+        assert_eq!(bitstream_restriction.max_bytes_per_pic_denom, 2);
+        assert_eq!(bitstream_restriction.log2_max_mv_length_vertical, 15);
+    }
+
+    #[test]
+    fn pcm_parameters_works() {
+        let bytes = test_sps_rbsp(0, true, None);
+
+        let sps = SequenceParameterSet::from_rbsp_reader(&mut bytes.as_slice()).unwrap();
+        assert!(sps.pcm_enabled_flag);
+        assert_eq!(
+            sps.pcm,
+            Some(PcmParameters {
+                pcm_sample_bit_depth_luma_minus1: 7,
+                pcm_sample_bit_depth_chroma_minus1: 6,
+                log2_min_pcm_luma_coding_block_size_minus3: 1,
+                log2_diff_max_min_pcm_luma_coding_block_size: 2,
+                pcm_loop_filter_disabled_flag: true,
+            })
+        );
+        assert!(sps.pcm_loop_filter_disabled_flag());
+        assert!(sps.short_term_ref_pic_sets.is_empty());
+        assert!(sps.sps_temporal_mvp_enabled_flag);
+        assert!(sps.vui.is_none());
+    }
+}
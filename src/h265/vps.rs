@@ -4,10 +4,12 @@ use bitstream_io::BitRead as _;
 use bitstream_io::{BigEndian, BitReader};
 
 use crate::base::read_exp_golomb_ue;
+use crate::h265::hrd::HrdParameters;
 use crate::h265::ptl::{ProfileTierLevel, SubLayerOrderingInfo};
+use crate::h265::vps_extension::VpsExtension;
 
 /// See _7.3.2.1 Video parameter set RBSP syntax_ in the spec.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct VideoParameterSet {
     /// Identifies the VPS for reference by other syntax elements.
     pub vps_video_parameter_set_id: u8,
@@ -22,18 +24,31 @@ pub struct VideoParameterSet {
     pub profile_tier_level: ProfileTierLevel,
     pub vps_max_layer_id: u8,
     pub vps_num_layer_sets_minus1: u16,
+    /// `LayerSetLayerIdList[i]`: The `nuh_layer_id` values included in the `i`-th layer set, in increasing order.
+    ///
+    /// Entry `0` is the layer set containing only the base layer. Includes the additional layer sets specified in
+    /// `vps_extension()`, if any.
+    pub layer_set_layer_id_list: Vec<Vec<u8>>,
     /// `Some` means `vps_sub_layer_ordering_info_present_flag == true`.
     pub sub_layer_ordering_info: Option<SubLayerOrderingInfo>,
     /// `Some` means `vps_timing_info_present_flag == true`.
     pub timing_info: Option<TimingInfo>,
+    /// `Some` means `vps_extension_flag == true`.
+    pub vps_extension: Option<VpsExtension>,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct TimingInfo {
     pub vps_num_units_in_tick: u32,
     pub vps_time_scale: u32,
     /// `Some` means `vps_poc_proportional_to_timing_flag == true`.
     pub vps_num_ticks_poc_diff_one_minus1: Option<u32>,
+    /// `hrd_layer_set_idx[i]` for `i` in `[0, vps_num_hrd_parameters)`: The index of the layer set to which `hrd_parameters[i]` applies.
+    pub hrd_layer_set_idx: Vec<u16>,
+    /// `hrd_parameters()` for `i` in `[0, vps_num_hrd_parameters)`.
+    ///
+    /// `HrdParameters::common_inf_present_flag` holds `cprms_present_flag[i]`, which is inferred to be `true` for `i == 0`.
+    pub hrd_parameters: Vec<HrdParameters>,
 }
 
 impl VideoParameterSet {
@@ -77,12 +92,28 @@ impl VideoParameterSet {
         };
 
         let vps_max_layer_id: u8 = bit_reader.read(6)?;
-        let vps_num_layer_sets_minus1 = read_exp_golomb_ue(&mut bit_reader)? as u16;
+        let vps_num_layer_sets_minus1 = read_exp_golomb_ue(&mut bit_reader)?;
+        if vps_num_layer_sets_minus1 > 1023 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "vps_num_layer_sets_minus1 out of range: {}",
+                    vps_num_layer_sets_minus1
+                ),
+            ));
+        }
+        let vps_num_layer_sets_minus1 = vps_num_layer_sets_minus1 as u16;
 
-        for i in 1..=vps_num_layer_sets_minus1 {
-            for j in 0..=vps_max_layer_id {
+        let mut layer_set_layer_id_list: Vec<Vec<u8>> = vec![vec![0]];
+        for _ in 1..=vps_num_layer_sets_minus1 {
+            let mut layer_id_list: Vec<u8> = Vec::new();
+            for m in 0..=vps_max_layer_id {
                 let layer_id_included_flag = bit_reader.read_bit()?;
+                if layer_id_included_flag {
+                    layer_id_list.push(m);
+                }
             }
+            layer_set_layer_id_list.push(layer_id_list);
         }
 
         let vps_timing_info_present_flag = bit_reader.read_bit()?;
@@ -98,26 +129,86 @@ impl VideoParameterSet {
             };
 
             let vps_num_hrd_parameters = read_exp_golomb_ue(&mut bit_reader)?;
-            if vps_num_hrd_parameters > 0 {
-                todo!("vps_num_hrd_parameters > 0 not supported");
+            if vps_num_hrd_parameters > vps_num_layer_sets_minus1 as u32 + 1 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "vps_num_hrd_parameters out of range: {}",
+                        vps_num_hrd_parameters
+                    ),
+                ));
+            }
+
+            let mut hrd_layer_set_idx: Vec<u16> = Vec::new();
+            let mut hrd_parameters: Vec<HrdParameters> = Vec::new();
+            for i in 0..vps_num_hrd_parameters {
+                // hrd_layer_set_idx[i] shall be in the range of (vps_base_layer_internal_flag ? 0 : 1) to
+                // vps_num_layer_sets_minus1.
+                let hrd_layer_set_idx_i = read_exp_golomb_ue(&mut bit_reader)?;
+                if hrd_layer_set_idx_i > vps_num_layer_sets_minus1 as u32
+                    || (hrd_layer_set_idx_i == 0 && !vps_base_layer_internal_flag)
+                {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "hrd_layer_set_idx[{}] out of range: {}",
+                            i, hrd_layer_set_idx_i
+                        ),
+                    ));
+                }
+                hrd_layer_set_idx.push(hrd_layer_set_idx_i as u16);
+                let cprms_present_flag = if i > 0 { bit_reader.read_bit()? } else { true };
+                hrd_parameters.push(HrdParameters::from_bit_reader(
+                    &mut bit_reader,
+                    cprms_present_flag,
+                    vps_max_sub_layers_minus1,
+                    hrd_parameters.last().map(|x| &x.common_info),
+                )?);
             }
 
             Some(TimingInfo {
                 vps_num_units_in_tick,
                 vps_time_scale,
                 vps_num_ticks_poc_diff_one_minus1,
+                hrd_layer_set_idx,
+                hrd_parameters,
             })
         } else {
             None
         };
 
         let vps_extension_flag = bit_reader.read_bit()?;
+        let vps_extension = if vps_extension_flag {
+            // `vps_extension_alignment_bit_equal_to_one`s
+            while !bit_reader.byte_aligned() {
+                bit_reader.read_bit()?;
+            }
+            Some(VpsExtension::from_bit_reader(
+                &mut bit_reader,
+                vps_base_layer_internal_flag,
+                vps_max_layers_minus1,
+                vps_max_sub_layers_minus1,
+                &profile_tier_level,
+                &mut layer_set_layer_id_list,
+            )?)
+        } else {
+            None
+        };
 
-        // Skip VPS extension.
+        // `vps_vui()` is not parsed, in which case neither `vps_extension2_flag` nor `rbsp_trailing_bits()` can be located.
+        if !vps_extension
+            .as_ref()
+            .is_some_and(|x| x.vps_vui_present_flag)
+        {
+            if vps_extension.is_some() {
+                // `vps_extension2_flag`: Shall be 0 in bitstreams conforming to this version.
+                bit_reader.read_bit()?;
+            }
 
-        // `rbsp_trailing_bits()`
-        bit_reader.read_unary1()?;
-        bit_reader.byte_align();
+            // `rbsp_trailing_bits()`
+            bit_reader.read_unary1()?;
+            bit_reader.byte_align();
+        }
 
         Ok(Self {
             vps_video_parameter_set_id,
@@ -129,8 +220,15 @@ impl VideoParameterSet {
             profile_tier_level,
             vps_max_layer_id,
             vps_num_layer_sets_minus1,
+            layer_set_layer_id_list,
             sub_layer_ordering_info,
             timing_info,
+            vps_extension,
         })
     }
+
+    /// `NumLayersInIdList[i]`: The number of layers in the `i`-th layer set, or `None` if there is no such layer set.
+    pub fn num_layers_in_id_list(&self, i: usize) -> Option<usize> {
+        self.layer_set_layer_id_list.get(i).map(|x| x.len())
+    }
 }
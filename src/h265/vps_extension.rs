@@ -0,0 +1,928 @@
+//! VPS extension for multi-layer (MV-HEVC/SHVC) bitstreams, as specified in _Annex F_.
+
+use std::io::{self, Read};
+
+use bitstream_io::BitRead as _;
+use bitstream_io::{BigEndian, BitReader};
+
+use crate::base::read_exp_golomb_ue;
+use crate::h265::ptl::{ProfileTierLevel, ProfileTierLevelCommon};
+use crate::h265::sps::ConformanceWindow;
+
+/// See `vps_extension()` in _F.7.3.2.1.1 Video parameter set extension syntax_ in the spec.
+///
+/// Per-layer arrays are indexed by the layer index `i` in `[0, MaxLayersMinus1]`, unlike the spec which indexes some
+/// of them by `nuh_layer_id`. Use `layer_idx_in_vps()` to convert a `nuh_layer_id` to a layer index.
+#[derive(Debug, Clone)]
+pub struct VpsExtension {
+    pub splitting_flag: bool,
+    /// `scalability_mask_flag[smIdx]`. See _Table F.1 – Mapping of ScalabilityId to scalability dimensions_:
+    /// `1` for multiview, `2` for spatial/quality scalability and `3` for auxiliary pictures.
+    pub scalability_mask_flag: [bool; 16],
+    /// `dimension_id_len_minus1[j]` for `j` in `[0, NumScalabilityTypes)`, including the inferred last one when `splitting_flag == true`.
+    pub dimension_id_len_minus1: Vec<u8>,
+    pub vps_nuh_layer_id_present_flag: bool,
+    /// `layer_id_in_nuh[i]`. Inferred to be `i` when not present.
+    pub layer_id_in_nuh: Vec<u8>,
+    /// `dimension_id[i][j]`, derived from `layer_id_in_nuh[i]` when `splitting_flag == true`.
+    pub dimension_id: Vec<Vec<u8>>,
+    /// `ScalabilityId[i][smIdx]`.
+    pub scalability_id: Vec<[u8; 16]>,
+    pub view_id_len: u8,
+    /// `view_id_val[i]` for `i` in `[0, NumViews)`: The view identifier of the `i`-th view, in view order.
+    pub view_id_val: Vec<u16>,
+    /// `direct_dependency_flag[i][j]`: Whether the layer with index `j` is a direct reference layer for the layer with index `i`.
+    pub direct_dependency_flag: Vec<Vec<bool>>,
+    /// `DependencyFlag[i][j]`: Whether the layer with index `j` is a direct or indirect reference layer for the layer with index `i`.
+    pub dependency_flag: Vec<Vec<bool>>,
+    /// `TreePartitionLayerIdList[k]` for `k` in `[0, NumIndependentLayers)`.
+    pub tree_partition_layer_id_list: Vec<Vec<u8>>,
+    pub num_add_layer_sets: u16,
+    /// `highest_layer_idx_plus1[i][treeIdx]` for `i` in `[0, num_add_layer_sets)` and `treeIdx` in `[1, NumIndependentLayers)`.
+    ///
+    /// Index `0` of the inner `Vec` is unused.
+    pub highest_layer_idx_plus1: Vec<Vec<u8>>,
+    /// `sub_layers_vps_max_minus1[i]`. Inferred to be `vps_max_sub_layers_minus1` when not present.
+    pub sub_layers_vps_max_minus1: Vec<u8>,
+    /// `max_tid_il_ref_pics_plus1[i][j]`. Inferred to be `7` when not present.
+    pub max_tid_il_ref_pics_plus1: Vec<Vec<u8>>,
+    pub default_ref_layers_active_flag: bool,
+    /// The `profile_tier_level()` structures in the VPS, for `i` in `[0, vps_num_profile_tier_level_minus1]`.
+    ///
+    /// Index `0` is `VideoParameterSet::profile_tier_level`, and index `1` is the one for the base layer when
+    /// `vps_base_layer_internal_flag == true`. The profile information is inferred from the preceding one when
+    /// `vps_profile_present_flag[i] == false`.
+    pub profile_tier_levels: Vec<ProfileTierLevel>,
+    pub num_add_olss: u16,
+    pub default_output_layer_idc: u8,
+    /// The output layer sets, for `i` in `[0, NumOutputLayerSets)`.
+    pub output_layer_sets: Vec<OutputLayerSet>,
+    /// `rep_format()` for `i` in `[0, vps_num_rep_formats_minus1]`.
+    pub rep_formats: Vec<RepFormat>,
+    /// `vps_rep_format_idx[i]`. Inferred when not present.
+    pub vps_rep_format_idx: Vec<u8>,
+    pub max_one_active_ref_layer_flag: bool,
+    pub vps_poc_lsb_aligned_flag: bool,
+    /// `poc_lsb_not_present_flag[i]`. `false` when not present.
+    pub poc_lsb_not_present_flag: Vec<bool>,
+    pub direct_dep_type_len_minus2: u8,
+    /// `direct_dependency_type[i][j]`, or `default_direct_dependency_type` when `default_direct_dependency_flag == true`.
+    ///
+    /// Only meaningful where `direct_dependency_flag[i][j] == true`.
+    pub direct_dependency_type: Vec<Vec<u32>>,
+    pub vps_non_vui_extension_data_byte: Vec<u8>,
+    /// `vps_vui()` is not parsed.
+    pub vps_vui_present_flag: bool,
+}
+
+/// An output layer set specified in `vps_extension()`.
+#[derive(Debug, Clone, Default)]
+pub struct OutputLayerSet {
+    /// `OlsIdxToLsIdx[i]`: The index of the layer set the output layer set is based on.
+    pub layer_set_idx: u16,
+    /// `output_layer_flag[i][j]` for `j` in `[0, NumLayersInIdList[OlsIdxToLsIdx[i]])`, including inferred values.
+    pub output_layer_flag: Vec<bool>,
+    /// `NecessaryLayerFlag[i][j]`: Whether the layer is either an output layer or a reference layer of an output layer.
+    pub necessary_layer_flag: Vec<bool>,
+    /// `profile_tier_level_idx[i][j]`: The index into `VpsExtension::profile_tier_levels`. `0` when not present.
+    pub profile_tier_level_idx: Vec<u8>,
+    pub alt_output_layer_flag: bool,
+    /// `sub_layer_flag_info_present_flag[i]` in `dpb_size()`.
+    pub sub_layer_flag_info_present_flag: bool,
+    /// `sub_layer_dpb_info_present_flag[i][j]` for the sub-layer `j` in `[0, MaxSubLayersInLayerSetMinus1[OlsIdxToLsIdx[i]]]`.
+    pub sub_layer_dpb_info_present_flag: Vec<bool>,
+    /// `max_vps_dec_pic_buffering_minus1[i][k][j]`, indexed `[k][j]`. Inferred from the lower sub-layer when not present.
+    pub max_vps_dec_pic_buffering_minus1: Vec<Vec<u8>>,
+    /// `max_vps_num_reorder_pics[i][j]`. Inferred from the lower sub-layer when not present.
+    pub max_vps_num_reorder_pics: Vec<u8>,
+    /// `max_vps_latency_increase_plus1[i][j]`. Inferred from the lower sub-layer when not present.
+    pub max_vps_latency_increase_plus1: Vec<u32>,
+}
+
+/// See `rep_format()` in _F.7.3.2.1.2 Representation format syntax_ in the spec.
+///
+/// The chroma format and bit depths are inferred from the preceding `rep_format()` when `chroma_and_bit_depth_vps_present_flag == false`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RepFormat {
+    pub pic_width_vps_in_luma_samples: u16,
+    pub pic_height_vps_in_luma_samples: u16,
+    pub chroma_format_vps_idc: u8,
+    pub separate_colour_plane_vps_flag: bool,
+    pub bit_depth_vps_luma_minus8: u8,
+    pub bit_depth_vps_chroma_minus8: u8,
+    /// `Some` means `conformance_window_vps_flag == true`.
+    pub conformance_window_vps: Option<ConformanceWindow>,
+}
+
+impl VpsExtension {
+    /// Reads `vps_extension()`, following `vps_extension_alignment_bit_equal_to_one`s.
+    ///
+    /// `layer_set_layer_id_list` holds `LayerSetLayerIdList` for the layer sets in the base part of the VPS, and is
+    /// extended with the additional layer sets specified by `num_add_layer_sets`.
+    pub fn from_bit_reader<R: Read>(
+        bit_reader: &mut BitReader<R, BigEndian>,
+        vps_base_layer_internal_flag: bool,
+        vps_max_layers_minus1: u8,
+        vps_max_sub_layers_minus1: u8,
+        profile_tier_level: &ProfileTierLevel,
+        layer_set_layer_id_list: &mut Vec<Vec<u8>>,
+    ) -> Result<Self, io::Error> {
+        let max_layers_minus1 = vps_max_layers_minus1.min(62) as usize;
+        let num_layers = max_layers_minus1 + 1;
+
+        let mut profile_tier_levels = vec![*profile_tier_level];
+        if vps_max_layers_minus1 > 0 && vps_base_layer_internal_flag {
+            profile_tier_levels.push(ProfileTierLevel::from_bit_reader(
+                bit_reader,
+                false,
+                vps_max_sub_layers_minus1,
+            )?);
+            Self::infer_profile(&mut profile_tier_levels);
+        }
+
+        let splitting_flag = bit_reader.read_bit()?;
+        let mut scalability_mask_flag = [false; 16];
+        for flag in scalability_mask_flag.iter_mut() {
+            *flag = bit_reader.read_bit()?;
+        }
+        let num_scalability_types = scalability_mask_flag.iter().filter(|&&x| x).count();
+
+        let mut dimension_id_len_minus1: Vec<u8> = Vec::new();
+        for _ in 0..num_scalability_types.saturating_sub(splitting_flag as usize) {
+            dimension_id_len_minus1.push(bit_reader.read(3)?);
+        }
+        // `dimBitOffset[j]`
+        let mut dim_bit_offset: Vec<u32> = vec![0];
+        if splitting_flag && num_scalability_types > 0 {
+            for &len_minus1 in &dimension_id_len_minus1 {
+                dim_bit_offset.push(dim_bit_offset.last().unwrap() + len_minus1 as u32 + 1);
+            }
+            let last_offset = *dim_bit_offset.last().unwrap();
+            if last_offset > 5 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "dimension_id_len_minus1 exceed 6 bits in total",
+                ));
+            }
+            dimension_id_len_minus1.push((5 - last_offset) as u8);
+            dim_bit_offset.push(6);
+        }
+
+        let vps_nuh_layer_id_present_flag = bit_reader.read_bit()?;
+        let mut layer_id_in_nuh: Vec<u8> = (0..num_layers as u8).collect();
+        let mut dimension_id: Vec<Vec<u8>> = vec![vec![0; num_scalability_types]; num_layers];
+        for i in 1..num_layers {
+            if vps_nuh_layer_id_present_flag {
+                layer_id_in_nuh[i] = bit_reader.read(6)?;
+                if layer_id_in_nuh[i] <= layer_id_in_nuh[i - 1] {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("layer_id_in_nuh[{}] not increasing", i),
+                    ));
+                }
+            }
+            if !splitting_flag {
+                for j in 0..num_scalability_types {
+                    dimension_id[i][j] = bit_reader.read(dimension_id_len_minus1[j] as u32 + 1)?;
+                }
+            } else {
+                for j in 0..num_scalability_types {
+                    dimension_id[i][j] = ((layer_id_in_nuh[i] as u32
+                        & ((1 << dim_bit_offset[j + 1]) - 1))
+                        >> dim_bit_offset[j]) as u8;
+                }
+            }
+        }
+
+        // (F-3)
+        let scalability_id: Vec<[u8; 16]> = dimension_id
+            .iter()
+            .map(|dimension_id| {
+                let mut scalability_id = [0; 16];
+                let mut dimension_id = dimension_id.iter();
+                for (sm_idx, &mask) in scalability_mask_flag.iter().enumerate() {
+                    if mask {
+                        scalability_id[sm_idx] = *dimension_id.next().unwrap();
+                    }
+                }
+                scalability_id
+            })
+            .collect();
+        let num_views = (0..num_layers)
+            .filter(|&i| (0..i).all(|j| scalability_id[i][1] != scalability_id[j][1]))
+            .count();
+
+        let view_id_len: u8 = bit_reader.read(4)?;
+        let mut view_id_val: Vec<u16> = Vec::new();
+        if view_id_len > 0 {
+            for _ in 0..num_views {
+                view_id_val.push(bit_reader.read(view_id_len as u32)?);
+            }
+        }
+
+        let mut direct_dependency_flag = vec![vec![false; num_layers]; num_layers];
+        for (i, direct_dependency_flag) in direct_dependency_flag.iter_mut().enumerate().skip(1) {
+            for flag in &mut direct_dependency_flag[..i] {
+                *flag = bit_reader.read_bit()?;
+            }
+        }
+
+        // (F-4)
+        let mut dependency_flag = direct_dependency_flag.clone();
+        for (i, direct_dependency_flag) in direct_dependency_flag.iter().enumerate() {
+            for k in 0..i {
+                if direct_dependency_flag[k] {
+                    let (lower, upper) = dependency_flag.split_at_mut(i);
+                    for (flag, &indirect) in upper[0].iter_mut().zip(&lower[k]) {
+                        *flag |= indirect;
+                    }
+                }
+            }
+        }
+
+        // (F-5), (F-6)
+        let num_direct_ref_layers: Vec<usize> = direct_dependency_flag
+            .iter()
+            .map(|flags| flags.iter().filter(|&&x| x).count())
+            .collect();
+        let mut tree_partition_layer_id_list: Vec<Vec<u8>> = Vec::new();
+        let mut layer_id_in_list_flag = [false; 64];
+        for i in 0..num_layers {
+            if num_direct_ref_layers[i] == 0 {
+                let mut tree_partition = vec![layer_id_in_nuh[i]];
+                for j in 0..num_layers {
+                    let pred_layer_id = layer_id_in_nuh[j];
+                    if dependency_flag[j][i] && !layer_id_in_list_flag[pred_layer_id as usize] {
+                        tree_partition.push(pred_layer_id);
+                        layer_id_in_list_flag[pred_layer_id as usize] = true;
+                    }
+                }
+                tree_partition_layer_id_list.push(tree_partition);
+            }
+        }
+        let num_independent_layers = tree_partition_layer_id_list.len();
+
+        let mut num_add_layer_sets: u16 = 0;
+        if num_independent_layers > 1 {
+            num_add_layer_sets =
+                read_exp_golomb_ue_max(bit_reader, "num_add_layer_sets", 1023)? as _;
+        }
+        let mut highest_layer_idx_plus1: Vec<Vec<u8>> = Vec::new();
+        for _ in 0..num_add_layer_sets {
+            let mut highest_layer_idx_plus1_i = vec![0; num_independent_layers];
+            for tree_idx in 1..num_independent_layers {
+                let length_in_bits =
+                    ceil_log2(tree_partition_layer_id_list[tree_idx].len() as u32 + 1);
+                highest_layer_idx_plus1_i[tree_idx] = bit_reader.read(length_in_bits)?;
+            }
+
+            // (F-9)
+            let mut layer_id_list: Vec<u8> = Vec::new();
+            for tree_idx in 1..num_independent_layers {
+                let tree_partition = &tree_partition_layer_id_list[tree_idx];
+                let count =
+                    (highest_layer_idx_plus1_i[tree_idx] as usize).min(tree_partition.len());
+                layer_id_list.extend_from_slice(&tree_partition[..count]);
+            }
+            layer_set_layer_id_list.push(layer_id_list);
+
+            highest_layer_idx_plus1.push(highest_layer_idx_plus1_i);
+        }
+        let num_layer_sets = layer_set_layer_id_list.len();
+
+        let vps_sub_layers_max_minus1_present_flag = bit_reader.read_bit()?;
+        let mut sub_layers_vps_max_minus1 = vec![vps_max_sub_layers_minus1; num_layers];
+        if vps_sub_layers_max_minus1_present_flag {
+            for value in sub_layers_vps_max_minus1.iter_mut() {
+                *value = bit_reader.read(3)?;
+            }
+        }
+
+        let max_tid_ref_present_flag = bit_reader.read_bit()?;
+        let mut max_tid_il_ref_pics_plus1 = vec![vec![7; num_layers]; num_layers];
+        if max_tid_ref_present_flag {
+            for i in 0..max_layers_minus1 {
+                for j in i + 1..num_layers {
+                    if direct_dependency_flag[j][i] {
+                        max_tid_il_ref_pics_plus1[i][j] = bit_reader.read(3)?;
+                    }
+                }
+            }
+        }
+
+        let default_ref_layers_active_flag = bit_reader.read_bit()?;
+
+        let vps_num_profile_tier_level_minus1 = read_exp_golomb_ue(bit_reader)? as usize;
+        for _ in
+            (if vps_base_layer_internal_flag { 2 } else { 1 })..=vps_num_profile_tier_level_minus1
+        {
+            let vps_profile_present_flag = bit_reader.read_bit()?;
+            profile_tier_levels.push(ProfileTierLevel::from_bit_reader(
+                bit_reader,
+                vps_profile_present_flag,
+                vps_max_sub_layers_minus1,
+            )?);
+            if !vps_profile_present_flag {
+                Self::infer_profile(&mut profile_tier_levels);
+            }
+        }
+
+        let mut num_add_olss: u16 = 0;
+        let mut default_output_layer_idc: u8 = 0;
+        if num_layer_sets > 1 {
+            num_add_olss = read_exp_golomb_ue_max(bit_reader, "num_add_olss", 1023)? as _;
+            default_output_layer_idc = bit_reader.read(2)?;
+        }
+        let default_output_layer_idc_clamped = default_output_layer_idc.min(2);
+        let num_output_layer_sets = num_add_olss as usize + num_layer_sets;
+
+        let layer_idx_in_vps = |layer_id: u8| {
+            layer_id_in_nuh
+                .iter()
+                .position(|&x| x == layer_id)
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Layer set with nuh_layer_id {} not in the VPS", layer_id),
+                    )
+                })
+        };
+
+        let mut output_layer_sets: Vec<OutputLayerSet> = vec![OutputLayerSet {
+            layer_set_idx: 0,
+            output_layer_flag: vec![true],
+            necessary_layer_flag: vec![true],
+            profile_tier_level_idx: vec![0],
+            ..Default::default()
+        }];
+        for i in 1..num_output_layer_sets {
+            let mut layer_set_idx = i;
+            if num_layer_sets > 2 && i >= num_layer_sets {
+                let length_in_bits = ceil_log2(num_layer_sets as u32 - 1);
+                let layer_set_idx_for_ols_minus1: usize =
+                    bit_reader.read::<u32>(length_in_bits)? as _;
+                layer_set_idx = layer_set_idx_for_ols_minus1 + 1;
+            } else if i >= num_layer_sets {
+                layer_set_idx = 1;
+            }
+            let layer_id_list = layer_set_layer_id_list.get(layer_set_idx).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Layer set {} out of range", layer_set_idx),
+                )
+            })?;
+            let num_layers_in_id_list = layer_id_list.len();
+
+            let mut output_layer_flag = vec![false; num_layers_in_id_list];
+            // `i > vps_num_layer_sets_minus1`: An output layer set based on an additional layer set.
+            if i >= num_layer_sets - num_add_layer_sets as usize
+                || default_output_layer_idc_clamped == 2
+            {
+                for flag in output_layer_flag.iter_mut() {
+                    *flag = bit_reader.read_bit()?;
+                }
+            } else if default_output_layer_idc_clamped == 0 {
+                output_layer_flag.fill(true);
+            } else if let Some(flag) = output_layer_flag.last_mut() {
+                // The layer with the highest `nuh_layer_id` in the layer set.
+                *flag = true;
+            }
+
+            // (F-10)
+            let mut necessary_layer_flag = vec![false; num_layers_in_id_list];
+            for ls_layer_idx in 0..num_layers_in_id_list {
+                if output_layer_flag[ls_layer_idx] {
+                    necessary_layer_flag[ls_layer_idx] = true;
+                    let curr_layer_idx = layer_idx_in_vps(layer_id_list[ls_layer_idx])?;
+                    for r_ls_layer_idx in 0..ls_layer_idx {
+                        let ref_layer_idx = layer_idx_in_vps(layer_id_list[r_ls_layer_idx])?;
+                        if dependency_flag[curr_layer_idx][ref_layer_idx] {
+                            necessary_layer_flag[r_ls_layer_idx] = true;
+                        }
+                    }
+                }
+            }
+
+            let mut profile_tier_level_idx = vec![0; num_layers_in_id_list];
+            for j in 0..num_layers_in_id_list {
+                if necessary_layer_flag[j] && vps_num_profile_tier_level_minus1 > 0 {
+                    let length_in_bits = ceil_log2(vps_num_profile_tier_level_minus1 as u32 + 1);
+                    profile_tier_level_idx[j] = bit_reader.read(length_in_bits)?;
+                }
+            }
+
+            let num_output_layers = output_layer_flag.iter().filter(|&&x| x).count();
+            let mut alt_output_layer_flag = false;
+            if num_output_layers == 1 {
+                let ols_highest_output_layer_idx = output_layer_flag
+                    .iter()
+                    .rposition(|&x| x)
+                    .map(|j| layer_idx_in_vps(layer_id_list[j]))
+                    .unwrap()?;
+                if num_direct_ref_layers[ols_highest_output_layer_idx] > 0 {
+                    alt_output_layer_flag = bit_reader.read_bit()?;
+                }
+            }
+
+            output_layer_sets.push(OutputLayerSet {
+                layer_set_idx: layer_set_idx as u16,
+                output_layer_flag,
+                necessary_layer_flag,
+                profile_tier_level_idx,
+                alt_output_layer_flag,
+                ..Default::default()
+            });
+        }
+
+        let vps_num_rep_formats_minus1 = read_exp_golomb_ue(bit_reader)?;
+        if vps_num_rep_formats_minus1 > 255 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "vps_num_rep_formats_minus1 out of range: {}",
+                    vps_num_rep_formats_minus1
+                ),
+            ));
+        }
+        let mut rep_formats: Vec<RepFormat> = Vec::new();
+        for _ in 0..=vps_num_rep_formats_minus1 {
+            let rep_format = RepFormat::from_bit_reader(bit_reader, rep_formats.last())?;
+            rep_formats.push(rep_format);
+        }
+
+        let rep_format_idx_present_flag = if vps_num_rep_formats_minus1 > 0 {
+            bit_reader.read_bit()?
+        } else {
+            false
+        };
+        let mut vps_rep_format_idx: Vec<u8> = (0..num_layers)
+            .map(|i| i.min(vps_num_rep_formats_minus1 as usize) as u8)
+            .collect();
+        if rep_format_idx_present_flag {
+            let length_in_bits = ceil_log2(vps_num_rep_formats_minus1 + 1);
+            for value in vps_rep_format_idx
+                .iter_mut()
+                .skip(vps_base_layer_internal_flag as usize)
+            {
+                *value = bit_reader.read(length_in_bits)?;
+            }
+        }
+
+        let max_one_active_ref_layer_flag = bit_reader.read_bit()?;
+        let vps_poc_lsb_aligned_flag = bit_reader.read_bit()?;
+        let mut poc_lsb_not_present_flag = vec![false; num_layers];
+        for i in 1..num_layers {
+            if num_direct_ref_layers[i] == 0 {
+                poc_lsb_not_present_flag[i] = bit_reader.read_bit()?;
+            }
+        }
+
+        // `dpb_size()`
+        // `MaxSubLayersInLayerSetMinus1[i]`
+        let max_sub_layers_in_layer_set_minus1: Vec<u8> = layer_set_layer_id_list
+            .iter()
+            .map(|layer_id_list| {
+                layer_id_list.iter().try_fold(0, |max, &layer_id| {
+                    Ok(max.max(sub_layers_vps_max_minus1[layer_idx_in_vps(layer_id)?]))
+                })
+            })
+            .collect::<Result<_, io::Error>>()?;
+        for output_layer_set in output_layer_sets.iter_mut().skip(1) {
+            let curr_ls_idx = output_layer_set.layer_set_idx as usize;
+            let layer_id_list = &layer_set_layer_id_list[curr_ls_idx];
+            let num_sub_layers = max_sub_layers_in_layer_set_minus1[curr_ls_idx] as usize + 1;
+
+            output_layer_set.sub_layer_flag_info_present_flag = bit_reader.read_bit()?;
+            output_layer_set.sub_layer_dpb_info_present_flag = vec![false; num_sub_layers];
+            output_layer_set.max_vps_dec_pic_buffering_minus1 =
+                vec![vec![0; num_sub_layers]; layer_id_list.len()];
+            output_layer_set.max_vps_num_reorder_pics = vec![0; num_sub_layers];
+            output_layer_set.max_vps_latency_increase_plus1 = vec![0; num_sub_layers];
+            for j in 0..num_sub_layers {
+                output_layer_set.sub_layer_dpb_info_present_flag[j] =
+                    if j > 0 && output_layer_set.sub_layer_flag_info_present_flag {
+                        bit_reader.read_bit()?
+                    } else {
+                        j == 0
+                    };
+                if output_layer_set.sub_layer_dpb_info_present_flag[j] {
+                    for (k, &layer_id) in layer_id_list.iter().enumerate() {
+                        if output_layer_set.necessary_layer_flag[k]
+                            && (vps_base_layer_internal_flag || layer_id != 0)
+                        {
+                            output_layer_set.max_vps_dec_pic_buffering_minus1[k][j] =
+                                read_exp_golomb_ue_max(
+                                    bit_reader,
+                                    "max_vps_dec_pic_buffering_minus1",
+                                    u8::MAX as u32,
+                                )? as _;
+                        }
+                    }
+                    output_layer_set.max_vps_num_reorder_pics[j] = read_exp_golomb_ue_max(
+                        bit_reader,
+                        "max_vps_num_reorder_pics",
+                        u8::MAX as u32,
+                    )? as _;
+                    output_layer_set.max_vps_latency_increase_plus1[j] =
+                        read_exp_golomb_ue(bit_reader)?;
+                } else {
+                    // Inferred from the lower sub-layer.
+                    for max_vps_dec_pic_buffering_minus1 in
+                        output_layer_set.max_vps_dec_pic_buffering_minus1.iter_mut()
+                    {
+                        max_vps_dec_pic_buffering_minus1[j] =
+                            max_vps_dec_pic_buffering_minus1[j - 1];
+                    }
+                    output_layer_set.max_vps_num_reorder_pics[j] =
+                        output_layer_set.max_vps_num_reorder_pics[j - 1];
+                    output_layer_set.max_vps_latency_increase_plus1[j] =
+                        output_layer_set.max_vps_latency_increase_plus1[j - 1];
+                }
+            }
+        }
+
+        let direct_dep_type_len_minus2 = read_exp_golomb_ue(bit_reader)?;
+        if direct_dep_type_len_minus2 > 30 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "direct_dep_type_len_minus2 out of range: {}",
+                    direct_dep_type_len_minus2
+                ),
+            ));
+        }
+        let direct_dep_type_len = direct_dep_type_len_minus2 + 2;
+        let default_direct_dependency_flag = bit_reader.read_bit()?;
+        let mut direct_dependency_type = vec![vec![0; num_layers]; num_layers];
+        if default_direct_dependency_flag {
+            let default_direct_dependency_type: u32 = bit_reader.read(direct_dep_type_len)?;
+            for direct_dependency_type in direct_dependency_type.iter_mut() {
+                direct_dependency_type.fill(default_direct_dependency_type);
+            }
+        } else {
+            for i in (if vps_base_layer_internal_flag { 1 } else { 2 })..num_layers {
+                for j in (!vps_base_layer_internal_flag as usize)..i {
+                    if direct_dependency_flag[i][j] {
+                        direct_dependency_type[i][j] = bit_reader.read(direct_dep_type_len)?;
+                    }
+                }
+            }
+        }
+
+        let vps_non_vui_extension_length = read_exp_golomb_ue(bit_reader)?;
+        if vps_non_vui_extension_length > 4096 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "vps_non_vui_extension_length out of range: {}",
+                    vps_non_vui_extension_length
+                ),
+            ));
+        }
+        let mut vps_non_vui_extension_data_byte: Vec<u8> = Vec::new();
+        for _ in 0..vps_non_vui_extension_length {
+            vps_non_vui_extension_data_byte.push(bit_reader.read(8)?);
+        }
+
+        let vps_vui_present_flag = bit_reader.read_bit()?;
+
+        Ok(Self {
+            splitting_flag,
+            scalability_mask_flag,
+            dimension_id_len_minus1,
+            vps_nuh_layer_id_present_flag,
+            layer_id_in_nuh,
+            dimension_id,
+            scalability_id,
+            view_id_len,
+            view_id_val,
+            direct_dependency_flag,
+            dependency_flag,
+            tree_partition_layer_id_list,
+            num_add_layer_sets,
+            highest_layer_idx_plus1,
+            sub_layers_vps_max_minus1,
+            max_tid_il_ref_pics_plus1,
+            default_ref_layers_active_flag,
+            profile_tier_levels,
+            num_add_olss,
+            default_output_layer_idc,
+            output_layer_sets,
+            rep_formats,
+            vps_rep_format_idx,
+            max_one_active_ref_layer_flag,
+            vps_poc_lsb_aligned_flag,
+            poc_lsb_not_present_flag,
+            direct_dep_type_len_minus2: direct_dep_type_len_minus2 as u8,
+            direct_dependency_type,
+            vps_non_vui_extension_data_byte,
+            vps_vui_present_flag,
+        })
+    }
+
+    /// `MaxLayersMinus1 + 1`: The number of layers.
+    pub fn num_layers(&self) -> usize {
+        self.layer_id_in_nuh.len()
+    }
+
+    /// `LayerIdxInVps[nuh_layer_id]`.
+    pub fn layer_idx_in_vps(&self, nuh_layer_id: u8) -> Option<usize> {
+        self.layer_id_in_nuh.iter().position(|&x| x == nuh_layer_id)
+    }
+
+    /// `ViewOrderIdx` of the layer with index `i`.
+    pub fn view_order_idx(&self, i: usize) -> u8 {
+        self.scalability_id[i][1]
+    }
+
+    /// `DependencyId` of the layer with index `i`.
+    pub fn dependency_id(&self, i: usize) -> u8 {
+        self.scalability_id[i][2]
+    }
+
+    /// `AuxId` of the layer with index `i`.
+    pub fn aux_id(&self, i: usize) -> u8 {
+        self.scalability_id[i][3]
+    }
+
+    /// The view identifier of the layer with index `i`, i.e. `view_id_val[ViewOrderIdx]`. `0` when not present.
+    pub fn view_id(&self, i: usize) -> u16 {
+        self.view_id_val
+            .get(self.view_order_idx(i) as usize)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// `NumDirectRefLayers` of the layer with index `i`.
+    pub fn num_direct_ref_layers(&self, i: usize) -> usize {
+        self.direct_dependency_flag[i]
+            .iter()
+            .filter(|&&x| x)
+            .count()
+    }
+
+    /// `NumIndependentLayers`.
+    pub fn num_independent_layers(&self) -> usize {
+        self.tree_partition_layer_id_list.len()
+    }
+
+    /// Copies the profile information of the second last entry to the last entry of `profile_tier_levels`.
+    fn infer_profile(profile_tier_levels: &mut [ProfileTierLevel]) {
+        if let [.., previous, last] = profile_tier_levels {
+            let level_idc = last.general.level_idc;
+            last.general = ProfileTierLevelCommon {
+                level_idc,
+                ..previous.general
+            };
+        }
+    }
+}
+
+impl RepFormat {
+    fn from_bit_reader<R: Read>(
+        bit_reader: &mut BitReader<R, BigEndian>,
+        previous: Option<&Self>,
+    ) -> Result<Self, io::Error> {
+        let mut value = Self {
+            pic_width_vps_in_luma_samples: bit_reader.read(16)?,
+            pic_height_vps_in_luma_samples: bit_reader.read(16)?,
+            ..previous.copied().unwrap_or_default()
+        };
+
+        let chroma_and_bit_depth_vps_present_flag = bit_reader.read_bit()?;
+        if chroma_and_bit_depth_vps_present_flag {
+            value.chroma_format_vps_idc = bit_reader.read(2)?;
+            value.separate_colour_plane_vps_flag = if value.chroma_format_vps_idc == 3 {
+                bit_reader.read_bit()?
+            } else {
+                false
+            };
+            value.bit_depth_vps_luma_minus8 = bit_reader.read(4)?;
+            value.bit_depth_vps_chroma_minus8 = bit_reader.read(4)?;
+        } else if previous.is_none() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "chroma_and_bit_depth_vps_present_flag == false for the first rep_format()",
+            ));
+        }
+
+        let conformance_window_vps_flag = bit_reader.read_bit()?;
+        value.conformance_window_vps = if conformance_window_vps_flag {
+            Some(ConformanceWindow {
+                conf_win_left_offset: read_exp_golomb_ue(bit_reader)?,
+                conf_win_right_offset: read_exp_golomb_ue(bit_reader)?,
+                conf_win_top_offset: read_exp_golomb_ue(bit_reader)?,
+                conf_win_bottom_offset: read_exp_golomb_ue(bit_reader)?,
+            })
+        } else {
+            None
+        };
+
+        Ok(value)
+    }
+}
+
+/// `Ceil(Log2(x))`.
+/// Reads `ue(v)` of `name`, which shall be at most `max`, e.g. so that it can be narrowed.
+fn read_exp_golomb_ue_max<R: Read>(
+    bit_reader: &mut BitReader<R, BigEndian>,
+    name: &str,
+    max: u32,
+) -> Result<u32, io::Error> {
+    let value = read_exp_golomb_ue(bit_reader)?;
+    if value > max {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} out of range: {}", name, value),
+        ));
+    }
+    Ok(value)
+}
+
+fn ceil_log2(x: u32) -> u32 {
+    if x <= 1 {
+        0
+    } else {
+        32 - (x - 1).leading_zeros()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use bitstream_io::{BitWrite, BitWriter};
+
+    use crate::base::write_exp_golomb_ue;
+
+    #[test]
+    fn vps_extension_works() {
+        // A two-view MV-HEVC stream, where the second view depends on the base view.
+        let vps_extension_bytes = |max_vps_dec_pic_buffering_minus1: u32| {
+            let mut bytes: Vec<u8> = Vec::new();
+            {
+                let mut bit_writer = BitWriter::endian(&mut bytes, BigEndian);
+                // `profile_tier_level(0, 0)` for the base layer: general_level_idc = 93
+                bit_writer.write(8, 93u8).unwrap();
+                // splitting_flag = 0, scalability_mask_flag[1] = 1 (multiview), dimension_id_len_minus1[0] = 0
+                bit_writer.write_bit(false).unwrap();
+                bit_writer.write(16, 0x4000u16).unwrap();
+                bit_writer.write(3, 0u8).unwrap();
+                // vps_nuh_layer_id_present_flag = 0, dimension_id[1][0] = 1
+                bit_writer.write_bit(false).unwrap();
+                bit_writer.write_bit(true).unwrap();
+                // view_id_len = 1, view_id_val = [0, 1]
+                bit_writer.write(4, 1u8).unwrap();
+                bit_writer.write(2, 0b01u8).unwrap();
+                // direct_dependency_flag[1][0] = 1
+                bit_writer.write_bit(true).unwrap();
+                // vps_sub_layers_max_minus1_present_flag = 0, max_tid_ref_present_flag = 0, default_ref_layers_active_flag = 1
+                bit_writer.write(3, 0b001u8).unwrap();
+                // vps_num_profile_tier_level_minus1 = 2
+                write_exp_golomb_ue(&mut bit_writer, 2);
+                // vps_profile_present_flag[2] = 1, `profile_tier_level(1, 0)`: Multiview Main
+                bit_writer.write_bit(true).unwrap();
+                bit_writer.write(8, 6u8).unwrap();
+                bit_writer.write(32, 1u32 << (31 - 6)).unwrap();
+                bit_writer.write(4, 0b1001u8).unwrap();
+                bit_writer.write(44, 0u64).unwrap();
+                bit_writer.write(8, 93u8).unwrap();
+                // num_add_olss = 0, default_output_layer_idc = 0
+                write_exp_golomb_ue(&mut bit_writer, 0);
+                bit_writer.write(2, 0u8).unwrap();
+                // profile_tier_level_idx[1] = [1, 2]
+                bit_writer.write(2, 1u8).unwrap();
+                bit_writer.write(2, 2u8).unwrap();
+                // vps_num_rep_formats_minus1 = 0, `rep_format()`: 1920x1080 4:2:0 8-bit, cropped at the bottom
+                write_exp_golomb_ue(&mut bit_writer, 0);
+                bit_writer.write(16, 1920u16).unwrap();
+                bit_writer.write(16, 1080u16).unwrap();
+                bit_writer.write_bit(true).unwrap();
+                bit_writer.write(2, 1u8).unwrap();
+                bit_writer.write(8, 0u8).unwrap();
+                bit_writer.write_bit(true).unwrap();
+                for offset in [0, 0, 0, 4] {
+                    write_exp_golomb_ue(&mut bit_writer, offset);
+                }
+                // max_one_active_ref_layer_flag = 1, vps_poc_lsb_aligned_flag = 0
+                bit_writer.write(2, 0b10u8).unwrap();
+                // `dpb_size()`: sub_layer_flag_info_present_flag = 0, max_vps_dec_pic_buffering_minus1 = [4, 4],
+                // max_vps_num_reorder_pics = 0, max_vps_latency_increase_plus1 = 0
+                bit_writer.write_bit(false).unwrap();
+                for value in [
+                    max_vps_dec_pic_buffering_minus1,
+                    max_vps_dec_pic_buffering_minus1,
+                    0,
+                    0,
+                ] {
+                    write_exp_golomb_ue(&mut bit_writer, value);
+                }
+                // direct_dep_type_len_minus2 = 0, default_direct_dependency_flag = 0, direct_dependency_type[1][0] = 2
+                write_exp_golomb_ue(&mut bit_writer, 0);
+                bit_writer.write_bit(false).unwrap();
+                bit_writer.write(2, 2u8).unwrap();
+                // vps_non_vui_extension_length = 0, vps_vui_present_flag = 0
+                write_exp_golomb_ue(&mut bit_writer, 0);
+                bit_writer.write_bit(false).unwrap();
+                bit_writer.byte_align().unwrap();
+            }
+            bytes
+        };
+        let bytes = vps_extension_bytes(4);
+
+        let profile_tier_level = ProfileTierLevel {
+            general: ProfileTierLevelCommon {
+                profile_idc: 1,
+                level_idc: Some(120),
+                ..Default::default()
+            },
+            sub_layers: [None; 6],
+        };
+        let mut layer_set_layer_id_list = vec![vec![0], vec![0, 1]];
+
+        let mut bit_reader = BitReader::endian(bytes.as_slice(), BigEndian);
+        let vps_extension = VpsExtension::from_bit_reader(
+            &mut bit_reader,
+            true,
+            1,
+            0,
+            &profile_tier_level,
+            &mut layer_set_layer_id_list,
+        )
+        .unwrap();
+
+        assert_eq!(vps_extension.num_layers(), 2);
+        assert_eq!(vps_extension.layer_idx_in_vps(1), Some(1));
+        assert_eq!(vps_extension.view_order_idx(1), 1);
+        assert_eq!(vps_extension.view_id(0), 0);
+        assert_eq!(vps_extension.view_id(1), 1);
+        assert!(vps_extension.dependency_flag[1][0]);
+        assert_eq!(vps_extension.num_direct_ref_layers(1), 1);
+        assert_eq!(vps_extension.num_independent_layers(), 1);
+
+        assert_eq!(vps_extension.profile_tier_levels.len(), 3);
+        assert_eq!(vps_extension.profile_tier_levels[1].general.profile_idc, 1);
+        assert_eq!(
+            vps_extension.profile_tier_levels[1].general.level_idc,
+            Some(93)
+        );
+        assert_eq!(vps_extension.profile_tier_levels[2].general.profile_idc, 6);
+
+        assert_eq!(layer_set_layer_id_list.len(), 2);
+        assert_eq!(vps_extension.output_layer_sets.len(), 2);
+        let output_layer_set = &vps_extension.output_layer_sets[1];
+        assert_eq!(output_layer_set.layer_set_idx, 1);
+        assert_eq!(output_layer_set.output_layer_flag, [true, true]);
+        assert_eq!(output_layer_set.profile_tier_level_idx, [1, 2]);
+        assert_eq!(
+            output_layer_set.max_vps_dec_pic_buffering_minus1,
+            [[4], [4]]
+        );
+
+        assert_eq!(vps_extension.rep_formats.len(), 1);
+        assert_eq!(
+            vps_extension.rep_formats[0].pic_width_vps_in_luma_samples,
+            1920
+        );
+        assert_eq!(
+            vps_extension.rep_formats[0]
+                .conformance_window_vps
+                .unwrap()
+                .conf_win_bottom_offset,
+            4
+        );
+        assert_eq!(vps_extension.vps_rep_format_idx, [0, 0]);
+        assert!(vps_extension.max_one_active_ref_layer_flag);
+        assert_eq!(vps_extension.direct_dependency_type[1][0], 2);
+        assert!(!vps_extension.vps_vui_present_flag);
+
+        // A layer set with a `nuh_layer_id` of no layer.
+        let mut layer_set_layer_id_list = vec![vec![0], vec![0, 2]];
+        let mut bit_reader = BitReader::endian(bytes.as_slice(), BigEndian);
+        let err = VpsExtension::from_bit_reader(
+            &mut bit_reader,
+            true,
+            1,
+            0,
+            &profile_tier_level,
+            &mut layer_set_layer_id_list,
+        )
+        .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        // max_vps_dec_pic_buffering_minus1 = 256 would wrap around to 0 as a `u8`.
+        let bytes = vps_extension_bytes(256);
+        let mut layer_set_layer_id_list = vec![vec![0], vec![0, 1]];
+        let mut bit_reader = BitReader::endian(bytes.as_slice(), BigEndian);
+        let err = VpsExtension::from_bit_reader(
+            &mut bit_reader,
+            true,
+            1,
+            0,
+            &profile_tier_level,
+            &mut layer_set_layer_id_list,
+        )
+        .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}